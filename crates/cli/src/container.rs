@@ -7,6 +7,12 @@ pub enum ContainerCommands {
     Logs {
         #[arg(value_name = "CONTAINER_NAME", help = "Managed container to inspect")]
         container: String,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            help = "Package instance the container belongs to"
+        )]
+        instance: Option<String>,
         #[arg(
             long = "tail",
             value_name = "LINES",
@@ -19,13 +25,18 @@ pub enum ContainerCommands {
 impl ContainerCommands {
     pub async fn execute(self) -> Result<()> {
         match self {
-            Self::Logs { container, tail } => logs(container, tail).await,
+            Self::Logs {
+                container,
+                instance,
+                tail,
+            } => logs(container, instance, tail).await,
         }
     }
 }
 
-async fn logs(container: String, tail: Option<usize>) -> Result<()> {
-    let logs = kittynode_core::docker::get_container_logs(&container, tail).await?;
+async fn logs(container: String, instance: Option<String>, tail: Option<usize>) -> Result<()> {
+    let logs =
+        kittynode_core::docker::get_container_logs(&container, instance.as_deref(), tail).await?;
     for line in logs {
         println!("{line}");
     }
@@ -1,7 +1,14 @@
 use clap::{Subcommand, ValueEnum};
 use eyre::{Result, WrapErr, eyre};
-use kittynode_core::packages::{Package, PackageConfig, RuntimeStatus};
+use kittynode_core::docker::PullProgress;
+use kittynode_core::ethereum::NodeHealth;
+use kittynode_core::packages::{
+    ContainerHealth, Package, PackageConfig, RuntimeStatus, instance_name,
+};
 use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+
+const PROGRESS_BAR_WIDTH: usize = 30;
 
 #[derive(Subcommand)]
 pub enum PackageCommands {
@@ -13,29 +20,91 @@ pub enum PackageCommands {
     Install {
         #[arg(value_name = "PACKAGE_NAME", help = "Name of the package to install")]
         name: String,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            help = "Named instance to install instead of the default one"
+        )]
+        instance: Option<String>,
         #[arg(
             long = "network",
             value_name = "NETWORK",
             help = "Select the network for supported packages"
         )]
         network: Option<EthereumNetwork>,
+        #[arg(
+            long = "dry-run",
+            help = "Show what would change without changing anything"
+        )]
+        dry_run: bool,
     },
     #[command(about = "Delete a package and optionally remove its Docker images")]
     Delete {
         #[arg(value_name = "PACKAGE_NAME", help = "Name of the package to delete")]
         name: String,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            help = "Named instance to delete instead of the default one"
+        )]
+        instance: Option<String>,
         #[arg(long = "include-images", help = "Remove associated Docker images")]
         include_images: bool,
+        #[arg(
+            long = "dry-run",
+            help = "Show what would change without changing anything"
+        )]
+        dry_run: bool,
+    },
+    #[command(about = "Upgrade a package's container images and roll back on failure")]
+    Upgrade {
+        #[arg(value_name = "PACKAGE_NAME", help = "Name of the package to upgrade")]
+        name: String,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            help = "Named instance to upgrade instead of the default one"
+        )]
+        instance: Option<String>,
+        #[arg(
+            long = "to",
+            value_name = "VERSION",
+            help = "Target version, or CLIENT=VERSION for packages with several clients"
+        )]
+        to: Option<String>,
     },
     #[command(about = "Stop all containers that belong to a package")]
     Stop {
         #[arg(value_name = "PACKAGE_NAME", help = "Name of the package to stop")]
         name: String,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            help = "Named instance to stop instead of the default one"
+        )]
+        instance: Option<String>,
     },
     #[command(about = "Start containers for a previously stopped package")]
     Start {
         #[arg(value_name = "PACKAGE_NAME", help = "Name of the package to start")]
         name: String,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            help = "Named instance to start instead of the default one"
+        )]
+        instance: Option<String>,
+    },
+    #[command(about = "Show a package's containers and, for Ethereum, its sync status and peers")]
+    Status {
+        #[arg(value_name = "PACKAGE_NAME", help = "Name of the package to inspect")]
+        name: String,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            help = "Named instance to inspect instead of the default one"
+        )]
+        instance: Option<String>,
     },
     #[command(about = "Manage package-specific configuration overrides")]
     Config {
@@ -75,6 +144,12 @@ pub(crate) enum PackageConfigCommands {
             help = "Package whose overrides should be shown"
         )]
         name: String,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            help = "Named instance to show instead of the default one"
+        )]
+        instance: Option<String>,
     },
     #[command(name = "set", about = "Set configuration overrides for a package")]
     Set {
@@ -83,6 +158,12 @@ pub(crate) enum PackageConfigCommands {
             help = "Package whose overrides should be updated"
         )]
         name: String,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            help = "Named instance to update instead of the default one"
+        )]
+        instance: Option<String>,
         #[arg(
             long = "value",
             value_name = "KEY=VALUE",
@@ -90,6 +171,11 @@ pub(crate) enum PackageConfigCommands {
             num_args = 0..
         )]
         values: Vec<(String, String)>,
+        #[arg(
+            long = "dry-run",
+            help = "Show what would change without changing anything"
+        )]
+        dry_run: bool,
     },
 }
 
@@ -98,15 +184,30 @@ impl PackageCommands {
         match self {
             Self::Catalog => get_package_catalog().await,
             Self::List => get_installed_packages().await,
-            Self::Install { name, network } => {
-                install_package(name, network.map(EthereumNetwork::as_str)).await
+            Self::Install {
+                name,
+                instance,
+                network,
+                dry_run,
+            } => {
+                install_package(
+                    name,
+                    instance,
+                    network.map(EthereumNetwork::as_str),
+                    dry_run,
+                )
+                .await
             }
             Self::Delete {
                 name,
+                instance,
                 include_images,
-            } => delete_package(name, include_images).await,
-            Self::Stop { name } => stop_package(name).await,
-            Self::Start { name } => start_package(name).await,
+                dry_run,
+            } => delete_package(name, instance, include_images, dry_run).await,
+            Self::Upgrade { name, instance, to } => upgrade_package(name, instance, to).await,
+            Self::Stop { name, instance } => stop_package(name, instance).await,
+            Self::Start { name, instance } => start_package(name, instance).await,
+            Self::Status { name, instance } => package_status(name, instance).await,
             Self::Config { command } => command.execute().await,
         }
     }
@@ -115,8 +216,13 @@ impl PackageCommands {
 impl PackageConfigCommands {
     async fn execute(self) -> Result<()> {
         match self {
-            Self::Show { name } => get_package_config(name).await,
-            Self::Set { name, values } => update_package_config(name, values).await,
+            Self::Show { name, instance } => get_package_config(name, instance).await,
+            Self::Set {
+                name,
+                instance,
+                values,
+                dry_run,
+            } => update_package_config(name, instance, values, dry_run).await,
         }
     }
 }
@@ -168,7 +274,14 @@ async fn get_installed_packages() -> Result<()> {
     Ok(())
 }
 
-async fn install_package(name: String, network: Option<&str>) -> Result<()> {
+async fn install_package(
+    name: String,
+    instance: Option<String>,
+    network: Option<&str>,
+    dry_run: bool,
+) -> Result<()> {
+    let instance = instance.as_deref();
+    let display_name = instance_name(&name, instance);
     if name == "ethereum" && network.is_none() {
         let supported = kittynode_core::ethereum::supported_networks_display("|");
         return Err(eyre!(
@@ -176,52 +289,282 @@ async fn install_package(name: String, network: Option<&str>) -> Result<()> {
         ));
     }
 
-    kittynode_core::packages::install_package_with_network(&name, network)
-        .await
-        .wrap_err_with(|| format!("Failed to install {name}"))?;
-    tracing::info!("installed {name}");
+    if dry_run {
+        let plan = kittynode_core::packages::plan_install_package(&name, instance, network)
+            .await
+            .wrap_err_with(|| format!("Failed to plan install of {display_name}"))?;
+        print!("{plan}");
+        return Ok(());
+    }
+
+    let show_progress = std::io::stderr().is_terminal();
+    let mut progress_bar = PullProgressBar::default();
+    let result = kittynode_core::packages::install_package_with_progress(
+        &name,
+        instance,
+        network,
+        |progress| {
+            if show_progress {
+                progress_bar.update(progress);
+            }
+        },
+    )
+    .await;
+    progress_bar.finish();
+    result.wrap_err_with(|| format!("Failed to install {display_name}"))?;
+    tracing::info!("installed {display_name}");
     Ok(())
 }
 
-async fn delete_package(name: String, include_images: bool) -> Result<()> {
+/// Renders image pulls as a single updating line per image on stderr.
+#[derive(Default)]
+struct PullProgressBar {
+    image: Option<String>,
+    /// Downloaded and total bytes per layer.
+    layers: HashMap<String, (u64, u64)>,
+}
+
+impl PullProgressBar {
+    fn update(&mut self, progress: PullProgress) {
+        if self.image.as_deref() != Some(progress.image.as_str()) {
+            self.finish();
+            self.image = Some(progress.image.clone());
+        }
+
+        if let Some(layer_id) = &progress.layer_id {
+            match (
+                progress.status.as_str(),
+                progress.current_bytes,
+                progress.total_bytes,
+            ) {
+                ("Downloading", Some(current), Some(total)) => {
+                    self.layers.insert(layer_id.clone(), (current, total));
+                }
+                ("Download complete" | "Pull complete", _, _) => {
+                    if let Some((current, total)) = self.layers.get_mut(layer_id) {
+                        *current = *total;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r{}", self.line());
+        let _ = stderr.flush();
+    }
+
+    fn finish(&mut self) {
+        if self.image.take().is_some() {
+            eprintln!();
+        }
+        self.layers.clear();
+    }
+
+    fn line(&self) -> String {
+        let image = self.image.as_deref().unwrap_or_default();
+        let (current, total) =
+            self.layers
+                .values()
+                .fold((0, 0), |(current, total), (layer_current, layer_total)| {
+                    (current + layer_current, total + layer_total)
+                });
+        let filled = if total == 0 {
+            0
+        } else {
+            (current.min(total) * PROGRESS_BAR_WIDTH as u64 / total) as usize
+        };
+        format!(
+            "{image} [{}{}] {} / {}",
+            "#".repeat(filled),
+            "-".repeat(PROGRESS_BAR_WIDTH - filled),
+            format_megabytes(current),
+            format_megabytes(total)
+        )
+    }
+}
+
+fn format_megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / 1_000_000.0)
+}
+
+async fn delete_package(
+    name: String,
+    instance: Option<String>,
+    include_images: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let instance = instance.as_deref();
     let packages = kittynode_core::packages::get_installed_packages()
         .await
         .wrap_err("Failed to list installed packages")?;
-    let Some(package) = packages.iter().find(|pkg| pkg.name() == name) else {
-        println!("Package {name} is not installed");
+    let display_name = instance_name(&name, instance);
+    if !packages.iter().any(|pkg| pkg.name() == display_name) {
+        println!("Package {display_name} is not installed");
         return Ok(());
-    };
-    let resolved_name = package.name();
+    }
+
+    if dry_run {
+        let plan = kittynode_core::packages::plan_delete_package(&name, instance, include_images)
+            .await
+            .wrap_err_with(|| format!("Failed to plan delete of {display_name}"))?;
+        print!("{plan}");
+        return Ok(());
+    }
+
+    kittynode_core::packages::delete_package(&name, instance, include_images)
+        .await
+        .wrap_err_with(|| format!("Failed to delete {display_name}"))?;
+    tracing::info!("deleted {display_name}");
+    Ok(())
+}
+
+async fn upgrade_package(name: String, instance: Option<String>, to: Option<String>) -> Result<()> {
+    let instance = instance.as_deref();
+    let display_name = instance_name(&name, instance);
+    let upgrades = kittynode_core::packages::upgrade_package(&name, instance, to.as_deref())
+        .await
+        .wrap_err_with(|| format!("Failed to upgrade {display_name}"))?;
+    if upgrades.is_empty() {
+        println!("{display_name} is already up to date");
+        return Ok(());
+    }
+    for upgrade in &upgrades {
+        let from = upgrade.from_image.as_deref().unwrap_or("unknown");
+        println!("{}: {from} -> {}", upgrade.name, upgrade.to_image);
+    }
+    tracing::info!("upgraded {display_name}");
+    Ok(())
+}
 
-    kittynode_core::packages::delete_package(resolved_name, include_images)
+async fn stop_package(name: String, instance: Option<String>) -> Result<()> {
+    let instance = instance.as_deref();
+    let display_name = instance_name(&name, instance);
+    kittynode_core::packages::stop_package(&name, instance)
         .await
-        .wrap_err_with(|| format!("Failed to delete {resolved_name}"))?;
-    tracing::info!("deleted {resolved_name}");
+        .wrap_err_with(|| format!("Failed to stop {display_name}"))?;
+    tracing::info!("stopped {display_name}");
     Ok(())
 }
 
-async fn stop_package(name: String) -> Result<()> {
-    kittynode_core::packages::stop_package(&name)
+async fn start_package(name: String, instance: Option<String>) -> Result<()> {
+    let instance = instance.as_deref();
+    let display_name = instance_name(&name, instance);
+    kittynode_core::packages::start_package(&name, instance)
         .await
-        .wrap_err_with(|| format!("Failed to stop {name}"))?;
-    tracing::info!("stopped {name}");
+        .wrap_err_with(|| format!("Failed to start {display_name}"))?;
+    tracing::info!("started {display_name}");
     Ok(())
 }
 
-async fn start_package(name: String) -> Result<()> {
-    kittynode_core::packages::start_package(&name)
+async fn package_status(name: String, instance: Option<String>) -> Result<()> {
+    let instance = instance.as_deref();
+    let display_name = instance_name(&name, instance);
+    let state = kittynode_core::packages::get_package(&name, instance)
         .await
-        .wrap_err_with(|| format!("Failed to start {name}"))?;
-    tracing::info!("started {name}");
+        .wrap_err_with(|| format!("Failed to get the status of {display_name}"))?;
+    let runtime = match state.runtime {
+        RuntimeStatus::Running => "running",
+        RuntimeStatus::PartiallyRunning => "partially running",
+        RuntimeStatus::NotRunning => "not running",
+    };
+    println!("{display_name} [status: {runtime}]");
+    for container in &state.containers {
+        let status = match (container.running, container.health) {
+            (false, _) => "stopped",
+            (true, ContainerHealth::Unhealthy) => "running, unhealthy",
+            (true, _) => "running",
+        };
+        println!("  {} [{status}]", container.name);
+    }
+    for missing in &state.missing_containers {
+        println!("  {missing} [missing]");
+    }
+
+    if name == "ethereum" && state.config_present {
+        match kittynode_core::ethereum::node_health(instance).await {
+            Ok(health) => print!("{}", render_node_health(&health)),
+            Err(error) => println!("Node health unavailable: {error}"),
+        }
+    }
     Ok(())
 }
 
-async fn get_package_config(name: String) -> Result<()> {
-    let config = kittynode_core::packages::get_package_config(&name).await?;
+fn render_node_health(health: &NodeHealth) -> String {
+    let mut lines = Vec::new();
+    let location = |external: bool| if external { "external" } else { "local" };
+    let optional =
+        |value: Option<u64>| value.map_or_else(|| "unknown".to_string(), |v| v.to_string());
+
+    let execution = &health.execution;
+    lines.push(format!(
+        "Execution client ({}, {}):",
+        location(execution.external),
+        execution.endpoint
+    ));
+    if execution.reachable {
+        let sync = match (execution.syncing, execution.highest_block) {
+            (Some(true), Some(highest)) => format!("syncing to block {highest}"),
+            (Some(true), None) => "syncing".to_string(),
+            (Some(false), _) => "synced".to_string(),
+            (None, _) => "unknown".to_string(),
+        };
+        lines.push(format!("  Sync: {sync}"));
+        lines.push(format!("  Block: {}", optional(execution.current_block)));
+        lines.push(format!("  Peers: {}", optional(execution.peer_count)));
+    } else {
+        lines.push("  Unreachable".to_string());
+    }
+    if let Some(error) = &execution.error {
+        lines.push(format!("  Error: {error}"));
+    }
+
+    let consensus = &health.consensus;
+    lines.push(format!(
+        "Consensus client ({}, {}):",
+        location(consensus.external),
+        consensus.endpoint
+    ));
+    if consensus.reachable {
+        let sync = match (consensus.syncing, consensus.sync_distance) {
+            (Some(true), Some(distance)) => format!("syncing, {distance} slots behind"),
+            (Some(true), None) => "syncing".to_string(),
+            (Some(false), _) => "synced".to_string(),
+            (None, _) => "unknown".to_string(),
+        };
+        lines.push(format!("  Sync: {sync}"));
+        lines.push(format!("  Head slot: {}", optional(consensus.head_slot)));
+        lines.push(format!("  Peers: {}", optional(consensus.peer_count)));
+        if consensus.execution_offline == Some(true) {
+            lines.push("  Execution client offline".to_string());
+        }
+    } else {
+        lines.push("  Unreachable".to_string());
+    }
+    if let Some(error) = &consensus.error {
+        lines.push(format!("  Error: {error}"));
+    }
+
+    let diagnostics = health.diagnostics();
+    if !diagnostics.is_empty() {
+        lines.push("Diagnostics:".to_string());
+        lines.extend(
+            diagnostics
+                .iter()
+                .map(|diagnostic| format!("  - {diagnostic}")),
+        );
+    }
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
+
+async fn get_package_config(name: String, instance: Option<String>) -> Result<()> {
+    let instance = instance.as_deref();
+    let display_name = instance_name(&name, instance);
+    let config = kittynode_core::packages::get_package_config(&name, instance).await?;
     if config.values.is_empty() {
-        println!("No overrides set for {name}");
+        println!("No overrides set for {display_name}");
     } else {
-        println!("Overrides for {name}:");
+        println!("Overrides for {display_name}:");
         for (key, value) in &config.values {
             println!("  {key}={value}");
         }
@@ -229,14 +572,28 @@ async fn get_package_config(name: String) -> Result<()> {
     Ok(())
 }
 
-async fn update_package_config(name: String, values: Vec<(String, String)>) -> Result<()> {
+async fn update_package_config(
+    name: String,
+    instance: Option<String>,
+    values: Vec<(String, String)>,
+    dry_run: bool,
+) -> Result<()> {
+    let instance = instance.as_deref();
+    let display_name = instance_name(&name, instance);
     let config = PackageConfig {
         values: values.into_iter().collect(),
     };
-    kittynode_core::packages::update_package_config(&name, config)
+    if dry_run {
+        let plan = kittynode_core::packages::plan_update_package_config(&name, instance, &config)
+            .await
+            .wrap_err_with(|| format!("Failed to plan config update for {display_name}"))?;
+        print!("{plan}");
+        return Ok(());
+    }
+    kittynode_core::packages::update_package_config(&name, instance, config)
         .await
-        .wrap_err_with(|| format!("Failed to update config for {name}"))?;
-    tracing::info!("updated config for {name}");
+        .wrap_err_with(|| format!("Failed to update config for {display_name}"))?;
+    tracing::info!("updated config for {display_name}");
     Ok(())
 }
 
@@ -254,7 +611,9 @@ fn parse_key_val(input: &str) -> Result<(String, String), String> {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_key_val;
+    use super::{PullProgressBar, parse_key_val, render_node_health};
+    use kittynode_core::docker::PullProgress;
+    use kittynode_core::ethereum::{ConsensusHealth, ExecutionHealth, NodeHealth};
 
     #[test]
     fn parse_key_val_returns_trimmed_pair() {
@@ -279,4 +638,71 @@ mod tests {
         let error = parse_key_val(" =value").expect_err("empty key should error");
         assert_eq!(error, "key cannot be empty");
     }
+
+    #[test]
+    fn node_health_lists_sync_state_and_diagnostics() {
+        let health = NodeHealth {
+            execution: ExecutionHealth {
+                endpoint: "http://127.0.0.1:8545".to_string(),
+                reachable: true,
+                syncing: Some(false),
+                current_block: Some(1200),
+                peer_count: Some(0),
+                ..Default::default()
+            },
+            consensus: ConsensusHealth {
+                endpoint: "http://beacon.example:5052".to_string(),
+                external: true,
+                ..Default::default()
+            },
+        };
+
+        let rendered = render_node_health(&health);
+        assert!(rendered.contains("Execution client (local, http://127.0.0.1:8545):"));
+        assert!(rendered.contains("  Sync: synced\n  Block: 1200\n  Peers: 0\n"));
+        assert!(
+            rendered.contains(
+                "Consensus client (external, http://beacon.example:5052):\n  Unreachable"
+            )
+        );
+        assert!(rendered.contains("  - Execution client has no peers"));
+    }
+
+    fn progress(
+        layer: &str,
+        status: &str,
+        current: Option<u64>,
+        total: Option<u64>,
+    ) -> PullProgress {
+        PullProgress {
+            image: "sigp/lighthouse:v8.1.3".to_string(),
+            layer_id: Some(layer.to_string()),
+            status: status.to_string(),
+            current_bytes: current,
+            total_bytes: total,
+        }
+    }
+
+    #[test]
+    fn pull_progress_bar_sums_layers() {
+        let mut bar = PullProgressBar::default();
+        bar.update(progress(
+            "a",
+            "Downloading",
+            Some(5_000_000),
+            Some(10_000_000),
+        ));
+        bar.update(progress("b", "Downloading", Some(0), Some(10_000_000)));
+        assert_eq!(
+            bar.line(),
+            format!(
+                "sigp/lighthouse:v8.1.3 [{}{}] 5.0 MB / 20.0 MB",
+                "#".repeat(7),
+                "-".repeat(23)
+            )
+        );
+
+        bar.update(progress("b", "Download complete", None, None));
+        assert!(bar.line().ends_with("15.0 MB / 20.0 MB"), "{}", bar.line());
+    }
 }
@@ -1,6 +1,8 @@
 use clap::Subcommand;
+use dialoguer::{Confirm, theme::ColorfulTheme};
 use eyre::Result;
 use kittynode_core::node::{OperationalMode, OperationalState};
+use kittynode_core::packages::{OrphanedResource, ResourceKind};
 use kittynode_core::system::SystemInfo;
 use std::fmt::Write;
 
@@ -16,6 +18,23 @@ pub enum SystemCommands {
         about = "Show overall operational status and readiness flags"
     )]
     State,
+    #[command(
+        name = "gc",
+        about = "List Kittynode containers, volumes and networks no installed package uses"
+    )]
+    Gc {
+        #[arg(
+            long = "remove",
+            help = "Remove the orphaned containers and networks, and the volumes you confirm"
+        )]
+        remove: bool,
+        #[arg(
+            long = "yes",
+            requires = "remove",
+            help = "Remove the listed orphaned volumes without asking about each one"
+        )]
+        yes: bool,
+    },
 }
 
 impl SystemCommands {
@@ -23,6 +42,7 @@ impl SystemCommands {
         match self {
             Self::Info => system_info().await,
             Self::State => operational_state().await,
+            Self::Gc { remove, yes } => collect_garbage(remove, yes).await,
         }
     }
 }
@@ -39,6 +59,96 @@ async fn operational_state() -> Result<()> {
     Ok(())
 }
 
+async fn collect_garbage(remove: bool, yes: bool) -> Result<()> {
+    let orphans = kittynode_core::packages::find_orphaned_resources().await?;
+    if !remove {
+        print!("{}", render_orphans(&orphans));
+        return Ok(());
+    }
+
+    let volumes: Vec<&OrphanedResource> = orphans
+        .iter()
+        .filter(|orphan| orphan.kind == ResourceKind::Volume && !orphan.protected)
+        .collect();
+    let confirmed = if yes {
+        if !volumes.is_empty() {
+            print!("{}", render_resource_list("Removing volumes", &volumes));
+        }
+        volumes.iter().map(|volume| volume.name.clone()).collect()
+    } else {
+        confirm_volume_removal(&volumes)?
+    };
+
+    let removed = kittynode_core::packages::remove_orphaned_resources(&confirmed).await?;
+    let kept: Vec<&OrphanedResource> = orphans
+        .iter()
+        .filter(|orphan| orphan.kind == ResourceKind::Volume && !confirmed.contains(&orphan.name))
+        .collect();
+    print!("{}", render_removal(&removed, &kept));
+    Ok(())
+}
+
+/// Asks about each orphaned volume, since removing one deletes its data for good.
+fn confirm_volume_removal(volumes: &[&OrphanedResource]) -> Result<Vec<String>> {
+    let theme = ColorfulTheme::default();
+    let mut confirmed = Vec::new();
+    for volume in volumes {
+        let remove = Confirm::with_theme(&theme)
+            .with_prompt(format!("Remove {volume}? Its data is deleted for good"))
+            .default(false)
+            .interact()?;
+        if remove {
+            confirmed.push(volume.name.clone());
+        }
+    }
+    Ok(confirmed)
+}
+
+fn render_orphans(orphans: &[OrphanedResource]) -> String {
+    if orphans.is_empty() {
+        return "No orphaned Kittynode resources found\n".to_string();
+    }
+    let mut output =
+        render_resource_list("Orphaned resources", &orphans.iter().collect::<Vec<_>>());
+    writeln!(
+        output,
+        "Run `kittynode system gc --remove` to remove them; each volume is confirmed first"
+    )
+    .expect("writing to string cannot fail");
+    output
+}
+
+fn render_removal(removed: &[OrphanedResource], kept: &[&OrphanedResource]) -> String {
+    if removed.is_empty() && kept.is_empty() {
+        return "No orphaned Kittynode resources found\n".to_string();
+    }
+    let mut output = String::new();
+    if !removed.is_empty() {
+        output.push_str(&render_resource_list(
+            "Removed resources",
+            &removed.iter().collect::<Vec<_>>(),
+        ));
+    }
+    if !kept.is_empty() {
+        output.push_str(&render_resource_list("Kept volumes", kept));
+    }
+    output
+}
+
+fn render_resource_list(heading: &str, resources: &[&OrphanedResource]) -> String {
+    let mut output = String::new();
+    writeln!(output, "{heading}:").expect("writing to string cannot fail");
+    for resource in resources {
+        let note = if resource.protected {
+            " [validator keys and slashing protection; never removed]"
+        } else {
+            ""
+        };
+        writeln!(output, "  - {resource}{note}").expect("writing to string cannot fail");
+    }
+    output
+}
+
 fn render_system_info(info: &SystemInfo) -> String {
     let mut output = String::new();
     writeln!(
@@ -85,8 +195,9 @@ fn render_operational_state(state: &OperationalState) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{render_operational_state, render_system_info};
+    use super::{render_operational_state, render_orphans, render_removal, render_system_info};
     use kittynode_core::node::{OperationalMode, OperationalState};
+    use kittynode_core::packages::{OrphanedResource, ResourceKind};
     use kittynode_core::system::SystemInfo;
     use serde_json::json;
 
@@ -199,4 +310,47 @@ mod tests {
         );
         assert_eq!(rendered.lines().count(), 3);
     }
+
+    #[test]
+    fn render_orphans_lists_resources_and_hints_at_removal() {
+        let orphans = vec![OrphanedResource {
+            kind: ResourceKind::Volume,
+            name: "kittynode-rethdata".into(),
+            package: Some("ethereum".into()),
+            network: Some("hoodi".into()),
+            protected: false,
+        }];
+
+        let expected = "Orphaned resources:\n  - volume 'kittynode-rethdata' (package ethereum, network hoodi)\nRun `kittynode system gc --remove` to remove them; each volume is confirmed first\n";
+        assert_eq!(render_orphans(&orphans), expected);
+        assert_eq!(
+            render_orphans(&[]),
+            "No orphaned Kittynode resources found\n"
+        );
+    }
+
+    #[test]
+    fn render_removal_lists_kept_volumes() {
+        let removed = vec![OrphanedResource {
+            kind: ResourceKind::Network,
+            name: "kittynode-ethereum-network".into(),
+            package: None,
+            network: None,
+            protected: false,
+        }];
+        let kept = OrphanedResource {
+            kind: ResourceKind::Volume,
+            name: "kittynode-lighthouse-data".into(),
+            package: None,
+            network: None,
+            protected: true,
+        };
+
+        let expected = "Removed resources:\n  - network 'kittynode-ethereum-network'\nKept volumes:\n  - volume 'kittynode-lighthouse-data' [validator keys and slashing protection; never removed]\n";
+        assert_eq!(render_removal(&removed, &[&kept]), expected);
+        assert_eq!(
+            render_removal(&[], &[]),
+            "No orphaned Kittynode resources found\n"
+        );
+    }
 }
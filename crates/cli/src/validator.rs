@@ -1,8 +1,8 @@
-use clap::Subcommand;
+use clap::{Args, Subcommand};
 use std::{
     collections::HashMap,
     io::{self, Write, stdout},
-    path::{Path, PathBuf},
+    path::PathBuf,
     time::{Duration, Instant},
 };
 
@@ -26,27 +26,27 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Paragraph, Wrap},
 };
-use tokio::{
-    io::{AsyncWrite, AsyncWriteExt},
-    runtime::Handle,
-};
+use tokio::runtime::Handle;
 use tracing::error;
 use zeroize::Zeroizing;
 
-use kittynode_core::docker::get_docker;
 use kittynode_core::docker::is_docker_running;
 use kittynode_core::ethereum::{
-    LIGHTHOUSE_DATA_DIR, LIGHTHOUSE_DATA_VOLUME, LIGHTHOUSE_VALIDATOR_CONTAINER_NAME,
+    deposited_validators, import_validator_keys, submit_bls_to_execution_changes,
+    submit_voluntary_exit, validator_status,
 };
 use kittynode_core::packages::{PackageConfig, install_package, update_package_config};
 #[cfg(target_os = "linux")]
 use kittynode_core::validator::swap_active;
 use kittynode_core::validator::{
-    EPHEMERY_NETWORK_NAME, ValidatorKeygenOutcome, ValidatorKeygenRequest, ValidatorProgress,
-    available_networks, check_internet_connectivity, ensure_ephemery_config, format_eth_from_gwei,
+    BlsToExecutionChangeRequest, DepositVerificationRequest, EPHEMERY_NETWORK_NAME, TopUpKeys,
+    TopUpRequest, ValidatorKeygenOutcome, ValidatorKeygenRequest, ValidatorProgress,
+    VoluntaryExitRequest, available_networks, check_internet_connectivity,
+    derive_validator_pubkeys, format_eth_from_gwei, generate_top_up_deposits,
     generate_validator_files_with_progress, normalize_withdrawal_address,
     parse_deposit_amount_gwei, parse_validator_count, resolve_withdrawal_address,
-    validate_endpoint_url, validate_password,
+    sign_bls_to_execution_changes, sign_voluntary_exit, validate_endpoint_url, validate_password,
+    verify_deposit_data, write_signed_voluntary_exit,
 };
 
 #[derive(Subcommand)]
@@ -55,6 +55,194 @@ pub enum ValidatorCommands {
     Keygen,
     #[command(name = "init", about = "Initialize the validator setup workflow")]
     Init,
+    #[command(
+        name = "status",
+        about = "Show the beacon chain status and balance of validator keys"
+    )]
+    Status {
+        #[arg(
+            value_name = "KEYS_PATH",
+            help = "Directory of keystores or deposit data files, or a single such file"
+        )]
+        keys: PathBuf,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            help = "Named Ethereum instance whose beacon node to query"
+        )]
+        instance: Option<String>,
+    },
+    #[command(
+        name = "exit",
+        about = "Sign a voluntary exit and submit it or write it to a file"
+    )]
+    Exit {
+        #[arg(
+            value_name = "KEYSTORE",
+            help = "EIP-2335 keystore of the validator to exit"
+        )]
+        keystore: PathBuf,
+        #[arg(
+            long = "index",
+            value_name = "VALIDATOR_INDEX",
+            help = "Validator index; looked up on the beacon node when submitting"
+        )]
+        index: Option<u64>,
+        #[arg(
+            long = "epoch",
+            value_name = "EPOCH",
+            default_value_t = 0,
+            help = "Earliest epoch the exit can be included in"
+        )]
+        epoch: u64,
+        #[arg(
+            long = "output-dir",
+            value_name = "DIR",
+            help = "Write the signed exit to this directory instead of submitting it"
+        )]
+        output_dir: Option<PathBuf>,
+        #[arg(
+            long = "network",
+            value_name = "NETWORK",
+            requires = "output_dir",
+            help = "Network to sign the exit for when writing it to a file"
+        )]
+        network: Option<String>,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            conflicts_with = "output_dir",
+            help = "Named Ethereum instance whose beacon node receives the exit"
+        )]
+        instance: Option<String>,
+    },
+    #[command(
+        name = "bls-change",
+        about = "Sign changes from BLS (0x00) to execution withdrawal credentials"
+    )]
+    BlsChange {
+        #[arg(
+            long = "validator-indices",
+            value_name = "INDICES",
+            value_delimiter = ',',
+            required = true,
+            help = "Comma-separated beacon chain indices of the validators to change"
+        )]
+        validator_indices: Vec<u64>,
+        #[arg(
+            long = "start-index",
+            value_name = "KEY_INDEX",
+            default_value_t = 0,
+            help = "Key index in the mnemonic of the first validator; the rest follow in order"
+        )]
+        start_index: u32,
+        #[arg(
+            long = "execution-address",
+            value_name = "ADDRESS",
+            help = "Execution address to withdraw to, which cannot be changed again"
+        )]
+        execution_address: String,
+        #[arg(
+            long = "output-dir",
+            value_name = "DIR",
+            help = "Write the signed changes to this directory instead of submitting them"
+        )]
+        output_dir: Option<PathBuf>,
+        #[arg(
+            long = "network",
+            value_name = "NETWORK",
+            requires = "output_dir",
+            help = "Network to sign the changes for when writing them to a file"
+        )]
+        network: Option<String>,
+        #[arg(
+            long = "instance",
+            value_name = "INSTANCE",
+            conflicts_with = "output_dir",
+            help = "Named Ethereum instance whose beacon node receives the changes"
+        )]
+        instance: Option<String>,
+    },
+    #[command(
+        name = "verify-deposits",
+        about = "Check a deposit data file before sending its deposits"
+    )]
+    VerifyDeposits {
+        #[arg(value_name = "FILE", help = "deposit_data-*.json file to check")]
+        file: PathBuf,
+        #[arg(
+            long = "network",
+            value_name = "NETWORK",
+            help = "Network the deposits are for; defaults to the one the file records"
+        )]
+        network: Option<String>,
+        #[arg(
+            long = "withdrawal-address",
+            value_name = "ADDRESS",
+            help = "Execution address every deposit must withdraw to"
+        )]
+        withdrawal_address: Option<String>,
+    },
+    #[command(
+        name = "top-up",
+        about = "Sign deposit data that adds ETH to existing validators"
+    )]
+    TopUp(TopUpArgs),
+}
+
+#[derive(Args)]
+pub struct TopUpArgs {
+    #[arg(
+        long = "network",
+        value_name = "NETWORK",
+        help = "Network the validators run on"
+    )]
+    network: String,
+    #[arg(
+        long = "amount",
+        value_name = "ETH",
+        help = "ETH to deposit to each validator"
+    )]
+    amount: String,
+    #[arg(
+        long = "keystore",
+        value_name = "KEYSTORE",
+        conflicts_with_all = ["start_index", "count"],
+        help = "EIP-2335 keystore of the validator; prompts for a mnemonic when omitted"
+    )]
+    keystore: Option<PathBuf>,
+    #[arg(
+        long = "start-index",
+        value_name = "KEY_INDEX",
+        default_value_t = 0,
+        help = "Key index in the mnemonic of the first validator to top up"
+    )]
+    start_index: u32,
+    #[arg(
+        long = "count",
+        value_name = "COUNT",
+        default_value_t = 1,
+        help = "Number of consecutive validators to top up"
+    )]
+    count: u16,
+    #[arg(
+        long = "withdrawal-address",
+        value_name = "ADDRESS",
+        help = "Withdrawal address of the validators; defaults to the mnemonic's first address"
+    )]
+    withdrawal_address: Option<String>,
+    #[arg(
+        long = "execution-credentials",
+        help = "The validators have 0x01 execution credentials instead of 0x02 compounding ones"
+    )]
+    execution_credentials: bool,
+    #[arg(
+        long = "output-dir",
+        value_name = "DIR",
+        default_value = "./validator-keys",
+        help = "Directory to write the deposit data file to"
+    )]
+    output_dir: PathBuf,
 }
 
 impl ValidatorCommands {
@@ -62,13 +250,43 @@ impl ValidatorCommands {
         match self {
             Self::Keygen => keygen(None).map(|_| ()),
             Self::Init => init().await,
+            Self::Status { keys, instance } => status(keys, instance).await,
+            Self::Exit {
+                keystore,
+                index,
+                epoch,
+                output_dir,
+                network,
+                instance,
+            } => exit(keystore, index, epoch, output_dir, network, instance).await,
+            Self::BlsChange {
+                validator_indices,
+                start_index,
+                execution_address,
+                output_dir,
+                network,
+                instance,
+            } => {
+                bls_change(
+                    validator_indices,
+                    start_index,
+                    execution_address,
+                    output_dir,
+                    network,
+                    instance,
+                )
+                .await
+            }
+            Self::VerifyDeposits {
+                file,
+                network,
+                withdrawal_address,
+            } => verify_deposits(file, network, withdrawal_address),
+            Self::TopUp(args) => top_up(args),
         }
     }
 }
 
-/// Lighthouse validator container name shared across the CLI.
-pub const VALIDATOR_CONTAINER_NAME: &str = LIGHTHOUSE_VALIDATOR_CONTAINER_NAME;
-
 fn desired_supported_networks() -> Vec<&'static str> {
     const DESIRED: &[&str] = &[EPHEMERY_NETWORK_NAME, "hoodi", "sepolia"];
     let available = available_networks();
@@ -134,6 +352,33 @@ pub fn keygen(preselected_network: Option<&str>) -> Result<Option<KeygenSummary>
         }
     }
 
+    let use_existing_mnemonic = Select::with_theme(&theme)
+        .with_prompt("Which mnemonic should the keys come from?")
+        .default(0)
+        .items(&["Create a new mnemonic", "Use an existing mnemonic"])
+        .interact()?
+        == 1;
+    let (existing_mnemonic, start_index) = if use_existing_mnemonic {
+        let phrase = Password::with_theme(&theme)
+            .with_prompt("Enter your existing mnemonic")
+            .validate_with(|value: &String| {
+                Mnemonic::from_phrase(&normalize_mnemonic(value), Language::English)
+                    .map(|_| ())
+                    .map_err(|error| error.to_string())
+            })
+            .interact()?;
+        let start_index = Input::<u32>::with_theme(&theme)
+            .with_prompt("Key index of the first validator to generate")
+            .default(0)
+            .interact_text()?;
+        (
+            Some(Zeroizing::new(normalize_mnemonic(&phrase))),
+            start_index,
+        )
+    } else {
+        (None, 0)
+    };
+
     let validator_count_input = Input::<String>::with_theme(&theme)
         .with_prompt("How many validators do you wish to run?")
         .default("1".to_string())
@@ -243,6 +488,9 @@ pub fn keygen(preselected_network: Option<&str>) -> Result<Option<KeygenSummary>
 
     println!("Validator key generation summary:");
     println!("  Validators: {validator_count}");
+    if use_existing_mnemonic {
+        println!("  Mnemonic: existing, from key index {start_index}");
+    }
     println!("  Network: {}", network);
     let withdrawal_summary = withdrawal_address_display
         .as_deref()
@@ -269,20 +517,40 @@ pub fn keygen(preselected_network: Option<&str>) -> Result<Option<KeygenSummary>
         return Ok(None);
     }
 
-    let mnemonic = Mnemonic::new(MnemonicType::Words24, Language::English);
-    let mnemonic_phrase = Zeroizing::new(mnemonic.to_string());
-    drop(mnemonic);
-
-    display_mnemonic_securely(mnemonic_phrase.as_str())?;
-    let mnemonic_verified = validate_mnemonic_once(&theme, mnemonic_phrase.as_str())?;
-    if let Err(error) = clear_clipboard() {
-        error!("Failed to clear system clipboard, mnemonic may remain in clipboard: {error}");
-    }
-    if !mnemonic_verified {
-        println!("✘ Mnemonic verification failed. Aborting validator key generation.");
-        return Ok(None);
-    }
-    println!("Mnemonic successfully verified!");
+    let mnemonic_phrase = match existing_mnemonic {
+        Some(mnemonic_phrase) => {
+            if !confirm_keys_are_new(
+                &theme,
+                &mnemonic_phrase,
+                start_index,
+                validator_count,
+                network,
+            )? {
+                println!("Aborting validator key generation.");
+                return Ok(None);
+            }
+            mnemonic_phrase
+        }
+        None => {
+            let mnemonic = Mnemonic::new(MnemonicType::Words24, Language::English);
+            let mnemonic_phrase = Zeroizing::new(mnemonic.to_string());
+            drop(mnemonic);
+
+            display_mnemonic_securely(mnemonic_phrase.as_str())?;
+            let mnemonic_verified = validate_mnemonic_once(&theme, mnemonic_phrase.as_str())?;
+            if let Err(error) = clear_clipboard() {
+                error!(
+                    "Failed to clear system clipboard, mnemonic may remain in clipboard: {error}"
+                );
+            }
+            if !mnemonic_verified {
+                println!("✘ Mnemonic verification failed. Aborting validator key generation.");
+                return Ok(None);
+            }
+            println!("Mnemonic successfully verified!");
+            mnemonic_phrase
+        }
+    };
 
     let password = Password::with_theme(&theme)
         .with_prompt("Enter a password to secure the keystore")
@@ -308,6 +576,7 @@ pub fn keygen(preselected_network: Option<&str>) -> Result<Option<KeygenSummary>
     let outcome = generate_validator_files_with_progress(
         ValidatorKeygenRequest {
             mnemonic_phrase,
+            start_index,
             validator_count,
             withdrawal_address,
             network: network.to_string(),
@@ -345,10 +614,48 @@ pub fn keygen(preselected_network: Option<&str>) -> Result<Option<KeygenSummary>
     }))
 }
 
+/// Warns when keys about to be generated from an existing mnemonic were already deposited, so
+/// that their deposit data isn't sent again by accident. Returns whether to go ahead.
+fn confirm_keys_are_new(
+    theme: &ColorfulTheme,
+    mnemonic_phrase: &str,
+    start_index: u32,
+    validator_count: u16,
+    network: &str,
+) -> Result<bool> {
+    let pubkeys = derive_validator_pubkeys(mnemonic_phrase, start_index, validator_count)?;
+    let lookup = tokio::task::block_in_place(|| {
+        Handle::current().block_on(deposited_validators(None, network, pubkeys))
+    });
+    match lookup {
+        Ok(deposited) if deposited.is_empty() => return Ok(true),
+        Ok(deposited) => {
+            println!("WARNING: these keys already have deposits on {network}:");
+            for status in &deposited {
+                println!(" - {} ({})", status.pubkey, status.state.as_str());
+            }
+            println!(
+                "Their deposit data would top up the existing validators rather than create new ones."
+            );
+        }
+        Err(error) => {
+            println!("WARNING: could not check the beacon chain for existing deposits: {error}");
+            println!(
+                "These keys may already have deposits on {network}. Run `kittynode validator status` on the deposit data before sending any deposit."
+            );
+        }
+    }
+    Ok(Confirm::with_theme(theme)
+        .with_prompt("Generate keystores and deposit data for them anyway?")
+        .default(false)
+        .interact()?)
+}
+
 const DOCKER_DOCS_URL: &str = "https://docs.kittynode.com/guides/set-up-docker";
 const NETWORK_OPTIONS: [&str; 3] = [EPHEMERY_NETWORK_NAME, "hoodi", "sepolia"];
-const EXECUTION_OPTIONS: [&str; 1] = ["reth (only option, others coming soon)"];
-const CONSENSUS_OPTIONS: [&str; 1] = ["lighthouse (only option, others coming soon)"];
+const EXECUTION_OPTIONS: [&str; 5] = ["reth", "geth", "nethermind", "besu", "erigon"];
+const CONSENSUS_OPTIONS: [&str; 5] = ["lighthouse", "teku", "prysm", "nimbus", "lodestar"];
+const VALIDATOR_OPTIONS: [&str; 4] = ["lighthouse", "teku", "nimbus", "lodestar"];
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum Step {
@@ -358,6 +665,7 @@ enum Step {
     ExternalEndpoints,
     Execution,
     Consensus,
+    Validator,
     Keygen,
     Summary,
     Launch,
@@ -377,6 +685,7 @@ struct InitState {
     network_index: usize,
     execution_index: usize,
     consensus_index: usize,
+    validator_index: usize,
     use_external_nodes: bool,
     external_execution_endpoint: String,
     external_consensus_endpoint: String,
@@ -395,6 +704,7 @@ impl InitState {
             network_index: 0,
             execution_index: 0,
             consensus_index: 0,
+            validator_index: 0,
             use_external_nodes: false,
             external_execution_endpoint: String::new(),
             external_consensus_endpoint: String::new(),
@@ -409,12 +719,311 @@ impl InitState {
     fn network(&self) -> &'static str {
         NETWORK_OPTIONS[self.network_index]
     }
+
+    fn execution_client(&self) -> &'static str {
+        EXECUTION_OPTIONS[self.execution_index]
+    }
+
+    fn consensus_client(&self) -> &'static str {
+        CONSENSUS_OPTIONS[self.consensus_index]
+    }
+
+    fn validator_client(&self) -> &'static str {
+        VALIDATOR_OPTIONS[self.validator_index]
+    }
 }
 
 pub async fn init() -> Result<()> {
     tokio::task::block_in_place(run_init_blocking)
 }
 
+async fn status(keys: PathBuf, instance: Option<String>) -> Result<()> {
+    let statuses = validator_status(instance.as_deref(), &keys).await?;
+    for status in &statuses {
+        println!("{}", status.pubkey);
+        if let Some(index) = status.index {
+            println!("  Index: {index}");
+        }
+        println!("  Status: {}", status.state.as_str());
+        if let Some(balance) = status.balance_gwei {
+            println!("  Balance: {} ETH", format_eth_from_gwei(balance));
+        }
+        if let Some(effective) = status.effective_balance_gwei {
+            println!(
+                "  Effective balance: {} ETH",
+                format_eth_from_gwei(effective)
+            );
+        }
+        if let Some(credentials) = status.withdrawal_credentials {
+            println!("  Withdrawal credentials: {}", credentials.prefix());
+        }
+    }
+    Ok(())
+}
+
+async fn exit(
+    keystore: PathBuf,
+    index: Option<u64>,
+    epoch: u64,
+    output_dir: Option<PathBuf>,
+    network: Option<String>,
+    instance: Option<String>,
+) -> Result<()> {
+    let theme = ColorfulTheme::default();
+
+    if let Some(output_dir) = output_dir {
+        let network = network.ok_or_else(|| eyre!("Pass --network to write the exit to a file"))?;
+        let validator_index =
+            index.ok_or_else(|| eyre!("Pass --index to write the exit to a file"))?;
+        let password = Zeroizing::new(
+            Password::with_theme(&theme)
+                .with_prompt("Enter the keystore password")
+                .interact()?,
+        );
+        let exit = sign_voluntary_exit(VoluntaryExitRequest {
+            keystore_path: keystore,
+            password,
+            network,
+            validator_index,
+            epoch,
+            genesis_validators_root: None,
+        })?;
+        let path = write_signed_voluntary_exit(&exit, &output_dir)?;
+        println!(
+            "Signed exit for validator {validator_index} written to {}",
+            path.display()
+        );
+        println!("Submit it to any beacon node of the network to exit the validator.");
+        return Ok(());
+    }
+
+    println!(
+        "A submitted exit cannot be undone. The validator stops earning rewards once it exits."
+    );
+    let proceed = Confirm::with_theme(&theme)
+        .with_prompt("Exit this validator?")
+        .default(false)
+        .interact()?;
+    if !proceed {
+        println!("Aborting validator exit.");
+        return Ok(());
+    }
+    let password = Zeroizing::new(
+        Password::with_theme(&theme)
+            .with_prompt("Enter the keystore password")
+            .interact()?,
+    );
+    let exit =
+        submit_voluntary_exit(instance.as_deref(), &keystore, password, index, epoch).await?;
+    println!(
+        "Exit for validator {} submitted; track it with `kittynode validator status`",
+        exit.message.validator_index
+    );
+    Ok(())
+}
+
+async fn bls_change(
+    validator_indices: Vec<u64>,
+    start_index: u32,
+    execution_address: String,
+    output_dir: Option<PathBuf>,
+    network: Option<String>,
+    instance: Option<String>,
+) -> Result<()> {
+    let theme = ColorfulTheme::default();
+    let execution_address = normalize_withdrawal_address(&execution_address)?
+        .parse()
+        .map_err(|error| eyre!("Invalid withdrawal address: {error}"))?;
+    if output_dir.is_some() && network.is_none() {
+        return Err(eyre!("Pass --network to write the changes to a file"));
+    }
+
+    let mnemonic_phrase = Zeroizing::new(normalize_mnemonic(
+        &Password::with_theme(&theme)
+            .with_prompt("Enter the mnemonic the validators were created from")
+            .interact()?,
+    ));
+
+    if let (Some(output_dir), Some(network)) = (output_dir, network) {
+        let signed = sign_bls_to_execution_changes(BlsToExecutionChangeRequest {
+            mnemonic_phrase,
+            network,
+            start_index,
+            validator_indices,
+            execution_address,
+            genesis_validators_root: None,
+        })?;
+        let path = signed.write(&output_dir)?;
+        println!(
+            "{} signed change(s) written to {}",
+            signed.changes.len(),
+            path.display()
+        );
+        println!(
+            "Check each from_bls_pubkey against the validator's withdrawal credentials before submitting."
+        );
+        return Ok(());
+    }
+
+    println!(
+        "Withdrawals of validator(s) {} will go to {execution_address}. This cannot be changed again.",
+        validator_indices
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let proceed = Confirm::with_theme(&theme)
+        .with_prompt("Submit the withdrawal credential changes?")
+        .default(false)
+        .interact()?;
+    if !proceed {
+        println!("Aborting withdrawal credential change.");
+        return Ok(());
+    }
+    let signed = submit_bls_to_execution_changes(
+        instance.as_deref(),
+        mnemonic_phrase,
+        start_index,
+        validator_indices,
+        execution_address,
+    )
+    .await?;
+    println!(
+        "{} change(s) submitted; track them with `kittynode validator status`",
+        signed.changes.len()
+    );
+    Ok(())
+}
+
+fn verify_deposits(
+    file: PathBuf,
+    network: Option<String>,
+    withdrawal_address: Option<String>,
+) -> Result<()> {
+    let withdrawal_address = match withdrawal_address {
+        Some(address) => Some(
+            normalize_withdrawal_address(&address)?
+                .parse()
+                .map_err(|error| eyre!("Invalid withdrawal address: {error}"))?,
+        ),
+        None => None,
+    };
+    let verification = verify_deposit_data(DepositVerificationRequest {
+        deposit_data_path: file,
+        network,
+        withdrawal_address,
+    })?;
+
+    println!("Network: {}", verification.network);
+    for deposit in &verification.deposits {
+        println!(
+            "0x{} {} ETH",
+            deposit.pubkey,
+            format_eth_from_gwei(deposit.amount_gwei)
+        );
+        if deposit.problems.is_empty() {
+            println!("  OK");
+        }
+        for problem in &deposit.problems {
+            println!("  {problem}");
+        }
+    }
+
+    let failed = verification
+        .deposits
+        .iter()
+        .filter(|deposit| !deposit.problems.is_empty())
+        .count();
+    if failed > 0 {
+        return Err(eyre!(
+            "{failed} of {} deposits failed verification",
+            verification.deposits.len()
+        ));
+    }
+    println!("All {} deposits verified", verification.deposits.len());
+    Ok(())
+}
+
+fn top_up(args: TopUpArgs) -> Result<()> {
+    let TopUpArgs {
+        network,
+        amount,
+        keystore,
+        start_index,
+        count,
+        withdrawal_address,
+        execution_credentials,
+        output_dir,
+    } = args;
+    let theme = ColorfulTheme::default();
+    let amount_gwei = parse_deposit_amount_gwei(&amount)?;
+    let withdrawal_address = match withdrawal_address {
+        Some(address) => Some(
+            normalize_withdrawal_address(&address)?
+                .parse()
+                .map_err(|error| eyre!("Invalid withdrawal address: {error}"))?,
+        ),
+        None => None,
+    };
+
+    let (keys, withdrawal_address) = match keystore {
+        Some(keystore_path) => {
+            let withdrawal_address = withdrawal_address.ok_or_else(|| {
+                eyre!("Pass --withdrawal-address to top up the validator of a keystore")
+            })?;
+            let password = Zeroizing::new(
+                Password::with_theme(&theme)
+                    .with_prompt("Enter the keystore password")
+                    .interact()?,
+            );
+            (
+                TopUpKeys::Keystore {
+                    keystore_path,
+                    password,
+                },
+                withdrawal_address,
+            )
+        }
+        None => {
+            let mnemonic_phrase = Zeroizing::new(normalize_mnemonic(
+                &Password::with_theme(&theme)
+                    .with_prompt("Enter the mnemonic the validators were created from")
+                    .interact()?,
+            ));
+            let address = match withdrawal_address {
+                Some(address) => address,
+                None => resolve_withdrawal_address(None, &mnemonic_phrase)?,
+            };
+            (
+                TopUpKeys::Mnemonic {
+                    mnemonic_phrase,
+                    start_index,
+                    count,
+                },
+                address,
+            )
+        }
+    };
+
+    let outcome = generate_top_up_deposits(TopUpRequest {
+        keys,
+        network,
+        withdrawal_address,
+        compounding: !execution_credentials,
+        amount_gwei,
+        output_dir,
+    })?;
+    for pubkey in &outcome.pubkeys {
+        println!("{pubkey} +{} ETH", format_eth_from_gwei(amount_gwei));
+    }
+    println!(
+        "Top-up deposit data written to {}",
+        outcome.deposit_data_path.display()
+    );
+    Ok(())
+}
+
 struct RawTerminalGuard;
 
 impl Drop for RawTerminalGuard {
@@ -534,7 +1143,7 @@ fn handle_event(
                         }
                         EndpointInputMode::Consensus => {
                             state.external_consensus_endpoint = state.endpoint_input_buffer.clone();
-                            state.step = Step::Keygen;
+                            state.step = Step::Validator;
                             state.endpoint_input_buffer.clear();
                             state.status = None;
                         }
@@ -567,6 +1176,17 @@ fn handle_event(
             KeyCode::Down if state.consensus_index + 1 < CONSENSUS_OPTIONS.len() => {
                 state.consensus_index += 1;
             }
+            KeyCode::Enter => {
+                state.step = Step::Validator;
+                state.status = None;
+            }
+            _ => {}
+        },
+        Step::Validator => match key.code {
+            KeyCode::Up if state.validator_index > 0 => state.validator_index -= 1,
+            KeyCode::Down if state.validator_index + 1 < VALIDATOR_OPTIONS.len() => {
+                state.validator_index += 1;
+            }
             KeyCode::Enter => {
                 state.step = Step::Keygen;
                 state.status = None;
@@ -613,15 +1233,7 @@ fn handle_event(
             if matches!(key.code, KeyCode::Enter)
                 && let Some(summary) = state.keygen_summary.as_ref()
             {
-                match run_launch_flow(
-                    terminal,
-                    handle,
-                    summary,
-                    state.network(),
-                    state.use_external_nodes,
-                    &state.external_execution_endpoint,
-                    &state.external_consensus_endpoint,
-                ) {
+                match run_launch_flow(terminal, handle, summary, state) {
                     Ok(()) => {
                         state.status = Some("Clients started successfully.".to_string());
                         state.step = Step::Deposit;
@@ -731,6 +1343,12 @@ fn render(frame: &mut Frame, state: &InitState) {
             lines.push(Line::from(""));
             lines.extend(option_lines(state.consensus_index, &CONSENSUS_OPTIONS));
         }
+        Step::Validator => {
+            lines.push(Line::styled("Select a validator client", title_style));
+            lines.push(Line::from("Use ↑/↓ and press Enter."));
+            lines.push(Line::from(""));
+            lines.extend(option_lines(state.validator_index, &VALIDATOR_OPTIONS));
+        }
         Step::Keygen => {
             lines.push(Line::styled("Generate validator keys", title_style));
             lines.push(Line::from(
@@ -765,10 +1383,21 @@ fn render(frame: &mut Frame, state: &InitState) {
         }
         Step::Launch => {
             lines.push(Line::styled("Start clients", title_style));
-            lines.push(Line::from(format!(
-                "This will configure Reth and Lighthouse for {} and import your keys.",
-                state.network()
-            )));
+            if state.use_external_nodes {
+                lines.push(Line::from(format!(
+                    "This will configure the {} validator client for {} and import your keys.",
+                    state.validator_client(),
+                    state.network()
+                )));
+            } else {
+                lines.push(Line::from(format!(
+                    "This will configure {} and {} with the {} validator client for {} and import your keys.",
+                    state.execution_client(),
+                    state.consensus_client(),
+                    state.validator_client(),
+                    state.network()
+                )));
+            }
             lines.push(Line::from(
                 "Docker output will appear in the terminal while this runs.",
             ));
@@ -856,11 +1485,11 @@ fn run_launch_flow(
     terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     handle: &Handle,
     summary: &KeygenSummary,
-    network: &str,
-    use_external_nodes: bool,
-    external_execution_endpoint: &str,
-    external_consensus_endpoint: &str,
+    state: &InitState,
 ) -> Result<()> {
+    let network = state.network();
+    let external_execution_endpoint = &state.external_execution_endpoint;
+    let external_consensus_endpoint = &state.external_consensus_endpoint;
     // Re-enable logging while the blocking container import flow executes.
     let _logs_on = crate::log_control::enable_guard();
     disable_raw_mode()?;
@@ -868,20 +1497,20 @@ fn run_launch_flow(
     terminal.show_cursor()?;
 
     let result = (|| -> Result<()> {
-        handle.block_on(remove_validator_container_if_present());
-        println!("Importing validator keys with Lighthouse...");
-        run_validator_import(summary, network)?;
-        println!("Configuring Ethereum clients for {network}...");
         let mut values = HashMap::new();
         values.insert("network".to_string(), network.to_string());
         values.insert("validator_enabled".to_string(), "true".to_string());
+        values.insert(
+            "validator_client".to_string(),
+            state.validator_client().to_string(),
+        );
         values.insert(
             "validator_fee_recipient".to_string(),
             summary.fee_recipient.clone(),
         );
 
         // Add external endpoints if configured
-        if use_external_nodes {
+        if state.use_external_nodes {
             values.insert(
                 "execution_endpoint".to_string(),
                 external_execution_endpoint.to_string(),
@@ -890,21 +1519,59 @@ fn run_launch_flow(
                 "consensus_endpoint".to_string(),
                 external_consensus_endpoint.to_string(),
             );
-            println!("Using external Ethereum nodes:");
-            println!("  EL: {}", external_execution_endpoint);
-            println!("  CL: {}", external_consensus_endpoint);
         } else {
             // When switching to local nodes, explicitly set empty values to remove external endpoints
             // This allows users to revert from external to local nodes without manual config editing
             values.insert("execution_endpoint".to_string(), String::new());
             values.insert("consensus_endpoint".to_string(), String::new());
+            values.insert(
+                "execution_client".to_string(),
+                state.execution_client().to_string(),
+            );
+            values.insert(
+                "consensus_client".to_string(),
+                state.consensus_client().to_string(),
+            );
         }
-
         let config = PackageConfig { values };
+
+        let password = Zeroizing::new(
+            Password::with_theme(&ColorfulTheme::default())
+                .with_prompt("Enter the keystore password to import validators")
+                .validate_with(|value: &String| {
+                    validate_password(value).map_err(|error| error.to_string())
+                })
+                .interact()?,
+        );
+        println!(
+            "Importing validator keys with {}...",
+            state.validator_client()
+        );
+        let import = handle.block_on(import_validator_keys(
+            None,
+            &config,
+            &summary.output_dir,
+            &password,
+        ))?;
+        println!(
+            "Imported {} keystore(s) into the {} validator client.",
+            import.keystores, import.client
+        );
+        println!(
+            "Slashing protection: {} in volume {}",
+            import.slashing_protection_path, import.data_volume
+        );
+
+        println!("Configuring Ethereum clients for {network}...");
+        if state.use_external_nodes {
+            println!("Using external Ethereum nodes:");
+            println!("  EL: {}", external_execution_endpoint);
+            println!("  CL: {}", external_consensus_endpoint);
+        }
         let mut needs_install = false;
         let update_result = handle
             .clone()
-            .block_on(async { update_package_config("ethereum", config.clone()).await });
+            .block_on(async { update_package_config("ethereum", None, config.clone()).await });
         if let Err(error) = update_result {
             if is_missing_docker_resource_error(&error) {
                 needs_install = true;
@@ -915,7 +1582,7 @@ fn run_launch_flow(
         if needs_install {
             handle.block_on(async { install_package("ethereum").await })?;
         }
-        println!("Execution and validator clients are running.");
+        println!("Ethereum and validator clients are running.");
         Ok(())
     })();
 
@@ -940,284 +1607,6 @@ fn is_missing_docker_resource_error(error: &Report) -> bool {
         || (msg.contains("network") && msg.contains("not found"))
 }
 
-fn run_validator_import(summary: &KeygenSummary, network: &str) -> Result<()> {
-    let keys_mount = canonicalize_path(&summary.output_dir);
-    let ephemery = if network == EPHEMERY_NETWORK_NAME {
-        Some(ensure_ephemery_config()?)
-    } else {
-        None
-    };
-    let metadata_mount = ephemery
-        .as_ref()
-        .map(|config| canonicalize_path(&config.metadata_dir));
-
-    use bollard::Docker;
-    use bollard::models::ContainerCreateBody;
-    use bollard::secret::HostConfig;
-    use tokio_stream::StreamExt;
-
-    async fn import_with_bollard(
-        keys_mount: &Path,
-        keys_display_root: &Path,
-        metadata_mount: Option<&Path>,
-        network: &str,
-        use_ephemery: bool,
-    ) -> Result<()> {
-        let docker: Docker = get_docker().await?;
-
-        let create_image_opts = Some(
-            bollard::query_parameters::CreateImageOptionsBuilder::default()
-                .from_image("sigp/lighthouse")
-                .tag("latest")
-                .build(),
-        );
-        let mut pull = docker.create_image(create_image_opts, None, None);
-        while pull.next().await.is_some() {}
-
-        let mut binds = vec![
-            format!("{LIGHTHOUSE_DATA_VOLUME}:{LIGHTHOUSE_DATA_DIR}"),
-            format!("{}:/root/validator_keys", keys_mount.display()),
-        ];
-        if let Some(meta) = metadata_mount {
-            binds.push(format!("{}:/root/networks/ephemery:ro", meta.display()));
-        }
-
-        let mut cmd: Vec<String> = vec!["lighthouse".to_string(), "--stdin-inputs".to_string()];
-        if use_ephemery {
-            cmd.push("--testnet-dir".into());
-            cmd.push("/root/networks/ephemery".into());
-        } else {
-            cmd.push("--network".into());
-            cmd.push(network.to_string());
-        }
-        cmd.extend([
-            "account".into(),
-            "validator".into(),
-            "import".into(),
-            "--directory".into(),
-            "/root/validator_keys".into(),
-        ]);
-
-        let host_config = HostConfig {
-            binds: Some(binds),
-            ..Default::default()
-        };
-
-        let config = ContainerCreateBody {
-            image: Some("sigp/lighthouse".to_string()),
-            cmd: Some(cmd),
-            tty: Some(false),
-            attach_stdin: Some(true),
-            attach_stdout: Some(true),
-            attach_stderr: Some(true),
-            open_stdin: Some(true),
-            host_config: Some(host_config),
-            ..Default::default()
-        };
-
-        let created = docker
-            .create_container(
-                None::<bollard::query_parameters::CreateContainerOptions>,
-                config,
-            )
-            .await?;
-
-        docker
-            .start_container(
-                &created.id,
-                None::<bollard::query_parameters::StartContainerOptions>,
-            )
-            .await?;
-
-        let bollard::container::AttachContainerResults {
-            mut output,
-            mut input,
-        } = docker
-            .attach_container(
-                &created.id,
-                Some(
-                    bollard::query_parameters::AttachContainerOptionsBuilder::default()
-                        .stdin(true)
-                        .stdout(true)
-                        .stderr(true)
-                        .stream(true)
-                        .build(),
-                ),
-            )
-            .await?;
-
-        let mut wait_stream = docker.wait_container(
-            &created.id,
-            None::<bollard::query_parameters::WaitContainerOptions>,
-        );
-
-        let mut stdout = std::io::stdout();
-        let mut buffer = String::new();
-        let mut current_keystore: Option<PathBuf> = None;
-        let mut exit_code: Option<i64> = None;
-        let mut output_finished = false;
-
-        while !output_finished || exit_code.is_none() {
-            tokio::select! {
-                maybe_chunk = output.next(), if !output_finished => {
-                    match maybe_chunk {
-                        Some(Ok(chunk)) => {
-                            handle_output_chunk(
-                                chunk,
-                                &mut stdout,
-                                &mut buffer,
-                                keys_display_root,
-                                &mut current_keystore,
-                                &mut input,
-                            ).await?;
-                        }
-                        Some(Err(error)) => return Err(error.into()),
-                        None => output_finished = true,
-                    }
-                }
-                maybe_status = wait_stream.next(), if exit_code.is_none() => {
-                    match maybe_status {
-                        Some(Ok(details)) => exit_code = Some(details.status_code),
-                        Some(Err(error)) => return Err(error.into()),
-                        None => exit_code = Some(0),
-                    }
-                }
-            }
-        }
-
-        drop(input);
-
-        docker
-            .remove_container(
-                &created.id,
-                Some(
-                    bollard::query_parameters::RemoveContainerOptionsBuilder::default()
-                        .force(true)
-                        .build(),
-                ),
-            )
-            .await?;
-
-        match exit_code.unwrap_or_default() {
-            0 => Ok(()),
-            code => Err(eyre!("lighthouse import exited with status code {code}")),
-        }
-    }
-
-    async fn handle_output_chunk<W>(
-        chunk: bollard::container::LogOutput,
-        stdout: &mut std::io::Stdout,
-        buffer: &mut String,
-        keys_display_root: &Path,
-        current_keystore: &mut Option<PathBuf>,
-        input: &mut W,
-    ) -> Result<()>
-    where
-        W: AsyncWrite + Unpin,
-    {
-        let bytes = chunk.into_bytes();
-        stdout.write_all(&bytes)?;
-        stdout.flush()?;
-
-        let text = String::from_utf8_lossy(&bytes);
-        buffer.push_str(&text);
-
-        while let Some(idx) = buffer.find('\n') {
-            let mut line = buffer[..idx].to_string();
-            buffer.drain(..idx + 1);
-            if line.ends_with('\r') {
-                line.pop();
-            }
-            process_line(&line, keys_display_root, current_keystore, input).await?;
-        }
-
-        if !buffer.is_empty() && line_contains_password_prompt(buffer) {
-            let mut line = buffer.clone();
-            if line.ends_with('\r') {
-                line.pop();
-            }
-            process_line(&line, keys_display_root, current_keystore, input).await?;
-            buffer.clear();
-        }
-
-        Ok(())
-    }
-
-    async fn process_line<W>(
-        line: &str,
-        keys_display_root: &Path,
-        current_keystore: &mut Option<PathBuf>,
-        input: &mut W,
-    ) -> Result<()>
-    where
-        W: AsyncWrite + Unpin,
-    {
-        if let Some(path) = extract_keystore_path(line, keys_display_root) {
-            *current_keystore = Some(path);
-        }
-
-        if line_contains_password_prompt(line) {
-            let prompt = current_keystore
-                .as_ref()
-                .map(|path| format!("Enter the password for {}", path.display()))
-                .unwrap_or_else(|| "Enter the keystore password to import validators".to_string());
-            let password = prompt_for_password(prompt).await?;
-            input.write_all(password.as_bytes()).await?;
-            input.write_all(b"\n").await?;
-            input.flush().await?;
-            drop(password);
-        }
-
-        Ok(())
-    }
-
-    async fn prompt_for_password(prompt: String) -> Result<Zeroizing<String>> {
-        tokio::task::spawn_blocking(move || -> Result<Zeroizing<String>> {
-            let theme = ColorfulTheme::default();
-            let password = Password::with_theme(&theme)
-                .with_prompt(&prompt)
-                .validate_with(|value: &String| {
-                    validate_password(value).map_err(|error| error.to_string())
-                })
-                .interact()
-                .map_err(|error| eyre!("Failed to read password: {error}"))?;
-            Ok(Zeroizing::new(password))
-        })
-        .await
-        .map_err(|error| eyre!("Failed to prompt for password: {error}"))?
-    }
-
-    fn extract_keystore_path(line: &str, keys_display_root: &Path) -> Option<PathBuf> {
-        const PREFIX: &str = "Keystore found at \"";
-        let start = line.find(PREFIX)?;
-        let remainder = &line[start + PREFIX.len()..];
-        let end = remainder.find('"')?;
-        let container_path = &remainder[..end];
-        if let Some(stripped) = container_path.strip_prefix("/root/validator_keys/") {
-            return Some(keys_display_root.join(stripped));
-        }
-        Some(PathBuf::from(container_path))
-    }
-
-    fn line_contains_password_prompt(line: &str) -> bool {
-        let lower = line.to_ascii_lowercase();
-        lower.contains("enter the keystore password")
-    }
-
-    let handle = Handle::current();
-    handle.block_on(import_with_bollard(
-        &keys_mount,
-        &summary.output_dir,
-        metadata_mount.as_deref(),
-        network,
-        ephemery.is_some(),
-    ))
-}
-
-fn canonicalize_path(path: &Path) -> PathBuf {
-    // Use dunce to get a platform-friendly canonical path (no \\\\?\\ prefix on Windows).
-    dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
-}
 fn clear_clipboard() -> Result<()> {
     let mut clipboard = arboard::Clipboard::new()
         .map_err(|error| eyre!("Failed to open system clipboard: {error}"))?;
@@ -1273,19 +1662,3 @@ fn capture_mnemonic_securely(theme: &ColorfulTheme) -> Result<String> {
 fn normalize_mnemonic(value: &str) -> String {
     value.split_whitespace().collect::<Vec<_>>().join(" ")
 }
-
-/// Removes the validator container if present so other commands can safely reconfigure Ethereum state.
-pub async fn remove_validator_container_if_present() {
-    if let Ok(docker) = get_docker().await {
-        let _ = docker
-            .remove_container(
-                VALIDATOR_CONTAINER_NAME,
-                Some(bollard::query_parameters::RemoveContainerOptions {
-                    force: true,
-                    link: false,
-                    v: false,
-                }),
-            )
-            .await;
-    }
-}
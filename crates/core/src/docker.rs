@@ -1,38 +1,80 @@
-use crate::packages::{Binding, Container, PortBinding};
+use crate::packages::{
+    Binding, Container, PortBinding, RestartPolicy, Ulimit, scoped_resource_name,
+    validate_instance_id,
+};
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 use bollard::API_DEFAULT_VERSION;
 use bollard::{
     Docker,
     errors::Error as DockerError,
+    exec::{StartExecOptions, StartExecResults},
     models::{
-        ContainerCreateBody, EndpointSettings, NetworkConnectRequest, NetworkCreateRequest,
-        PortBinding as DockerPortBinding,
+        ContainerCreateBody, EndpointSettings, ExecConfig, NetworkConnectRequest,
+        NetworkCreateRequest, PortBinding as DockerPortBinding, ResourcesUlimits,
+        RestartPolicy as DockerRestartPolicy, RestartPolicyNameEnum, VolumeCreateOptions,
     },
     query_parameters::{
         CreateContainerOptionsBuilder, CreateImageOptionsBuilder, ListContainersOptionsBuilder,
         LogsOptionsBuilder,
     },
-    secret::{ContainerSummary, ContainerSummaryStateEnum, HostConfig},
+    secret::{
+        ContainerStateStatusEnum, ContainerSummary, ContainerSummaryStateEnum, HealthStatusEnum,
+        HostConfig,
+    },
 };
 #[cfg(target_os = "linux")]
 use eyre::eyre;
 use eyre::{Report, Result};
-use std::collections::HashMap;
 #[cfg(target_os = "linux")]
 use std::collections::HashSet;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 use std::{
     env,
     path::{Path, PathBuf},
 };
+use tokio::time::sleep;
 use tokio_stream::StreamExt;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 pub const RESOURCE_PREFIX: &str = "kittynode-";
+/// Prefix of the ownership labels set on every container, volume and network Kittynode creates.
+pub const LABEL_PREFIX: &str = "io.kittynode.";
+pub const MANAGED_LABEL: &str = "io.kittynode.managed";
+pub const PACKAGE_LABEL: &str = "io.kittynode.package";
+/// The chain network a resource was created for, such as `hoodi`.
+pub const NETWORK_LABEL: &str = "io.kittynode.network";
+pub const MANIFEST_VERSION_LABEL: &str = "io.kittynode.manifest-version";
+const PULL_ATTEMPTS: u32 = 4;
+const PULL_RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+/// Longest stop grace period Kittynode gives a container.
+const MAX_STOP_TIMEOUT_SECS: u64 = 1800;
+const STOP_REQUEST_TIMEOUT: Duration = Duration::from_secs(MAX_STOP_TIMEOUT_SECS + 60);
+const NANO_CPUS_PER_CPU: f64 = 1_000_000_000.0;
+/// Log lines included in the error when a one-off container fails.
+const FAILED_JOB_LOG_LINES: usize = 20;
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct LogsQuery {
     pub tail: Option<usize>,
+    /// Package instance the container belongs to; `None` for the default instance.
+    pub instance: Option<String>,
+}
+
+/// A progress update reported by Docker while pulling an image.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullProgress {
+    /// Image reference being pulled, including tag or digest.
+    pub image: String,
+    /// Layer the update refers to; `None` for image-level status lines.
+    pub layer_id: Option<String>,
+    pub status: String,
+    pub current_bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
 }
 
 pub async fn get_docker() -> Result<Docker> {
@@ -306,7 +348,11 @@ fn annotate_error(err: Report, attempted: &[String]) -> Report {
     }
 }
 
-pub(crate) async fn create_or_recreate_network(docker: &Docker, network_name: &str) -> Result<()> {
+pub(crate) async fn create_or_recreate_network(
+    docker: &Docker,
+    network_name: &str,
+    labels: &HashMap<String, String>,
+) -> Result<()> {
     let network_exists = docker
         .list_networks(None::<bollard::query_parameters::ListNetworksOptions>)
         .await?
@@ -350,6 +396,7 @@ pub(crate) async fn create_or_recreate_network(docker: &Docker, network_name: &s
     let network_config = NetworkCreateRequest {
         name: network_name.to_string(),
         driver: Some("bridge".to_string()),
+        labels: Some(labels.clone()),
         ..Default::default()
     };
     docker.create_network(network_config).await?;
@@ -358,6 +405,9 @@ pub(crate) async fn create_or_recreate_network(docker: &Docker, network_name: &s
     Ok(())
 }
 
+/// Lists containers named exactly `name`.
+///
+/// Docker's `name` filter matches substrings, so the results are narrowed to exact matches.
 pub async fn find_container(docker: &Docker, name: &str) -> Result<Vec<ContainerSummary>> {
     let filters = HashMap::from([("name".to_string(), vec![name.to_string()])]);
 
@@ -366,10 +416,22 @@ pub async fn find_container(docker: &Docker, name: &str) -> Result<Vec<Container
         .filters(&filters)
         .build();
 
-    Ok(docker.list_containers(Some(options)).await?)
+    let mut containers = docker.list_containers(Some(options)).await?;
+    containers.retain(|container| container_names(container).any(|found| found == name));
+    Ok(containers)
+}
+
+/// Returns a container's names without the leading `/` Docker adds.
+pub(crate) fn container_names(container: &ContainerSummary) -> impl Iterator<Item = &str> {
+    container
+        .names
+        .iter()
+        .flatten()
+        .map(|name| name.trim_start_matches('/'))
 }
 
 pub(crate) async fn remove_container(docker: &Docker, name: &str) -> Result<()> {
+    let docker = &with_stop_timeout(docker);
     for container in find_container(docker, name).await? {
         let id = container
             .id
@@ -390,6 +452,7 @@ pub(crate) async fn remove_container(docker: &Docker, name: &str) -> Result<()>
 }
 
 pub(crate) async fn stop_named_container(docker: &Docker, name: &str) -> Result<()> {
+    let docker = &with_stop_timeout(docker);
     for container in find_container(docker, name).await? {
         if !container_is_running(&container) {
             continue;
@@ -408,6 +471,12 @@ pub(crate) async fn stop_named_container(docker: &Docker, name: &str) -> Result<
     Ok(())
 }
 
+/// Docker answers a stop request only after the container's stop timeout, which can be longer
+/// than the client's default request timeout.
+fn with_stop_timeout(docker: &Docker) -> Docker {
+    docker.clone().with_timeout(STOP_REQUEST_TIMEOUT)
+}
+
 pub(crate) async fn start_named_container(docker: &Docker, name: &str) -> Result<()> {
     for container in find_container(docker, name).await? {
         if container_is_running(&container) {
@@ -440,26 +509,136 @@ pub(crate) async fn pull_and_start_container(
     docker: &Docker,
     container: &Container,
     network_name: &str,
+    on_progress: &mut (dyn FnMut(PullProgress) + Send),
 ) -> Result<()> {
-    let exposed_ports: HashMap<String, HashMap<(), ()>> = container
-        .port_bindings
-        .keys()
-        .map(|key| (key.to_string(), HashMap::new()))
-        .collect();
+    pull_image(docker, container, on_progress).await?;
+
+    let _ = remove_container(docker, container.name.as_str()).await;
+    create_and_start_container(
+        docker,
+        container,
+        &container.image_reference(),
+        network_name,
+    )
+    .await
+}
+
+/// Pulls the image referenced by the container's tag or digest.
+///
+/// Temporary registry and connection failures are retried with exponential backoff; anything
+/// else, or running out of attempts, fails the pull.
+pub(crate) async fn pull_image(
+    docker: &Docker,
+    container: &Container,
+    on_progress: &mut (dyn FnMut(PullProgress) + Send),
+) -> Result<()> {
+    let reference = container.image_reference();
+    let mut attempt = 1;
+    loop {
+        match pull_image_once(docker, container, &reference, on_progress).await {
+            Ok(()) => return Ok(()),
+            Err(error) if attempt < PULL_ATTEMPTS && is_transient_pull_error(&error) => {
+                let delay = PULL_RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                warn!(
+                    "Pulling '{reference}' failed (attempt {attempt} of {PULL_ATTEMPTS}): {error}; retrying in {}s",
+                    delay.as_secs()
+                );
+                sleep(delay).await;
+                attempt += 1;
+            }
+            Err(error) => {
+                return Err(
+                    Report::from(error).wrap_err(format!("Failed to pull image '{reference}'"))
+                );
+            }
+        }
+    }
+}
+
+async fn pull_image_once(
+    docker: &Docker,
+    container: &Container,
+    reference: &str,
+    on_progress: &mut (dyn FnMut(PullProgress) + Send),
+) -> Result<(), DockerError> {
+    // Docker accepts a digest in place of the tag when pulling by digest.
+    let tag = container.digest.as_deref().unwrap_or(&container.tag);
     let options = Some(
         CreateImageOptionsBuilder::default()
             .from_image(container.image.as_str())
-            .tag("latest")
+            .tag(tag)
             .build(),
     );
 
     let mut stream = docker.create_image(options, None, None);
     while let Some(item) = stream.next().await {
-        match item {
-            Ok(info) => info!("Pulling image info: {:?}", info),
-            Err(e) => error!("Error pulling image: {:?}", e),
+        let info = item?;
+        if let Some(message) = info.error_detail.and_then(|detail| detail.message) {
+            return Err(DockerError::DockerStreamError { error: message });
         }
+        let detail = info.progress_detail.unwrap_or_default();
+        on_progress(PullProgress {
+            image: reference.to_string(),
+            layer_id: info.id,
+            status: info.status.unwrap_or_default(),
+            current_bytes: detail.current.and_then(|bytes| u64::try_from(bytes).ok()),
+            total_bytes: detail.total.and_then(|bytes| u64::try_from(bytes).ok()),
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns whether a pull error is likely to go away on its own, such as a registry outage,
+/// rate limit, or dropped connection. Missing images and authentication failures are not.
+fn is_transient_pull_error(error: &DockerError) -> bool {
+    match error {
+        DockerError::DockerResponseServerError {
+            status_code,
+            message,
+        } => *status_code >= 500 && !is_permanent_pull_message(message),
+        DockerError::DockerStreamError { error } => !is_permanent_pull_message(error),
+        DockerError::RequestTimeoutError | DockerError::IOError { .. } => true,
+        _ => false,
     }
+}
+
+fn is_permanent_pull_message(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    ["not found", "manifest unknown", "unauthorized", "denied"]
+        .iter()
+        .any(|needle| message.contains(needle))
+}
+
+/// Logs pull progress for callers that do not render it themselves.
+pub(crate) fn log_pull_progress(progress: PullProgress) {
+    // Byte counts arrive many times per second, so only status changes are logged.
+    if progress.current_bytes.is_none() {
+        match &progress.layer_id {
+            Some(layer_id) => info!("{}: {layer_id}: {}", progress.image, progress.status),
+            None => info!("{}: {}", progress.image, progress.status),
+        }
+    }
+}
+
+/// Returns the local image ID that an image reference resolves to.
+pub(crate) async fn image_id(docker: &Docker, reference: &str) -> Result<Option<String>> {
+    Ok(docker.inspect_image(reference).await?.id)
+}
+
+/// Creates the container from `image` (a reference or image ID), starts it, and connects it to
+/// the given Docker network. Any existing container with the same name must already be removed.
+pub(crate) async fn create_and_start_container(
+    docker: &Docker,
+    container: &Container,
+    image: &str,
+    network_name: &str,
+) -> Result<()> {
+    let exposed_ports: HashMap<String, HashMap<(), ()>> = container
+        .port_bindings
+        .keys()
+        .map(|key| (key.to_string(), HashMap::new()))
+        .collect();
 
     let port_bindings = container
         .port_bindings
@@ -467,6 +646,8 @@ pub(crate) async fn pull_and_start_container(
         .map(|(k, v)| (k.to_string(), Some(convert_port_bindings(v))))
         .collect();
 
+    create_volumes(docker, container).await?;
+
     let bindings = container
         .volume_bindings
         .iter()
@@ -477,14 +658,36 @@ pub(crate) async fn pull_and_start_container(
     let host_config = HostConfig {
         binds: Some(bindings),
         port_bindings: Some(port_bindings),
+        restart_policy: Some(convert_restart_policy(container.restart_policy)),
+        memory: container
+            .memory_limit_bytes
+            .map(|bytes| i64::try_from(bytes).unwrap_or(i64::MAX)),
+        nano_cpus: container
+            .cpu_limit
+            .map(|cpus| (cpus * NANO_CPUS_PER_CPU) as i64),
+        ulimits: (!container.ulimits.is_empty()).then(|| convert_ulimits(&container.ulimits)),
         ..Default::default()
     };
 
     let config = ContainerCreateBody {
-        image: Some(container.image.to_string()),
-        cmd: Some(container.cmd.clone()),
+        image: Some(image.to_string()),
+        entrypoint: (!container.entrypoint.is_empty()).then(|| container.entrypoint.clone()),
+        cmd: Some(container.command()),
+        env: (!container.env.is_empty()).then(|| {
+            let mut env: Vec<String> = container
+                .env
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect();
+            env.sort();
+            env
+        }),
+        labels: (!container.labels.is_empty()).then(|| container.labels.clone()),
         exposed_ports: Some(exposed_ports),
         host_config: Some(host_config),
+        stop_timeout: container
+            .stop_timeout_secs
+            .map(|secs| secs.min(MAX_STOP_TIMEOUT_SECS) as i64),
         ..Default::default()
     };
 
@@ -494,7 +697,6 @@ pub(crate) async fn pull_and_start_container(
             .build(),
     );
 
-    let _ = remove_container(docker, container.name.as_str()).await;
     let created_container = docker.create_container(options, config).await?;
     info!("Container {} created successfully.", container.name);
 
@@ -522,6 +724,210 @@ pub(crate) async fn pull_and_start_container(
     Ok(())
 }
 
+/// Runs `container` as root until it exits, then removes it.
+///
+/// One-off jobs such as validator key imports write into volumes owned by whichever user the
+/// client image runs as. Fails with the job's last log lines if it exits with a non-zero status.
+pub(crate) async fn run_container_to_completion(
+    docker: &Docker,
+    container: &Container,
+) -> Result<()> {
+    let _ = remove_container(docker, &container.name).await;
+    create_volumes(docker, container).await?;
+
+    let bindings = container
+        .volume_bindings
+        .iter()
+        .chain(&container.file_bindings)
+        .map(create_binding_string)
+        .collect();
+    let config = ContainerCreateBody {
+        image: Some(container.image_reference()),
+        entrypoint: (!container.entrypoint.is_empty()).then(|| container.entrypoint.clone()),
+        cmd: Some(container.command()),
+        user: Some("0:0".to_string()),
+        labels: (!container.labels.is_empty()).then(|| container.labels.clone()),
+        host_config: Some(HostConfig {
+            binds: Some(bindings),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let options = Some(
+        CreateContainerOptionsBuilder::default()
+            .name(container.name.as_str())
+            .build(),
+    );
+    docker.create_container(options, config).await?;
+    docker
+        .start_container(
+            &container.name,
+            None::<bollard::query_parameters::StartContainerOptions>,
+        )
+        .await?;
+
+    let mut status_code = 0;
+    let mut wait = docker.wait_container(
+        &container.name,
+        None::<bollard::query_parameters::WaitContainerOptions>,
+    );
+    while let Some(result) = wait.next().await {
+        match result {
+            Ok(response) => status_code = response.status_code,
+            // Bollard reports a non-zero exit status as an error.
+            Err(DockerError::DockerContainerWaitError { code, .. }) => status_code = code,
+            Err(error) => {
+                let _ = remove_container(docker, &container.name).await;
+                return Err(error.into());
+            }
+        }
+    }
+
+    let logs = if status_code == 0 {
+        Vec::new()
+    } else {
+        get_container_logs_from_docker(docker, &container.name, Some(FAILED_JOB_LOG_LINES))
+            .await
+            .unwrap_or_default()
+    };
+    remove_container(docker, &container.name).await?;
+    if status_code != 0 {
+        return Err(eyre::eyre!(
+            "Container '{}' exited with status {status_code}:\n{}",
+            container.name,
+            logs.concat().trim_end()
+        ));
+    }
+    Ok(())
+}
+
+/// Creates the container's named volumes up front so they carry its ownership labels.
+///
+/// Docker leaves volumes that already exist untouched, including their labels.
+async fn create_volumes(docker: &Docker, container: &Container) -> Result<()> {
+    let labels: HashMap<String, String> = container
+        .labels
+        .iter()
+        .filter(|(key, _)| key.starts_with(LABEL_PREFIX))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    for binding in &container.volume_bindings {
+        docker
+            .create_volume(VolumeCreateOptions {
+                name: Some(binding.source.clone()),
+                labels: Some(labels.clone()),
+                ..Default::default()
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+/// Waits until a freshly started container is healthy.
+///
+/// Containers with a Docker `HEALTHCHECK` must report healthy; others must keep running for
+/// `settle` without exiting or restarting. Fails once `timeout` elapses.
+pub(crate) async fn wait_for_container_healthy(
+    docker: &Docker,
+    name: &str,
+    settle: Duration,
+    timeout: Duration,
+) -> Result<()> {
+    let started = Instant::now();
+    let mut running_since: Option<Instant> = None;
+
+    loop {
+        let inspect = docker
+            .inspect_container(
+                name,
+                None::<bollard::query_parameters::InspectContainerOptions>,
+            )
+            .await?;
+        let state = inspect.state.unwrap_or_default();
+
+        match state.status {
+            Some(ContainerStateStatusEnum::EXITED | ContainerStateStatusEnum::DEAD) => {
+                return Err(eyre::eyre!(
+                    "Container '{name}' exited with code {}",
+                    state.exit_code.unwrap_or_default()
+                ));
+            }
+            Some(ContainerStateStatusEnum::RESTARTING) => {
+                return Err(eyre::eyre!("Container '{name}' is restarting"));
+            }
+            Some(ContainerStateStatusEnum::RUNNING) => {
+                let health = state.health.and_then(|health| health.status);
+                match health {
+                    Some(HealthStatusEnum::HEALTHY) => return Ok(()),
+                    Some(HealthStatusEnum::UNHEALTHY) => {
+                        return Err(eyre::eyre!("Container '{name}' reported unhealthy"));
+                    }
+                    Some(HealthStatusEnum::STARTING) => {}
+                    _ => {
+                        let since = *running_since.get_or_insert_with(Instant::now);
+                        if since.elapsed() >= settle {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+            _ => running_since = None,
+        }
+
+        if started.elapsed() >= timeout {
+            return Err(eyre::eyre!(
+                "Container '{name}' did not become healthy within {}s",
+                timeout.as_secs()
+            ));
+        }
+        sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Runs `command` inside a running container and returns its exit code.
+pub(crate) async fn exec_in_container(
+    docker: &Docker,
+    name: &str,
+    command: &[String],
+) -> Result<i64> {
+    let config = ExecConfig {
+        cmd: Some(command.to_vec()),
+        attach_stdout: Some(true),
+        attach_stderr: Some(true),
+        ..Default::default()
+    };
+    let exec = docker.create_exec(name, config).await?;
+
+    if let StartExecResults::Attached { mut output, .. } = docker
+        .start_exec(&exec.id, None::<StartExecOptions>)
+        .await?
+    {
+        while let Some(chunk) = output.next().await {
+            chunk?;
+        }
+    }
+
+    let inspect = docker.inspect_exec(&exec.id).await?;
+    inspect
+        .exit_code
+        .ok_or_else(|| eyre::eyre!("Command in container '{name}' did not report an exit code"))
+}
+
+/// Returns the registry digest Docker recorded for an image pulled from `repository`.
+pub(crate) async fn image_repo_digest(
+    docker: &Docker,
+    image_id: &str,
+    repository: &str,
+) -> Result<Option<String>> {
+    let inspect = docker.inspect_image(image_id).await?;
+    let prefix = format!("{repository}@");
+    Ok(inspect
+        .repo_digests
+        .unwrap_or_default()
+        .iter()
+        .find_map(|digest| digest.strip_prefix(&prefix).map(str::to_string)))
+}
+
 async fn get_container_logs_from_docker(
     docker: &Docker,
     container_name: &str,
@@ -556,12 +962,18 @@ async fn get_container_logs_from_docker(
     Ok(log_strings)
 }
 
+/// Fetches logs for `container_name`, or for its copy in the named package `instance`.
 pub async fn get_container_logs(
     container_name: &str,
+    instance: Option<&str>,
     tail_lines: Option<usize>,
 ) -> Result<Vec<String>> {
+    if let Some(instance) = instance {
+        validate_instance_id(instance)?;
+    }
+    let container_name = scoped_resource_name(container_name, instance);
     let docker = get_docker_instance().await?;
-    get_container_logs_from_docker(&docker, container_name, tail_lines).await
+    get_container_logs_from_docker(&docker, &container_name, tail_lines).await
 }
 
 fn create_binding_string(binding: &Binding) -> String {
@@ -575,6 +987,30 @@ fn create_binding_string(binding: &Binding) -> String {
     }
 }
 
+fn convert_restart_policy(policy: RestartPolicy) -> DockerRestartPolicy {
+    let name = match policy {
+        RestartPolicy::No => RestartPolicyNameEnum::NO,
+        RestartPolicy::OnFailure => RestartPolicyNameEnum::ON_FAILURE,
+        RestartPolicy::UnlessStopped => RestartPolicyNameEnum::UNLESS_STOPPED,
+        RestartPolicy::Always => RestartPolicyNameEnum::ALWAYS,
+    };
+    DockerRestartPolicy {
+        name: Some(name),
+        maximum_retry_count: None,
+    }
+}
+
+fn convert_ulimits(ulimits: &[Ulimit]) -> Vec<ResourcesUlimits> {
+    ulimits
+        .iter()
+        .map(|ulimit| ResourcesUlimits {
+            name: Some(ulimit.name.clone()),
+            soft: Some(ulimit.soft),
+            hard: Some(ulimit.hard),
+        })
+        .collect()
+}
+
 fn convert_port_bindings(bindings: &[PortBinding]) -> Vec<DockerPortBinding> {
     bindings
         .iter()
@@ -584,3 +1020,42 @@ fn convert_port_bindings(bindings: &[PortBinding]) -> Vec<DockerPortBinding> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_transient_pull_error;
+    use bollard::errors::Error as DockerError;
+
+    #[test]
+    fn registry_outages_are_transient() {
+        assert!(is_transient_pull_error(
+            &DockerError::DockerResponseServerError {
+                status_code: 503,
+                message: "received unexpected HTTP status: 503 Service Unavailable".to_string(),
+            }
+        ));
+        assert!(is_transient_pull_error(&DockerError::DockerStreamError {
+            error: "unexpected EOF".to_string(),
+        }));
+        assert!(is_transient_pull_error(&DockerError::RequestTimeoutError));
+    }
+
+    #[test]
+    fn missing_images_and_auth_failures_are_not_retried() {
+        assert!(!is_transient_pull_error(
+            &DockerError::DockerResponseServerError {
+                status_code: 404,
+                message: "manifest for example/image:v9 not found".to_string(),
+            }
+        ));
+        assert!(!is_transient_pull_error(
+            &DockerError::DockerResponseServerError {
+                status_code: 500,
+                message: "Head https://registry/v2/x/manifests/v1: unauthorized".to_string(),
+            }
+        ));
+        assert!(!is_transient_pull_error(&DockerError::DockerStreamError {
+            error: "manifest unknown".to_string(),
+        }));
+    }
+}
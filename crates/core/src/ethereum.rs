@@ -1,19 +1,56 @@
+#[path = "ethereum/beacon_api.rs"]
+mod beacon_api;
+#[path = "ethereum/consensus.rs"]
+mod consensus;
 #[path = "ethereum/containers.rs"]
 mod containers;
 #[path = "ethereum/ephemery.rs"]
 mod ephemery;
+#[path = "ethereum/execution.rs"]
+mod execution;
+#[path = "ethereum/key_import.rs"]
+mod key_import;
+#[path = "ethereum/mev_boost.rs"]
+mod mev_boost;
+#[path = "ethereum/node_health.rs"]
+mod node_health;
+#[path = "ethereum/operation_pool.rs"]
+mod operation_pool;
 #[path = "ethereum/settings.rs"]
 mod settings;
+#[path = "ethereum/validator.rs"]
+mod validator;
+#[path = "ethereum/validator_status.rs"]
+mod validator_status;
 
+pub(crate) use ephemery::ephemery_dir;
 pub use ephemery::{
     EPHEMERY_CHECKPOINT_URLS, EPHEMERY_NETWORK_NAME, EphemeryConfig, ensure_ephemery_config,
 };
+pub use key_import::ValidatorKeyImport;
+pub use node_health::{ConsensusHealth, ExecutionHealth, NodeHealth};
+pub(crate) use validator::is_validator_data_volume;
+pub use validator_status::{ValidatorState, ValidatorStatus, WithdrawalCredentialsType};
 
 use crate::docker::{find_container, get_docker_instance};
-use crate::packages::{Container, Package, PackageConfig, PackageConfigStore, PackageDefinition};
+use crate::packages::{
+    ConfigField, ConfigFieldKind, Container, Package, PackageConfig, PackageConfigStore,
+    PackageDefinition, RestartPolicy, instance_name, list_instances, merge_config_update,
+    resolve_instance_name, scoped_resource_name,
+};
 use crate::paths::{generate_jwt_secret, kittynode_path};
+use crate::validator::{
+    BlsToExecutionChangeRequest, BlsToExecutionChanges, SignedVoluntaryExit, VoluntaryExitRequest,
+    bls_withdrawal_credentials, sign_bls_to_execution_changes, sign_voluntary_exit,
+};
+use alloy_primitives::Address;
+use consensus::ConsensusClient;
+use execution::ExecutionClient;
 use eyre::{Context, Result, eyre};
-use std::iter;
+use std::{collections::HashSet, iter, path::Path};
+use tokio::task;
+use validator::ValidatorClient;
+use zeroize::Zeroizing;
 
 pub const ETHEREUM_EXECUTION_NETWORKS: &[&str] = &["hoodi", "mainnet", "sepolia"];
 
@@ -36,35 +73,393 @@ pub fn is_supported_network(network: &str) -> bool {
 
 pub(crate) struct Ethereum;
 
-const ETHEREUM_NAME: &str = "ethereum";
-pub const LIGHTHOUSE_DATA_DIR: &str = "/root/.lighthouse";
-pub const LIGHTHOUSE_DATA_VOLUME: &str = "kittynode-lighthouse-data";
+pub(crate) const ETHEREUM_NAME: &str = "ethereum";
+pub(crate) const LIGHTHOUSE_DATA_DIR: &str = "/root/.lighthouse";
+pub(crate) const LIGHTHOUSE_DATA_VOLUME: &str = "kittynode-lighthouse-data";
 const ETHEREUM_NETWORK_RESOURCE: &str = "kittynode-ethereum-network";
-pub const LIGHTHOUSE_VALIDATOR_CONTAINER_NAME: &str = "kittynode-lighthouse-validator";
+pub(crate) const LIGHTHOUSE_VALIDATOR_CONTAINER_NAME: &str = "kittynode-lighthouse-validator";
+pub(crate) const LIGHTHOUSE_IMAGE: &str = "sigp/lighthouse";
+/// Reth release used unless the package config sets `reth_version`.
+pub const DEFAULT_RETH_VERSION: &str = "v1.9.3";
+/// Geth release used unless the package config sets `geth_version`.
+pub const DEFAULT_GETH_VERSION: &str = "v1.16.7";
+/// Nethermind release used unless the package config sets `nethermind_version`.
+pub const DEFAULT_NETHERMIND_VERSION: &str = "1.35.2";
+/// Besu release used unless the package config sets `besu_version`.
+pub const DEFAULT_BESU_VERSION: &str = "25.11.0";
+/// Erigon release used unless the package config sets `erigon_version`.
+pub const DEFAULT_ERIGON_VERSION: &str = "v3.2.2";
+/// Lighthouse release used unless the package config sets `lighthouse_version`.
+pub const DEFAULT_LIGHTHOUSE_VERSION: &str = "v8.1.3";
+/// Teku release used unless the package config sets `teku_version`.
+pub const DEFAULT_TEKU_VERSION: &str = "25.11.1";
+/// Prysm release used unless the package config sets `prysm_version`.
+pub const DEFAULT_PRYSM_VERSION: &str = "v7.0.0";
+/// Nimbus release used unless the package config sets `nimbus_version`.
+pub const DEFAULT_NIMBUS_VERSION: &str = "multiarch-v25.11.1";
+/// Lodestar release used unless the package config sets `lodestar_version`.
+pub const DEFAULT_LODESTAR_VERSION: &str = "v1.36.0";
+/// MEV-Boost release used unless the package config sets `mev_boost_version`.
+pub const DEFAULT_MEV_BOOST_VERSION: &str = "1.10.1";
 
 impl PackageDefinition for Ethereum {
     const NAME: &'static str = ETHEREUM_NAME;
 
     fn get_package() -> Result<Package> {
-        let saved_config = PackageConfigStore::load(ETHEREUM_NAME)?;
-        let containers = match settings::selected_network(&saved_config) {
-            Some(network) => build_ethereum_package_containers(&saved_config, network)?,
-            None => Vec::new(),
-        };
+        instance_package(None)
+    }
+
+    fn config_schema() -> Vec<ConfigField> {
+        let mut schema = vec![
+            ConfigField::new(
+                "network",
+                ConfigFieldKind::Enum,
+                "Ethereum network the node joins",
+            )
+            .with_values(supported_networks_iter())
+            .required(),
+            ConfigField::new(
+                "execution_client",
+                ConfigFieldKind::Enum,
+                "Execution client to run when no external execution endpoint is set",
+            )
+            .with_values(ExecutionClient::ALL.map(ExecutionClient::as_str))
+            .with_default(ExecutionClient::default().as_str()),
+            ConfigField::new(
+                "consensus_client",
+                ConfigFieldKind::Enum,
+                "Consensus client to run when no external beacon node endpoint is set",
+            )
+            .with_values(ConsensusClient::ALL.map(ConsensusClient::as_str))
+            .with_default(ConsensusClient::default().as_str()),
+            ConfigField::new(
+                "execution_endpoint",
+                ConfigFieldKind::Url,
+                "External execution client endpoint; leave empty to run the execution client locally",
+            ),
+            ConfigField::new(
+                "consensus_endpoint",
+                ConfigFieldKind::Url,
+                "External beacon node endpoint; leave empty to run the consensus client locally",
+            ),
+            ConfigField::new(
+                "validator_enabled",
+                ConfigFieldKind::Bool,
+                "Run a validator client alongside the node",
+            )
+            .with_default("false"),
+            ConfigField::new(
+                "validator_client",
+                ConfigFieldKind::Enum,
+                "Validator client to run; it uses the image pin of the matching consensus client",
+            )
+            .with_values(ValidatorClient::ALL.map(ValidatorClient::as_str))
+            .with_default(ValidatorClient::default().as_str()),
+            ConfigField::new(
+                "validator_fee_recipient",
+                ConfigFieldKind::Address,
+                "Address that receives execution layer rewards from proposed blocks",
+            ),
+            ConfigField::new(
+                "validator_graffiti",
+                ConfigFieldKind::String,
+                "Optional graffiti included in proposed blocks",
+            ),
+            ConfigField::new(
+                "mev_boost_enabled",
+                ConfigFieldKind::Bool,
+                "Run MEV-Boost so the validator can propose blocks from external builders",
+            )
+            .with_default("false"),
+            ConfigField::new(
+                "mev_boost_relays",
+                ConfigFieldKind::String,
+                "Comma-separated relay URLs MEV-Boost queries; each must serve the selected network",
+            ),
+            ConfigField::new(
+                "mev_boost_min_bid",
+                ConfigFieldKind::Number,
+                "Optional minimum builder bid in ETH, at most 1; lower bids fall back to a local block",
+            ),
+            ConfigField::new(
+                "validator_gas_limit",
+                ConfigFieldKind::Integer,
+                "Optional gas limit the validator client registers with MEV-Boost relays",
+            ),
+            ConfigField::new(
+                "validator_builder_boost_factor",
+                ConfigFieldKind::Integer,
+                "Optional percentage builder bids are weighed by against local blocks; 0 always builds locally. Ignored by Teku",
+            ),
+            ConfigField::new(
+                "reth_version",
+                ConfigFieldKind::ImageTag,
+                "Reth image tag to run",
+            )
+            .with_default(DEFAULT_RETH_VERSION),
+            ConfigField::new(
+                "reth_digest",
+                ConfigFieldKind::ImageDigest,
+                "Optional sha256 digest that pins the Reth image",
+            ),
+            ConfigField::new(
+                "lighthouse_version",
+                ConfigFieldKind::ImageTag,
+                "Lighthouse image tag to run",
+            )
+            .with_default(DEFAULT_LIGHTHOUSE_VERSION),
+            ConfigField::new(
+                "lighthouse_digest",
+                ConfigFieldKind::ImageDigest,
+                "Optional sha256 digest that pins the Lighthouse image",
+            ),
+            ConfigField::new(
+                "mev_boost_version",
+                ConfigFieldKind::ImageTag,
+                "MEV-Boost image tag to run",
+            )
+            .with_default(DEFAULT_MEV_BOOST_VERSION),
+            ConfigField::new(
+                "mev_boost_digest",
+                ConfigFieldKind::ImageDigest,
+                "Optional sha256 digest that pins the MEV-Boost image",
+            ),
+            ConfigField::new(
+                "execution_p2p_port",
+                ConfigFieldKind::Port,
+                "Execution client P2P port (TCP and UDP discovery)",
+            )
+            .with_default(&settings::DEFAULT_EXECUTION_P2P_PORT.to_string()),
+            ConfigField::new(
+                "execution_p2p_address",
+                ConfigFieldKind::IpAddress,
+                "Host address the execution client P2P port is published on",
+            )
+            .with_default(settings::ALL_INTERFACES),
+            ConfigField::new(
+                "execution_metrics_port",
+                ConfigFieldKind::Port,
+                "Execution client Prometheus metrics port",
+            )
+            .with_default(&settings::DEFAULT_EXECUTION_METRICS_PORT.to_string()),
+            ConfigField::new(
+                "execution_metrics_address",
+                ConfigFieldKind::IpAddress,
+                "Host address the execution client metrics port is published on",
+            )
+            .with_default(settings::ALL_INTERFACES),
+            ConfigField::new(
+                "execution_rpc_port",
+                ConfigFieldKind::Port,
+                "Execution client JSON-RPC port, used to check sync status and peers",
+            )
+            .with_default(&settings::DEFAULT_EXECUTION_RPC_PORT.to_string()),
+            ConfigField::new(
+                "execution_rpc_address",
+                ConfigFieldKind::IpAddress,
+                "Host address the execution client JSON-RPC port is published on",
+            )
+            .with_default(settings::LOCALHOST),
+            ConfigField::new(
+                "consensus_p2p_port",
+                ConfigFieldKind::Port,
+                "Consensus client P2P port (TCP and UDP discovery)",
+            )
+            .with_default(&settings::DEFAULT_CONSENSUS_P2P_PORT.to_string()),
+            ConfigField::new(
+                "consensus_quic_port",
+                ConfigFieldKind::Port,
+                "Consensus client QUIC port (UDP), used by Lighthouse and Prysm",
+            )
+            .with_default(&settings::DEFAULT_CONSENSUS_QUIC_PORT.to_string()),
+            ConfigField::new(
+                "consensus_p2p_address",
+                ConfigFieldKind::IpAddress,
+                "Host address the consensus client P2P and QUIC ports are published on",
+            )
+            .with_default(settings::ALL_INTERFACES),
+            ConfigField::new(
+                "consensus_http_port",
+                ConfigFieldKind::Port,
+                "Beacon node REST API port",
+            )
+            .with_default(&settings::DEFAULT_CONSENSUS_HTTP_PORT.to_string()),
+            ConfigField::new(
+                "consensus_http_address",
+                ConfigFieldKind::IpAddress,
+                "Host address the beacon node REST API is published on",
+            )
+            .with_default(settings::LOCALHOST),
+            ConfigField::new(
+                "restart_policy",
+                ConfigFieldKind::Enum,
+                "What Docker does when a client container exits or the host reboots",
+            )
+            .with_values(RestartPolicy::ALL.map(RestartPolicy::as_str))
+            .with_default(settings::DEFAULT_RESTART_POLICY.as_str()),
+            ConfigField::new(
+                "stop_timeout_secs",
+                ConfigFieldKind::Integer,
+                "Seconds clients get to shut down cleanly before Docker kills them",
+            )
+            .with_default(&settings::DEFAULT_STOP_TIMEOUT_SECS.to_string()),
+            ConfigField::new(
+                "execution_memory_limit_mib",
+                ConfigFieldKind::Integer,
+                "Optional execution client memory limit in MiB",
+            ),
+            ConfigField::new(
+                "execution_cpu_limit",
+                ConfigFieldKind::Number,
+                "Optional number of CPUs the execution client may use, such as 2.5",
+            ),
+            ConfigField::new(
+                "consensus_memory_limit_mib",
+                ConfigFieldKind::Integer,
+                "Optional beacon node memory limit in MiB",
+            ),
+            ConfigField::new(
+                "consensus_cpu_limit",
+                ConfigFieldKind::Number,
+                "Optional number of CPUs the beacon node may use",
+            ),
+            ConfigField::new(
+                "nofile_limit",
+                ConfigFieldKind::Integer,
+                "Optional open file limit for the execution client and the beacon node",
+            ),
+            ConfigField::new(
+                "execution_extra_args",
+                ConfigFieldKind::Arguments,
+                "Extra command-line arguments passed to the execution client",
+            ),
+            ConfigField::new(
+                "consensus_extra_args",
+                ConfigFieldKind::Arguments,
+                "Extra command-line arguments passed to the beacon node",
+            ),
+            ConfigField::new(
+                "validator_extra_args",
+                ConfigFieldKind::Arguments,
+                "Extra command-line arguments passed to the validator client",
+            ),
+        ];
+        // Reth and Lighthouse image fields come first above; the other clients follow the same key
+        // pattern.
+        for client in ExecutionClient::ALL
+            .into_iter()
+            .filter(|client| *client != ExecutionClient::Reth)
+        {
+            schema.push(
+                ConfigField::new(
+                    &format!("{}_version", client.as_str()),
+                    ConfigFieldKind::ImageTag,
+                    &format!("{} image tag to run", client.display_name()),
+                )
+                .with_default(client.default_version()),
+            );
+            schema.push(ConfigField::new(
+                &format!("{}_digest", client.as_str()),
+                ConfigFieldKind::ImageDigest,
+                &format!(
+                    "Optional sha256 digest that pins the {} image",
+                    client.display_name()
+                ),
+            ));
+        }
+        for client in ConsensusClient::ALL
+            .into_iter()
+            .filter(|client| *client != ConsensusClient::Lighthouse)
+        {
+            schema.push(
+                ConfigField::new(
+                    &format!("{}_version", client.as_str()),
+                    ConfigFieldKind::ImageTag,
+                    &format!("{} image tag to run", client.display_name()),
+                )
+                .with_default(client.default_version()),
+            );
+            schema.push(ConfigField::new(
+                &format!("{}_digest", client.as_str()),
+                ConfigFieldKind::ImageDigest,
+                &format!(
+                    "Optional sha256 digest that pins the {} image",
+                    client.display_name()
+                ),
+            ));
+        }
+        schema
+    }
+}
+
+/// Builds the Ethereum package for the default instance or the named `instance`.
+pub(crate) fn instance_package(instance: Option<&str>) -> Result<Package> {
+    let name = instance_name(ETHEREUM_NAME, instance);
+    let saved_config = PackageConfigStore::load(&name)?;
+    let containers = match settings::selected_network(&saved_config) {
+        Some(network) => build_ethereum_package_containers(&saved_config, network, instance)?,
+        None => Vec::new(),
+    };
+
+    Ok(ethereum_package(instance, containers))
+}
+
+fn ethereum_package(instance: Option<&str>, containers: Vec<Container>) -> Package {
+    Package {
+        name: instance_name(ETHEREUM_NAME, instance),
+        description: "This package installs an Ethereum node.".to_string(),
+        network_name: scoped_resource_name(ETHEREUM_NETWORK_RESOURCE, instance),
+        default_config: PackageConfig::from_schema_defaults(&Ethereum::config_schema()),
+        config_schema: Ethereum::config_schema(),
+        containers,
+        manifest_path: None,
+    }
+}
+
+/// Builds the Ethereum package for `config` without generating a JWT secret or downloading
+/// Ephemery metadata, so that a planned change can be previewed without touching the host.
+pub(crate) fn preview_package(config: &PackageConfig, instance: Option<&str>) -> Result<Package> {
+    let Some(network) = settings::selected_network(config) else {
+        return Ok(ethereum_package(instance, Vec::new()));
+    };
+    if !is_supported_network(network) {
+        return Err(eyre!("Unsupported Ethereum network: {network}"));
+    }
+
+    let root = kittynode_path()?;
+    let settings = settings::ethereum_settings_from_config(config);
+    let ephemery = (network == EPHEMERY_NETWORK_NAME).then(|| EphemeryConfig {
+        tag: String::new(),
+        metadata_dir: ephemery_dir(&root).join("current").join("metadata"),
+        execution_bootnodes: Vec::new(),
+        consensus_bootnodes: Vec::new(),
+    });
+    let containers = containers::build_ethereum_containers(
+        network,
+        &settings,
+        &resources(&root, instance, &settings),
+        ephemery.as_ref(),
+    )?;
+    Ok(ethereum_package(instance, containers))
+}
 
-        Ok(Package {
-            name: ETHEREUM_NAME.to_string(),
-            description: "This package installs an Ethereum node.".to_string(),
-            network_name: ETHEREUM_NETWORK_RESOURCE.to_string(),
-            default_config: PackageConfig::new(),
-            containers,
-        })
+fn resources(
+    root: &Path,
+    instance: Option<&str>,
+    settings: &settings::EthereumSettings,
+) -> containers::EthereumResources {
+    let package_root =
+        PackageConfigStore::package_dir(root, &instance_name(ETHEREUM_NAME, instance));
+    containers::EthereumResources {
+        jwt_source_path: package_root.join("jwt.hex").display().to_string(),
+        names: containers::ResourceNames::new(instance, settings),
     }
 }
 
 fn build_ethereum_package_containers(
     config: &PackageConfig,
     network: &str,
+    instance: Option<&str>,
 ) -> Result<Vec<Container>> {
     if !is_supported_network(network) {
         return Err(eyre!("Unsupported Ethereum network: {network}"));
@@ -72,14 +467,11 @@ fn build_ethereum_package_containers(
 
     let settings = settings::ethereum_settings_from_config(config);
     if settings.runs_local_node() {
-        generate_jwt_secret(ETHEREUM_NAME)
+        generate_jwt_secret(&instance_name(ETHEREUM_NAME, instance))
             .wrap_err("Failed to ensure JWT secret for Ethereum package")?;
     }
 
-    let package_root = PackageConfigStore::package_dir(&kittynode_path()?, ETHEREUM_NAME);
-    let resources = containers::EthereumResourcePaths {
-        jwt_source_path: package_root.join("jwt.hex").display().to_string(),
-    };
+    let resources = resources(&kittynode_path()?, instance, &settings);
     let ephemery = if network == EPHEMERY_NETWORK_NAME {
         Some(ensure_ephemery_config()?)
     } else {
@@ -89,8 +481,322 @@ fn build_ethereum_package_containers(
     containers::build_ethereum_containers(network, &settings, &resources, ephemery.as_ref())
 }
 
+/// Returns the names of the default instance and every named Ethereum instance.
+fn instance_names(base_dir: &Path) -> Result<Vec<String>> {
+    let instances = list_instances(base_dir, ETHEREUM_NAME)?;
+    Ok(iter::once(ETHEREUM_NAME.to_string())
+        .chain(
+            instances
+                .iter()
+                .map(|instance| instance_name(ETHEREUM_NAME, Some(instance))),
+        )
+        .collect())
+}
+
+/// Returns host ports for a named instance that no other Ethereum instance is configured to use.
+///
+/// Returns `None` when `config` already sets any of those ports, so the assignment happens once
+/// and stays stable across reinstalls.
+pub(crate) fn instance_port_assignment(
+    base_dir: &Path,
+    instance: &str,
+    config: &PackageConfig,
+) -> Result<Option<Vec<(String, String)>>> {
+    if settings::has_port_overrides(config) {
+        return Ok(None);
+    }
+
+    let name = instance_name(ETHEREUM_NAME, Some(instance));
+    let mut taken = HashSet::new();
+    for other in instance_names(base_dir)? {
+        if other != name {
+            let other_config = PackageConfigStore::load_from(base_dir, &other)?;
+            taken.extend(
+                settings::ethereum_settings_from_config(&other_config)
+                    .ports
+                    .host_ports(),
+            );
+        }
+    }
+    settings::instance_port_config(&taken)
+        .map(Some)
+        .ok_or_else(|| eyre!("Could not find free host ports for Ethereum instance '{instance}'"))
+}
+
+/// Saves the ports from [`instance_port_assignment`] into the instance's config.
+pub(crate) fn assign_instance_ports(base_dir: &Path, instance: &str) -> Result<()> {
+    let name = instance_name(ETHEREUM_NAME, Some(instance));
+    let mut config = PackageConfigStore::load_from(base_dir, &name)?;
+    if let Some(ports) = instance_port_assignment(base_dir, instance, &config)? {
+        config.values.extend(ports);
+        PackageConfigStore::save_to(base_dir, &name, &config)?;
+    }
+    Ok(())
+}
+
+/// Returns true when an Ethereum instance other than `except` is configured for Ephemery, in
+/// which case the shared Ephemery cache has to stay.
+pub(crate) fn ephemery_cache_in_use(base_dir: &Path, except: &str) -> Result<bool> {
+    for name in instance_names(base_dir)? {
+        if name != except
+            && settings::selected_network(&PackageConfigStore::load_from(base_dir, &name)?)
+                == Some(EPHEMERY_NETWORK_NAME)
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Imports the keystores in `keys_dir` into the validator client selected by `config` merged
+/// over the saved config of the default or named `instance`.
+///
+/// Every keystore must be encrypted with `password`. The validator client container is removed
+/// first so it never runs while its keys change; updating the package config starts it again.
+pub async fn import_validator_keys(
+    instance: Option<&str>,
+    config: &PackageConfig,
+    keys_dir: &Path,
+    password: &str,
+) -> Result<ValidatorKeyImport> {
+    let merged = merge_config_update(&resolve_instance_name(ETHEREUM_NAME, instance)?, config)?;
+    key_import::import_validator_keys(instance, &merged, keys_dir, password).await
+}
+
+/// Reports whether the clients of the default or named Ethereum `instance` are reachable, synced
+/// and connected to peers, querying `execution_endpoint` and `consensus_endpoint` when they are
+/// set.
+pub async fn node_health(instance: Option<&str>) -> Result<NodeHealth> {
+    let (_, settings) = configured_settings(instance)?;
+    Ok(node_health::node_health(&settings).await)
+}
+
+/// Reports where each validator key in `keys_path` stands on the beacon chain, as seen by the
+/// beacon node of the default or named Ethereum `instance`.
+///
+/// `keys_path` is a directory of keystores or deposit data files, or a single such file.
+pub async fn validator_status(
+    instance: Option<&str>,
+    keys_path: &Path,
+) -> Result<Vec<ValidatorStatus>> {
+    let (_, settings) = configured_settings(instance)?;
+    let pubkeys = validator_status::read_validator_pubkeys(keys_path)?;
+    validator_status::validator_statuses(settings.beacon_api_url(), pubkeys).await
+}
+
+/// Returns the statuses of the `pubkeys` that already have a deposit on the beacon chain of the
+/// default or named Ethereum `instance`.
+///
+/// Fails when the instance runs a different network than `network`, since its chain cannot tell
+/// whether the keys were deposited on `network`.
+pub async fn deposited_validators(
+    instance: Option<&str>,
+    network: &str,
+    pubkeys: Vec<String>,
+) -> Result<Vec<ValidatorStatus>> {
+    let (configured_network, settings) = configured_settings(instance)?;
+    if configured_network != network {
+        return Err(eyre!(
+            "The Ethereum node runs {configured_network}, not {network}"
+        ));
+    }
+    Ok(
+        validator_status::validator_statuses(settings.beacon_api_url(), pubkeys)
+            .await?
+            .into_iter()
+            .filter(|status| status.state != ValidatorState::NotDeposited)
+            .collect(),
+    )
+}
+
+/// Signs a voluntary exit with the keystore at `keystore_path` and submits it to the beacon node
+/// of the default or named Ethereum `instance`.
+///
+/// The validator index is looked up on the beacon node when not given. Once the exit is
+/// included on chain it cannot be undone.
+pub async fn submit_voluntary_exit(
+    instance: Option<&str>,
+    keystore_path: &Path,
+    password: Zeroizing<String>,
+    validator_index: Option<u64>,
+    epoch: u64,
+) -> Result<SignedVoluntaryExit> {
+    let (network, settings) = configured_settings(instance)?;
+    let beacon_url = settings.beacon_api_url();
+    let validator_index = match validator_index {
+        Some(index) => index,
+        None => {
+            let pubkeys = validator_status::read_validator_pubkeys(keystore_path)?;
+            let status = validator_status::validator_statuses(beacon_url.clone(), pubkeys)
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| eyre!("No validator found in {}", keystore_path.display()))?;
+            status.index.ok_or_else(|| {
+                eyre!(
+                    "Validator {} has no index because its deposit has not been processed",
+                    status.pubkey
+                )
+            })?
+        }
+    };
+
+    let request = VoluntaryExitRequest {
+        keystore_path: keystore_path.to_path_buf(),
+        password,
+        network,
+        validator_index,
+        epoch,
+        genesis_validators_root: Some(
+            operation_pool::genesis_validators_root(beacon_url.clone()).await?,
+        ),
+    };
+    let exit = task::spawn_blocking(move || sign_voluntary_exit(request)).await??;
+    operation_pool::submit_voluntary_exit(beacon_url, exit.clone()).await?;
+    Ok(exit)
+}
+
+/// Signs changes of the withdrawal credentials of `validator_indices` to `execution_address`
+/// with withdrawal keys derived from the mnemonic, and submits them to the beacon node of the
+/// default or named Ethereum `instance`.
+///
+/// The first validator uses the key at `start_index` in the mnemonic and the rest follow in
+/// order. Nothing is submitted unless every derived key matches the 0x00 withdrawal credentials
+/// the beacon node reports for its validator.
+pub async fn submit_bls_to_execution_changes(
+    instance: Option<&str>,
+    mnemonic_phrase: Zeroizing<String>,
+    start_index: u32,
+    validator_indices: Vec<u64>,
+    execution_address: Address,
+) -> Result<BlsToExecutionChanges> {
+    let (network, settings) = configured_settings(instance)?;
+    let beacon_url = settings.beacon_api_url();
+    let request = BlsToExecutionChangeRequest {
+        mnemonic_phrase,
+        network,
+        start_index,
+        validator_indices: validator_indices.clone(),
+        execution_address,
+        genesis_validators_root: Some(
+            operation_pool::genesis_validators_root(beacon_url.clone()).await?,
+        ),
+    };
+    let signed = task::spawn_blocking(move || sign_bls_to_execution_changes(request)).await??;
+
+    let current =
+        operation_pool::withdrawal_credentials(beacon_url.clone(), validator_indices).await?;
+    for change in &signed.changes {
+        let index = change.message.validator_index;
+        let credentials = current
+            .get(&index)
+            .ok_or_else(|| eyre!("Validator {index} not found on the beacon chain"))?;
+        if !credentials.starts_with("0x00") {
+            return Err(eyre!(
+                "Validator {index} already has execution withdrawal credentials {credentials}"
+            ));
+        }
+        if *credentials != bls_withdrawal_credentials(&change.message.from_bls_pubkey)? {
+            return Err(eyre!(
+                "The withdrawal key derived for validator {index} doesn't match its withdrawal credentials; check the mnemonic and start index"
+            ));
+        }
+    }
+
+    operation_pool::submit_bls_to_execution_changes(beacon_url, signed.changes.clone()).await?;
+    Ok(signed)
+}
+
+/// Loads the network and settings of an Ethereum instance that has a network selected.
+fn configured_settings(instance: Option<&str>) -> Result<(String, settings::EthereumSettings)> {
+    let name = resolve_instance_name(ETHEREUM_NAME, instance)?;
+    let config = PackageConfigStore::load(&name)?;
+    let network = settings::selected_network(&config)
+        .ok_or_else(|| eyre!("Ethereum node config not found for {name}"))?
+        .to_string();
+    Ok((network, settings::ethereum_settings_from_config(&config)))
+}
+
+/// Returns the health diagnostics of every Ethereum instance with a network selected, each
+/// prefixed with its instance name.
+pub(crate) async fn node_diagnostics() -> Result<Vec<String>> {
+    let mut diagnostics = Vec::new();
+    for name in instance_names(&kittynode_path()?)? {
+        let config = PackageConfigStore::load(&name)?;
+        if settings::selected_network(&config).is_none() {
+            continue;
+        }
+        let settings = settings::ethereum_settings_from_config(&config);
+        let health = node_health::node_health(&settings).await;
+        diagnostics.extend(
+            health
+                .diagnostics()
+                .into_iter()
+                .map(|diagnostic| format!("{name}: {diagnostic}")),
+        );
+    }
+    Ok(diagnostics)
+}
+
 pub async fn is_validator_installed() -> Result<bool> {
     let docker = get_docker_instance().await?;
-    let containers = find_container(&docker, LIGHTHOUSE_VALIDATOR_CONTAINER_NAME).await?;
-    Ok(!containers.is_empty())
+    for client in ValidatorClient::ALL {
+        if !find_container(&docker, client.container_name())
+            .await?
+            .is_empty()
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assign_instance_ports, ephemery_cache_in_use};
+    use crate::packages::{PackageConfig, PackageConfigStore};
+    use tempfile::tempdir;
+
+    fn save(base_dir: &std::path::Path, name: &str, values: &[(&str, &str)]) {
+        let mut config = PackageConfig::new();
+        for (key, value) in values {
+            config.values.insert(key.to_string(), value.to_string());
+        }
+        PackageConfigStore::save_to(base_dir, name, &config).expect("save config");
+    }
+
+    #[test]
+    fn assign_instance_ports_avoids_other_instances_and_is_stable() {
+        let temp_dir = tempdir().expect("tempdir");
+        let base_dir = temp_dir.path().join(".config").join("kittynode");
+        save(&base_dir, "ethereum", &[("network", "mainnet")]);
+        save(&base_dir, "ethereum@hoodi", &[("network", "hoodi")]);
+        save(&base_dir, "ethereum@ephemery", &[("network", "ephemery")]);
+
+        assign_instance_ports(&base_dir, "hoodi").expect("ports should be assigned");
+        assign_instance_ports(&base_dir, "ephemery").expect("ports should be assigned");
+        assign_instance_ports(&base_dir, "hoodi").expect("reassigning is a no-op");
+
+        let port = |name: &str| {
+            PackageConfigStore::load_from(&base_dir, name)
+                .expect("load config")
+                .values
+                .get("execution_p2p_port")
+                .cloned()
+        };
+        assert_eq!(port("ethereum"), None);
+        assert_eq!(port("ethereum@hoodi").as_deref(), Some("30313"));
+        assert_eq!(port("ethereum@ephemery").as_deref(), Some("30323"));
+    }
+
+    #[test]
+    fn ephemery_cache_in_use_ignores_the_deleted_instance() {
+        let temp_dir = tempdir().expect("tempdir");
+        let base_dir = temp_dir.path().join(".config").join("kittynode");
+        save(&base_dir, "ethereum", &[("network", "ephemery")]);
+        save(&base_dir, "ethereum@hoodi", &[("network", "hoodi")]);
+
+        assert!(!ephemery_cache_in_use(&base_dir, "ethereum").expect("check should succeed"));
+        assert!(ephemery_cache_in_use(&base_dir, "ethereum@hoodi").expect("check should succeed"));
+    }
 }
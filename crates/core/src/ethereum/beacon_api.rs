@@ -0,0 +1,69 @@
+use eyre::{Result, eyre};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Applies to each request so that an unreachable node doesn't hold up status reports.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub(crate) fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build()
+}
+
+/// Fetches a beacon API path and returns its `data` field.
+pub(crate) fn get_data(agent: &ureq::Agent, base: &str, path: &str) -> Result<Value> {
+    let response = agent
+        .get(&format!("{}{path}", base.trim_end_matches('/')))
+        .call()
+        .map_err(|error| eyre!("{path} failed: {error}"))?;
+    data(path, response)
+}
+
+/// Posts `body` to a beacon API path and returns its `data` field.
+pub(crate) fn post_data(
+    agent: &ureq::Agent,
+    base: &str,
+    path: &str,
+    body: &Value,
+) -> Result<Value> {
+    data(path, post(agent, base, path, body)?)
+}
+
+/// Posts `body` to a beacon API path, surfacing the node's reason when it rejects the request.
+pub(crate) fn post(
+    agent: &ureq::Agent,
+    base: &str,
+    path: &str,
+    body: &Value,
+) -> Result<ureq::Response> {
+    agent
+        .post(&format!("{}{path}", base.trim_end_matches('/')))
+        .send_json(body)
+        .map_err(|error| match error {
+            ureq::Error::Status(code, response) => {
+                match response
+                    .into_json::<Value>()
+                    .ok()
+                    .and_then(|body| body.get("message")?.as_str().map(str::to_string))
+                {
+                    Some(message) => eyre!("{path} failed with status {code}: {message}"),
+                    None => eyre!("{path} failed with status {code}"),
+                }
+            }
+            error => eyre!("{path} failed: {error}"),
+        })
+}
+
+fn data(path: &str, response: ureq::Response) -> Result<Value> {
+    let response: Value = response
+        .into_json()
+        .map_err(|error| eyre!("{path} returned invalid JSON: {error}"))?;
+    response
+        .get("data")
+        .cloned()
+        .ok_or_else(|| eyre!("{path} returned no data"))
+}
+
+/// Beacon API numbers are decimal strings.
+pub(crate) fn parse_decimal(value: &Value) -> Option<u64> {
+    value.as_str().and_then(|number| number.parse().ok())
+}
@@ -0,0 +1,443 @@
+use super::execution::EPHEMERY_MOUNT;
+use crate::ethereum::{
+    DEFAULT_LIGHTHOUSE_VERSION, DEFAULT_LODESTAR_VERSION, DEFAULT_NIMBUS_VERSION,
+    DEFAULT_PRYSM_VERSION, DEFAULT_TEKU_VERSION, EphemeryConfig, LIGHTHOUSE_DATA_DIR,
+    LIGHTHOUSE_DATA_VOLUME, LIGHTHOUSE_IMAGE,
+};
+
+/// Path every consensus client serves its health endpoint on through the beacon REST API.
+pub(crate) const BEACON_HEALTH_PATH: &str = "/eth/v1/node/health";
+
+/// Consensus layer clients the Ethereum package can run as its beacon node.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum ConsensusClient {
+    #[default]
+    Lighthouse,
+    Teku,
+    Prysm,
+    Nimbus,
+    Lodestar,
+}
+
+/// Host ports a beacon node listens on, already resolved from package config.
+pub(crate) struct ConsensusPorts {
+    pub p2p: u16,
+    pub quic: u16,
+    pub http: u16,
+}
+
+/// Where the beacon node finds its execution client, the state it syncs from and, with
+/// MEV-Boost enabled, its block builder.
+pub(crate) struct BeaconSources<'a> {
+    pub execution_endpoint: &'a str,
+    pub checkpoint_sync_url: &'a str,
+    pub builder_endpoint: Option<&'a str>,
+}
+
+impl ConsensusClient {
+    pub const ALL: [Self; 5] = [
+        Self::Lighthouse,
+        Self::Teku,
+        Self::Prysm,
+        Self::Nimbus,
+        Self::Lodestar,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lighthouse => "lighthouse",
+            Self::Teku => "teku",
+            Self::Prysm => "prysm",
+            Self::Nimbus => "nimbus",
+            Self::Lodestar => "lodestar",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|client| client.as_str() == value)
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Lighthouse => "Lighthouse",
+            Self::Teku => "Teku",
+            Self::Prysm => "Prysm",
+            Self::Nimbus => "Nimbus",
+            Self::Lodestar => "Lodestar",
+        }
+    }
+
+    pub fn image(self) -> &'static str {
+        match self {
+            Self::Lighthouse => LIGHTHOUSE_IMAGE,
+            Self::Teku => "consensys/teku",
+            Self::Prysm => "gcr.io/offchainlabs/prysm/beacon-chain",
+            Self::Nimbus => "statusim/nimbus-eth2",
+            Self::Lodestar => "chainsafe/lodestar",
+        }
+    }
+
+    pub fn default_version(self) -> &'static str {
+        match self {
+            Self::Lighthouse => DEFAULT_LIGHTHOUSE_VERSION,
+            Self::Teku => DEFAULT_TEKU_VERSION,
+            Self::Prysm => DEFAULT_PRYSM_VERSION,
+            Self::Nimbus => DEFAULT_NIMBUS_VERSION,
+            Self::Lodestar => DEFAULT_LODESTAR_VERSION,
+        }
+    }
+
+    pub fn container_name(self) -> &'static str {
+        match self {
+            Self::Lighthouse => "kittynode-lighthouse-node",
+            Self::Teku => "kittynode-teku-node",
+            Self::Prysm => "kittynode-prysm-node",
+            Self::Nimbus => "kittynode-nimbus-node",
+            Self::Lodestar => "kittynode-lodestar-node",
+        }
+    }
+
+    pub fn data_volume(self) -> &'static str {
+        match self {
+            Self::Lighthouse => LIGHTHOUSE_DATA_VOLUME,
+            Self::Teku => "kittynode-teku-data",
+            Self::Prysm => "kittynode-prysm-data",
+            Self::Nimbus => "kittynode-nimbus-data",
+            Self::Lodestar => "kittynode-lodestar-data",
+        }
+    }
+
+    /// Directory the data volume is mounted on. Images that run as a non-root user need a
+    /// directory their user already owns.
+    pub fn data_dir(self) -> &'static str {
+        match self {
+            Self::Lighthouse => LIGHTHOUSE_DATA_DIR,
+            Self::Teku => "/var/lib/teku",
+            Self::Prysm => "/data",
+            Self::Nimbus => "/home/user/data",
+            Self::Lodestar => "/data",
+        }
+    }
+
+    /// The JWT secret is mounted read-only inside the data directory.
+    pub fn jwt_path(self, network: &str) -> String {
+        match self {
+            Self::Lighthouse => format!("{LIGHTHOUSE_DATA_DIR}/{network}/jwt.hex"),
+            _ => format!("{}/jwt.hex", self.data_dir()),
+        }
+    }
+
+    /// Whether the client also listens for QUIC peers next to its TCP and UDP P2P port.
+    pub fn uses_quic(self) -> bool {
+        matches!(self, Self::Lighthouse | Self::Prysm)
+    }
+
+    pub fn command(
+        self,
+        network: &str,
+        ports: &ConsensusPorts,
+        sources: &BeaconSources<'_>,
+        ephemery: Option<&EphemeryConfig>,
+    ) -> Vec<String> {
+        let jwt_path = self.jwt_path(network);
+        let bootnodes = ephemery
+            .map(|ephemery| ephemery.consensus_bootnodes.as_slice())
+            .unwrap_or_default();
+        let mut command = match self {
+            Self::Lighthouse => {
+                lighthouse_command(network, &jwt_path, ports, sources, ephemery, bootnodes)
+            }
+            Self::Teku => teku_command(network, &jwt_path, ports, sources, ephemery, bootnodes),
+            Self::Prysm => prysm_command(network, &jwt_path, ports, sources, ephemery, bootnodes),
+            Self::Nimbus => nimbus_command(network, &jwt_path, ports, sources, ephemery, bootnodes),
+            Self::Lodestar => {
+                lodestar_command(network, &jwt_path, ports, sources, ephemery, bootnodes)
+            }
+        };
+        if let Some(builder_endpoint) = sources.builder_endpoint {
+            command.extend(self.builder_args(builder_endpoint));
+        }
+        command
+    }
+
+    fn builder_args(self, builder_endpoint: &str) -> Vec<String> {
+        match self {
+            Self::Lighthouse => vec!["--builder".to_string(), builder_endpoint.to_string()],
+            Self::Teku => vec![format!("--builder-endpoint={builder_endpoint}")],
+            Self::Prysm => vec![format!("--http-mev-relay={builder_endpoint}")],
+            Self::Nimbus => vec![
+                "--payload-builder=true".to_string(),
+                format!("--payload-builder-url={builder_endpoint}"),
+            ],
+            Self::Lodestar => vec![
+                "--builder".to_string(),
+                format!("--builder.urls={builder_endpoint}"),
+            ],
+        }
+    }
+}
+
+fn lighthouse_command(
+    network: &str,
+    jwt_path: &str,
+    ports: &ConsensusPorts,
+    sources: &BeaconSources<'_>,
+    ephemery: Option<&EphemeryConfig>,
+    bootnodes: &[String],
+) -> Vec<String> {
+    let mut command = vec!["lighthouse".to_string()];
+    if ephemery.is_some() {
+        command.push("--testnet-dir".to_string());
+        command.push(EPHEMERY_MOUNT.to_string());
+    } else {
+        command.push("--network".to_string());
+        command.push(network.to_string());
+    }
+    command.extend([
+        "beacon".to_string(),
+        "--http".to_string(),
+        "--http-address".to_string(),
+        "0.0.0.0".to_string(),
+        "--http-port".to_string(),
+        ports.http.to_string(),
+        "--port".to_string(),
+        ports.p2p.to_string(),
+        "--discovery-port".to_string(),
+        ports.p2p.to_string(),
+        "--quic-port".to_string(),
+        ports.quic.to_string(),
+        "--checkpoint-sync-url".to_string(),
+        sources.checkpoint_sync_url.to_string(),
+        "--execution-jwt".to_string(),
+        jwt_path.to_string(),
+        "--execution-endpoint".to_string(),
+        sources.execution_endpoint.to_string(),
+    ]);
+    if !bootnodes.is_empty() {
+        command.push("--boot-nodes".to_string());
+        command.push(bootnodes.join(","));
+    }
+    command
+}
+
+fn teku_command(
+    network: &str,
+    jwt_path: &str,
+    ports: &ConsensusPorts,
+    sources: &BeaconSources<'_>,
+    ephemery: Option<&EphemeryConfig>,
+    bootnodes: &[String],
+) -> Vec<String> {
+    let mut command = vec![match ephemery {
+        Some(_) => format!("--network={EPHEMERY_MOUNT}/config.yaml"),
+        None => format!("--network={network}"),
+    }];
+    command.extend([
+        format!("--data-path={}", ConsensusClient::Teku.data_dir()),
+        format!("--p2p-port={}", ports.p2p),
+        "--rest-api-enabled=true".to_string(),
+        "--rest-api-interface=0.0.0.0".to_string(),
+        format!("--rest-api-port={}", ports.http),
+        "--rest-api-host-allowlist=*".to_string(),
+        format!("--checkpoint-sync-url={}", sources.checkpoint_sync_url),
+        format!("--ee-endpoint={}", sources.execution_endpoint),
+        format!("--ee-jwt-secret-file={jwt_path}"),
+    ]);
+    if !bootnodes.is_empty() {
+        command.push(format!("--p2p-discovery-bootnodes={}", bootnodes.join(",")));
+    }
+    command
+}
+
+fn prysm_command(
+    network: &str,
+    jwt_path: &str,
+    ports: &ConsensusPorts,
+    sources: &BeaconSources<'_>,
+    ephemery: Option<&EphemeryConfig>,
+    bootnodes: &[String],
+) -> Vec<String> {
+    let mut command = match ephemery {
+        Some(_) => vec![
+            format!("--chain-config-file={EPHEMERY_MOUNT}/config.yaml"),
+            format!("--genesis-state={EPHEMERY_MOUNT}/genesis.ssz"),
+        ],
+        None => vec![format!("--{network}")],
+    };
+    command.extend([
+        format!("--datadir={}", ConsensusClient::Prysm.data_dir()),
+        "--accept-terms-of-use".to_string(),
+        format!("--p2p-tcp-port={}", ports.p2p),
+        format!("--p2p-udp-port={}", ports.p2p),
+        format!("--p2p-quic-port={}", ports.quic),
+        "--http-host=0.0.0.0".to_string(),
+        format!("--http-port={}", ports.http),
+        format!("--checkpoint-sync-url={}", sources.checkpoint_sync_url),
+        format!("--genesis-beacon-api-url={}", sources.checkpoint_sync_url),
+        format!("--execution-endpoint={}", sources.execution_endpoint),
+        format!("--jwt-secret={jwt_path}"),
+    ]);
+    command.extend(
+        bootnodes
+            .iter()
+            .map(|bootnode| format!("--bootstrap-node={bootnode}")),
+    );
+    command
+}
+
+fn nimbus_command(
+    network: &str,
+    jwt_path: &str,
+    ports: &ConsensusPorts,
+    sources: &BeaconSources<'_>,
+    ephemery: Option<&EphemeryConfig>,
+    bootnodes: &[String],
+) -> Vec<String> {
+    let mut command = vec![match ephemery {
+        Some(_) => format!("--network={EPHEMERY_MOUNT}"),
+        None => format!("--network={network}"),
+    }];
+    command.extend([
+        format!("--data-dir={}", ConsensusClient::Nimbus.data_dir()),
+        format!("--tcp-port={}", ports.p2p),
+        format!("--udp-port={}", ports.p2p),
+        "--rest".to_string(),
+        "--rest-address=0.0.0.0".to_string(),
+        format!("--rest-port={}", ports.http),
+        // Nimbus only syncs from a checkpoint when it starts with an empty database.
+        format!("--external-beacon-api-url={}", sources.checkpoint_sync_url),
+        format!("--el={}", sources.execution_endpoint),
+        format!("--jwt-secret={jwt_path}"),
+    ]);
+    command.extend(
+        bootnodes
+            .iter()
+            .map(|bootnode| format!("--bootstrap-node={bootnode}")),
+    );
+    command
+}
+
+fn lodestar_command(
+    network: &str,
+    jwt_path: &str,
+    ports: &ConsensusPorts,
+    sources: &BeaconSources<'_>,
+    ephemery: Option<&EphemeryConfig>,
+    bootnodes: &[String],
+) -> Vec<String> {
+    let mut command = vec!["beacon".to_string()];
+    match ephemery {
+        Some(_) => command.extend([
+            format!("--paramsFile={EPHEMERY_MOUNT}/config.yaml"),
+            format!("--genesisStateFile={EPHEMERY_MOUNT}/genesis.ssz"),
+        ]),
+        None => command.push(format!("--network={network}")),
+    }
+    command.extend([
+        format!("--dataDir={}", ConsensusClient::Lodestar.data_dir()),
+        format!("--port={}", ports.p2p),
+        "--rest".to_string(),
+        "--rest.address=0.0.0.0".to_string(),
+        format!("--rest.port={}", ports.http),
+        format!("--checkpointSyncUrl={}", sources.checkpoint_sync_url),
+        format!("--execution.urls={}", sources.execution_endpoint),
+        format!("--jwt-secret={jwt_path}"),
+    ]);
+    if !bootnodes.is_empty() {
+        command.push(format!("--bootnodes={}", bootnodes.join(",")));
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BeaconSources, ConsensusClient, ConsensusPorts};
+    use crate::ethereum::EphemeryConfig;
+    use crate::ethereum::execution::EPHEMERY_MOUNT;
+    use std::path::PathBuf;
+
+    const PORTS: ConsensusPorts = ConsensusPorts {
+        p2p: 9000,
+        quic: 9001,
+        http: 5052,
+    };
+    const SOURCES: BeaconSources<'static> = BeaconSources {
+        execution_endpoint: "http://kittynode-reth-node:8551",
+        checkpoint_sync_url: "https://checkpoint.example",
+        builder_endpoint: None,
+    };
+
+    #[test]
+    fn client_names_round_trip() {
+        for client in ConsensusClient::ALL {
+            assert_eq!(ConsensusClient::parse(client.as_str()), Some(client));
+        }
+        assert_eq!(ConsensusClient::parse("grandine"), None);
+    }
+
+    #[test]
+    fn every_client_follows_the_execution_client_and_serves_the_rest_api() {
+        for client in ConsensusClient::ALL {
+            let args = client.command("hoodi", &PORTS, &SOURCES, None).join(" ");
+            for expected in [
+                SOURCES.execution_endpoint,
+                SOURCES.checkpoint_sync_url,
+                &client.jwt_path("hoodi"),
+                "5052",
+                "9000",
+            ] {
+                assert!(
+                    args.contains(expected),
+                    "{client:?} lacks {expected}: {args}"
+                );
+            }
+            assert_eq!(
+                args.contains("9001"),
+                client.uses_quic(),
+                "{client:?}: {args}"
+            );
+            assert!(!args.contains("builder"), "{client:?}: {args}");
+        }
+    }
+
+    #[test]
+    fn every_client_points_at_mev_boost_when_enabled() {
+        let sources = BeaconSources {
+            builder_endpoint: Some("http://kittynode-mev-boost:18550"),
+            ..SOURCES
+        };
+        for client in ConsensusClient::ALL {
+            let args = client.command("hoodi", &PORTS, &sources, None).join(" ");
+            assert!(
+                args.contains("http://kittynode-mev-boost:18550"),
+                "{client:?}: {args}"
+            );
+        }
+    }
+
+    #[test]
+    fn ephemery_points_each_client_at_the_testnet_files() {
+        let ephemery = EphemeryConfig {
+            tag: "ephemery-1".to_string(),
+            metadata_dir: PathBuf::from("/tmp/ephemery"),
+            execution_bootnodes: Vec::new(),
+            consensus_bootnodes: vec!["enr:-a".to_string(), "enr:-b".to_string()],
+        };
+        for client in ConsensusClient::ALL {
+            let args = client.command("ephemery", &PORTS, &SOURCES, Some(&ephemery));
+            assert!(
+                args.iter().any(|arg| arg.contains(EPHEMERY_MOUNT)),
+                "{client:?}: {args:?}"
+            );
+            assert!(
+                !args.iter().any(|arg| arg.ends_with("=ephemery")),
+                "{client:?} should not pass Ephemery as a named network: {args:?}"
+            );
+            let joined = args.join(" ");
+            assert!(joined.contains("enr:-a"), "{client:?}: {joined}");
+            assert!(joined.contains("enr:-b"), "{client:?}: {joined}");
+        }
+    }
+}
@@ -1,221 +1,265 @@
-use crate::ethereum::{
-    EPHEMERY_CHECKPOINT_URLS, EphemeryConfig, LIGHTHOUSE_DATA_DIR, LIGHTHOUSE_DATA_VOLUME,
-    LIGHTHOUSE_VALIDATOR_CONTAINER_NAME,
+use super::consensus::{BEACON_HEALTH_PATH, BeaconSources, ConsensusPorts};
+use super::execution::{AUTHRPC_PORT, EPHEMERY_MOUNT, ExecutionPorts};
+use super::mev_boost::{self, MEV_BOOST_CONTAINER_NAME, MEV_BOOST_IMAGE, MEV_BOOST_PORT};
+use super::settings::{EthereumSettings, MevBoostSettings, PublishedPort, ValidatorSettings};
+use super::validator::{IMPORT_MOUNT, ValidatorClient, ValidatorOptions};
+use crate::ethereum::{EPHEMERY_CHECKPOINT_URLS, EphemeryConfig};
+use crate::packages::{
+    Binding, Container, HealthCheck, HealthProbe, PortBinding, scoped_resource_name,
 };
-use crate::packages::{Binding, Container, PortBinding};
 use eyre::{Result, eyre};
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
 
-const RETH_DATA_VOLUME: &str = "kittynode-rethdata";
-const RETH_NODE_CONTAINER_NAME: &str = "kittynode-reth-node";
-const LIGHTHOUSE_NODE_CONTAINER_NAME: &str = "kittynode-lighthouse-node";
+const EXECUTION_HEALTH_TIMEOUT_SECS: u64 = 120;
+/// Checkpoint sync has to finish downloading state before the beacon API starts listening.
+const CONSENSUS_HEALTH_TIMEOUT_SECS: u64 = 600;
+const MEV_BOOST_HEALTH_TIMEOUT_SECS: u64 = 60;
 
-pub(crate) struct EthereumResourcePaths {
+pub(crate) struct EthereumResources {
     pub jwt_source_path: String,
+    pub names: ResourceNames,
+}
+
+/// Docker names of the Ethereum containers and volumes, suffixed with the instance id if any.
+pub(crate) struct ResourceNames {
+    pub execution_node: String,
+    pub consensus_node: String,
+    pub validator_node: String,
+    pub mev_boost: String,
+    pub execution_data: String,
+    pub consensus_data: String,
+    /// Holds the validator keys and slashing protection. Lighthouse shares it with the
+    /// Lighthouse beacon node when both are selected.
+    pub validator_data: String,
+}
+
+impl ResourceNames {
+    pub fn new(instance: Option<&str>, settings: &EthereumSettings) -> Self {
+        let execution_client = settings.execution_client;
+        let consensus_client = settings.consensus_client;
+        let validator_client = settings.validator_client;
+        Self {
+            execution_node: scoped_resource_name(execution_client.container_name(), instance),
+            consensus_node: scoped_resource_name(consensus_client.container_name(), instance),
+            validator_node: scoped_resource_name(validator_client.container_name(), instance),
+            mev_boost: scoped_resource_name(MEV_BOOST_CONTAINER_NAME, instance),
+            execution_data: scoped_resource_name(execution_client.data_volume(), instance),
+            consensus_data: scoped_resource_name(consensus_client.data_volume(), instance),
+            validator_data: scoped_resource_name(validator_client.data_volume(), instance),
+        }
+    }
 }
 
 pub(crate) fn build_ethereum_containers(
     network: &str,
-    settings: &super::settings::EthereumSettings,
-    resources: &EthereumResourcePaths,
+    settings: &EthereumSettings,
+    resources: &EthereumResources,
     ephemery: Option<&EphemeryConfig>,
 ) -> Result<Vec<Container>> {
     let mut containers = Vec::new();
 
+    if let Some(mev_boost) = &settings.mev_boost {
+        mev_boost::validate(network, &mev_boost.relays, mev_boost.min_bid)?;
+    }
+
     if settings.runs_local_node() {
-        containers.push(build_reth_container(network, resources, ephemery));
-        containers.push(build_lighthouse_beacon_container(
-            network, resources, ephemery,
+        containers.push(build_execution_container(
+            network, settings, resources, ephemery,
+        )?);
+        // An external beacon node brings its own builder connection.
+        if let Some(mev_boost) = &settings.mev_boost {
+            containers.push(build_mev_boost_container(
+                network, mev_boost, settings, resources,
+            ));
+        }
+        containers.push(build_consensus_container(
+            network, settings, resources, ephemery,
         )?);
     }
 
     if let Some(validator) = &settings.validator {
-        containers.push(build_lighthouse_validator_container(
-            network, validator, settings, ephemery,
+        containers.push(build_validator_container(
+            network, validator, settings, resources, ephemery,
         ));
     }
 
     Ok(containers)
 }
 
-fn build_reth_container(
+fn build_execution_container(
     network: &str,
-    resources: &EthereumResourcePaths,
+    settings: &EthereumSettings,
+    resources: &EthereumResources,
     ephemery: Option<&EphemeryConfig>,
-) -> Container {
-    let mut command = vec!["node".to_string(), "--chain".to_string()];
-    if ephemery.is_some() {
-        command.push("/root/networks/ephemery/genesis.json".to_string());
-        command.push("--datadir".to_string());
-        command.push(format!("/root/.local/share/reth/{network}"));
-    } else {
-        command.push(network.to_string());
-    }
-    command.extend([
-        "--metrics".to_string(),
-        "0.0.0.0:9001".to_string(),
-        "--authrpc.addr".to_string(),
-        "0.0.0.0".to_string(),
-        "--authrpc.port".to_string(),
-        "8551".to_string(),
-        "--authrpc.jwtsecret".to_string(),
-        format!("/root/.local/share/reth/{network}/jwt.hex"),
-    ]);
-    if let Some(ephemery) = ephemery
-        && !ephemery.execution_bootnodes.is_empty()
-    {
-        command.push("--bootnodes".to_string());
-        command.push(ephemery.execution_bootnodes.join(","));
-    }
+) -> Result<Container> {
+    let client = settings.execution_client;
+    let ports = &settings.ports;
+    let runtime = &settings.runtime;
+    let command = client.command(
+        network,
+        &ExecutionPorts {
+            p2p: ports.execution_p2p.port,
+            metrics: ports.execution_metrics.port,
+            rpc: ports.execution_rpc.port,
+        },
+        ephemery,
+    );
 
-    Container {
-        name: RETH_NODE_CONTAINER_NAME.to_string(),
-        image: "ghcr.io/paradigmxyz/reth".to_string(),
-        cmd: command,
-        port_bindings: HashMap::from([
-            (
-                "9001/tcp".to_string(),
-                vec![PortBinding {
-                    host_ip: Some("0.0.0.0".to_string()),
-                    host_port: Some("9001".to_string()),
-                }],
-            ),
-            (
-                "30303/tcp".to_string(),
-                vec![PortBinding {
-                    host_ip: Some("0.0.0.0".to_string()),
-                    host_port: Some("30303".to_string()),
-                }],
-            ),
-            (
-                "30303/udp".to_string(),
-                vec![PortBinding {
-                    host_ip: Some("0.0.0.0".to_string()),
-                    host_port: Some("30303".to_string()),
-                }],
-            ),
-        ]),
+    Ok(Container {
+        name: resources.names.execution_node.clone(),
+        image: client.image().to_string(),
+        tag: settings.execution_image.tag.clone(),
+        digest: settings.execution_image.digest.clone(),
+        entrypoint: command.entrypoint,
+        cmd: command.args,
+        extra_args: settings.extra_args.execution.clone(),
+        env: HashMap::new(),
+        labels: HashMap::new(),
+        port_bindings: port_bindings(
+            &resources.names.execution_node,
+            &[
+                (&ports.execution_metrics, "tcp"),
+                (&ports.execution_rpc, "tcp"),
+                (&ports.execution_p2p, "tcp"),
+                (&ports.execution_p2p, "udp"),
+            ],
+        )?,
         volume_bindings: vec![Binding {
-            source: RETH_DATA_VOLUME.to_string(),
-            destination: format!("/root/.local/share/reth/{network}"),
+            source: resources.names.execution_data.clone(),
+            destination: client.data_dir(network),
             options: None,
         }],
         file_bindings: jwt_and_ephemery_bindings(
             &resources.jwt_source_path,
-            &format!("/root/.local/share/reth/{network}/jwt.hex"),
+            &client.jwt_path(network),
             ephemery,
         ),
-    }
+        health_check: Some(HealthCheck::new(
+            HealthProbe::Tcp {
+                port: ports.execution_rpc.port,
+            },
+            EXECUTION_HEALTH_TIMEOUT_SECS,
+        )),
+        depends_on: Vec::new(),
+        restart_policy: runtime.restart_policy,
+        memory_limit_bytes: runtime.execution.memory_limit_bytes,
+        cpu_limit: runtime.execution.cpu_limit,
+        ulimits: runtime.ulimits(),
+        stop_timeout_secs: Some(runtime.stop_timeout_secs),
+    })
 }
 
-fn build_lighthouse_beacon_container(
+fn build_consensus_container(
     network: &str,
-    resources: &EthereumResourcePaths,
+    settings: &EthereumSettings,
+    resources: &EthereumResources,
     ephemery: Option<&EphemeryConfig>,
 ) -> Result<Container> {
-    let lighthouse_jwt_path = format!("{LIGHTHOUSE_DATA_DIR}/{network}/jwt.hex");
+    let client = settings.consensus_client;
+    let ports = &settings.ports;
+    let runtime = &settings.runtime;
+    let execution_endpoint = format!("http://{}:{AUTHRPC_PORT}", resources.names.execution_node);
+    let builder_endpoint = settings
+        .mev_boost
+        .as_ref()
+        .map(|_| format!("http://{}:{MEV_BOOST_PORT}", resources.names.mev_boost));
+    let command = client.command(
+        network,
+        &ConsensusPorts {
+            p2p: ports.consensus_p2p.port,
+            quic: ports.consensus_quic.port,
+            http: ports.consensus_http.port,
+        },
+        &BeaconSources {
+            execution_endpoint: &execution_endpoint,
+            checkpoint_sync_url: checkpoint_sync_url(network, ephemery)?,
+            builder_endpoint: builder_endpoint.as_deref(),
+        },
+        ephemery,
+    );
 
-    let mut command = vec!["lighthouse".to_string()];
-    if ephemery.is_some() {
-        command.push("--testnet-dir".to_string());
-        command.push("/root/networks/ephemery".to_string());
-    } else {
-        command.push("--network".to_string());
-        command.push(network.to_string());
+    let mut published = vec![
+        (&ports.consensus_p2p, "tcp"),
+        (&ports.consensus_p2p, "udp"),
+        (&ports.consensus_http, "tcp"),
+    ];
+    if client.uses_quic() {
+        published.push((&ports.consensus_quic, "udp"));
     }
-    command.extend([
-        "beacon".to_string(),
-        "--http".to_string(),
-        "--http-address".to_string(),
-        "0.0.0.0".to_string(),
-        "--checkpoint-sync-url".to_string(),
-        checkpoint_sync_url(network, ephemery)?.to_string(),
-        "--execution-jwt".to_string(),
-        lighthouse_jwt_path.clone(),
-        "--execution-endpoint".to_string(),
-        format!("http://{RETH_NODE_CONTAINER_NAME}:8551"),
-    ]);
-    if let Some(ephemery) = ephemery
-        && !ephemery.consensus_bootnodes.is_empty()
-    {
-        command.push("--boot-nodes".to_string());
-        command.push(ephemery.consensus_bootnodes.join(","));
+    let mut depends_on = vec![resources.names.execution_node.clone()];
+    if settings.mev_boost.is_some() {
+        depends_on.push(resources.names.mev_boost.clone());
     }
 
     Ok(Container {
-        name: LIGHTHOUSE_NODE_CONTAINER_NAME.to_string(),
-        image: "sigp/lighthouse".to_string(),
+        name: resources.names.consensus_node.clone(),
+        image: client.image().to_string(),
+        tag: settings.consensus_image.tag.clone(),
+        digest: settings.consensus_image.digest.clone(),
+        entrypoint: Vec::new(),
         cmd: command,
-        port_bindings: HashMap::from([
-            (
-                "9000/tcp".to_string(),
-                vec![PortBinding {
-                    host_ip: Some("0.0.0.0".to_string()),
-                    host_port: Some("9000".to_string()),
-                }],
-            ),
-            (
-                "9000/udp".to_string(),
-                vec![PortBinding {
-                    host_ip: Some("0.0.0.0".to_string()),
-                    host_port: Some("9000".to_string()),
-                }],
-            ),
-            (
-                "9001/udp".to_string(),
-                vec![PortBinding {
-                    host_ip: Some("0.0.0.0".to_string()),
-                    host_port: Some("9001".to_string()),
-                }],
-            ),
-            (
-                "5052/tcp".to_string(),
-                vec![PortBinding {
-                    host_ip: Some("127.0.0.1".to_string()),
-                    host_port: Some("5052".to_string()),
-                }],
-            ),
-        ]),
+        extra_args: settings.extra_args.consensus.clone(),
+        env: HashMap::new(),
+        labels: HashMap::new(),
+        port_bindings: port_bindings(&resources.names.consensus_node, &published)?,
         volume_bindings: vec![Binding {
-            source: LIGHTHOUSE_DATA_VOLUME.to_string(),
-            destination: LIGHTHOUSE_DATA_DIR.to_string(),
+            source: resources.names.consensus_data.clone(),
+            destination: client.data_dir().to_string(),
             options: None,
         }],
         file_bindings: jwt_and_ephemery_bindings(
             &resources.jwt_source_path,
-            &lighthouse_jwt_path,
+            &client.jwt_path(network),
             ephemery,
         ),
+        health_check: Some(HealthCheck::new(
+            HealthProbe::Http {
+                port: ports.consensus_http.port,
+                path: BEACON_HEALTH_PATH.to_string(),
+            },
+            CONSENSUS_HEALTH_TIMEOUT_SECS,
+        )),
+        depends_on,
+        restart_policy: runtime.restart_policy,
+        memory_limit_bytes: runtime.consensus.memory_limit_bytes,
+        cpu_limit: runtime.consensus.cpu_limit,
+        ulimits: runtime.ulimits(),
+        stop_timeout_secs: Some(runtime.stop_timeout_secs),
     })
 }
 
-fn build_lighthouse_validator_container(
+fn build_validator_container(
     network: &str,
-    validator: &super::settings::ValidatorSettings,
-    settings: &super::settings::EthereumSettings,
+    validator: &ValidatorSettings,
+    settings: &EthereumSettings,
+    resources: &EthereumResources,
     ephemery: Option<&EphemeryConfig>,
 ) -> Container {
-    let mut command = vec!["lighthouse".to_string()];
-    if ephemery.is_some() {
-        command.push("--testnet-dir".to_string());
-        command.push("/root/networks/ephemery".to_string());
+    let client = settings.validator_client;
+    let beacon_endpoint = settings.consensus_endpoint.clone().unwrap_or_else(|| {
+        format!(
+            "http://{}:{}",
+            resources.names.consensus_node, settings.ports.consensus_http.port
+        )
+    });
+    let depends_on = if settings.consensus_endpoint.is_none() {
+        vec![resources.names.consensus_node.clone()]
     } else {
-        command.push("--network".to_string());
-        command.push(network.to_string());
-    }
-
-    let beacon_endpoint = settings
-        .consensus_endpoint
-        .clone()
-        .unwrap_or_else(|| format!("http://{LIGHTHOUSE_NODE_CONTAINER_NAME}:5052"));
-
-    command.extend([
-        "vc".to_string(),
-        "--beacon-nodes".to_string(),
-        beacon_endpoint,
-        "--suggested-fee-recipient".to_string(),
-        validator.fee_recipient.clone(),
-    ]);
+        Vec::new()
+    };
+    let command = client.command(
+        network,
+        &ValidatorOptions {
+            beacon_endpoint: &beacon_endpoint,
+            fee_recipient: &validator.fee_recipient,
+            graffiti: validator.graffiti.as_deref(),
+            builder: settings
+                .mev_boost
+                .as_ref()
+                .map(|mev_boost| &mev_boost.builder),
+        },
+        ephemery,
+    );
 
     let mut file_bindings = Vec::new();
     if let Some(ephemery) = ephemery {
@@ -223,17 +267,125 @@ fn build_lighthouse_validator_container(
     }
 
     Container {
-        name: LIGHTHOUSE_VALIDATOR_CONTAINER_NAME.to_string(),
-        image: "sigp/lighthouse".to_string(),
+        name: resources.names.validator_node.clone(),
+        image: client.image().to_string(),
+        tag: settings.validator_image.tag.clone(),
+        digest: settings.validator_image.digest.clone(),
+        entrypoint: Vec::new(),
         cmd: command,
+        extra_args: settings.extra_args.validator.clone(),
+        env: HashMap::new(),
+        labels: HashMap::new(),
         port_bindings: HashMap::new(),
-        volume_bindings: vec![Binding {
-            source: LIGHTHOUSE_DATA_VOLUME.to_string(),
-            destination: LIGHTHOUSE_DATA_DIR.to_string(),
-            options: None,
-        }],
+        volume_bindings: vec![validator_data_binding(client, resources)],
         file_bindings,
+        health_check: None,
+        depends_on,
+        restart_policy: settings.runtime.restart_policy,
+        memory_limit_bytes: None,
+        cpu_limit: None,
+        ulimits: Vec::new(),
+        stop_timeout_secs: Some(settings.runtime.stop_timeout_secs),
+    }
+}
+
+fn build_mev_boost_container(
+    network: &str,
+    mev_boost: &MevBoostSettings,
+    settings: &EthereumSettings,
+    resources: &EthereumResources,
+) -> Container {
+    Container {
+        name: resources.names.mev_boost.clone(),
+        image: MEV_BOOST_IMAGE.to_string(),
+        tag: mev_boost.image.tag.clone(),
+        digest: mev_boost.image.digest.clone(),
+        entrypoint: Vec::new(),
+        cmd: mev_boost::command(network, &mev_boost.relays, mev_boost.min_bid),
+        extra_args: Vec::new(),
+        env: HashMap::new(),
+        labels: HashMap::new(),
+        // Only the beacon node talks to MEV-Boost, over the package network.
+        port_bindings: HashMap::new(),
+        volume_bindings: Vec::new(),
+        file_bindings: Vec::new(),
+        // The builder API isn't published, so the probe runs inside the container.
+        health_check: Some(HealthCheck::new(
+            HealthProbe::Exec {
+                command: mev_boost::health_command(),
+            },
+            MEV_BOOST_HEALTH_TIMEOUT_SECS,
+        )),
+        depends_on: Vec::new(),
+        restart_policy: settings.runtime.restart_policy,
+        memory_limit_bytes: None,
+        cpu_limit: None,
+        ulimits: Vec::new(),
+        stop_timeout_secs: None,
+    }
+}
+
+/// Builds the one-off container that moves keys staged in `staging_dir` into the validator
+/// client's data volume.
+pub(crate) fn build_key_import_container(
+    network: &str,
+    settings: &EthereumSettings,
+    resources: &EthereumResources,
+    staging_dir: &Path,
+    ephemery: Option<&EphemeryConfig>,
+) -> Container {
+    let client = settings.validator_client;
+    let command = client.import_command(network, ephemery.is_some());
+
+    let mut file_bindings = vec![Binding {
+        source: staging_dir.to_string_lossy().to_string(),
+        destination: IMPORT_MOUNT.to_string(),
+        options: Some("ro".to_string()),
+    }];
+    if let Some(ephemery) = ephemery {
+        file_bindings.push(ephemery_binding(ephemery));
+    }
+
+    Container {
+        name: format!("{}-import", resources.names.validator_node),
+        image: client.image().to_string(),
+        tag: settings.validator_image.tag.clone(),
+        digest: settings.validator_image.digest.clone(),
+        entrypoint: command.entrypoint,
+        cmd: command.args,
+        volume_bindings: vec![validator_data_binding(client, resources)],
+        file_bindings,
+        ..Default::default()
+    }
+}
+
+fn validator_data_binding(client: ValidatorClient, resources: &EthereumResources) -> Binding {
+    Binding {
+        source: resources.names.validator_data.clone(),
+        destination: client.data_dir().to_string(),
+        options: None,
+    }
+}
+
+/// Publishes each port on its configured host address under the same port number.
+fn port_bindings(
+    container_name: &str,
+    ports: &[(&PublishedPort, &str)],
+) -> Result<HashMap<String, Vec<PortBinding>>> {
+    let mut bindings = HashMap::new();
+    for (published, protocol) in ports {
+        let key = format!("{}/{protocol}", published.port);
+        let binding = vec![PortBinding {
+            host_ip: Some(published.address.clone()),
+            host_port: Some(published.port.to_string()),
+        }];
+        if bindings.insert(key.clone(), binding).is_some() {
+            return Err(eyre!(
+                "Port {key} is configured more than once for container '{container_name}'"
+            ));
+        }
     }
+    Ok(bindings)
 }
 
 fn jwt_and_ephemery_bindings(
@@ -255,7 +407,7 @@ fn jwt_and_ephemery_bindings(
 fn ephemery_binding(ephemery: &EphemeryConfig) -> Binding {
     Binding {
         source: ephemery.metadata_dir.to_string_lossy().to_string(),
-        destination: "/root/networks/ephemery".to_string(),
+        destination: EPHEMERY_MOUNT.to_string(),
         options: Some("ro".to_string()),
     }
 }
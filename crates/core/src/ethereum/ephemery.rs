@@ -35,7 +35,7 @@ pub fn ensure_ephemery_config() -> Result<EphemeryConfig> {
     ensure_ephemery_config_with(&base_dir, fetch_latest_release, download_and_install)
 }
 
-fn ephemery_dir(config_root: &Path) -> PathBuf {
+pub(crate) fn ephemery_dir(config_root: &Path) -> PathBuf {
     config_root
         .join("packages")
         .join("ethereum")
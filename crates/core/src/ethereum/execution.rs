@@ -0,0 +1,495 @@
+use crate::ethereum::{
+    DEFAULT_BESU_VERSION, DEFAULT_ERIGON_VERSION, DEFAULT_GETH_VERSION, DEFAULT_NETHERMIND_VERSION,
+    DEFAULT_RETH_VERSION, EphemeryConfig,
+};
+
+/// Port every execution client serves the Engine API on inside the package network.
+pub(crate) const AUTHRPC_PORT: u16 = 8551;
+/// Where the Ephemery metadata directory is mounted inside client containers.
+pub(crate) const EPHEMERY_MOUNT: &str = "/root/networks/ephemery";
+
+/// Execution layer clients the Ethereum package can run.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum ExecutionClient {
+    #[default]
+    Reth,
+    Geth,
+    Nethermind,
+    Besu,
+    Erigon,
+}
+
+/// Host ports an execution client listens on, already resolved from package config.
+pub(crate) struct ExecutionPorts {
+    pub p2p: u16,
+    pub metrics: u16,
+    /// JSON-RPC port serving the `eth` and `net` namespaces.
+    pub rpc: u16,
+}
+
+/// How to start an execution client: an optional entrypoint override and its arguments.
+pub(crate) struct ExecutionCommand {
+    pub entrypoint: Vec<String>,
+    pub args: Vec<String>,
+}
+
+impl ExecutionClient {
+    pub const ALL: [Self; 5] = [
+        Self::Reth,
+        Self::Geth,
+        Self::Nethermind,
+        Self::Besu,
+        Self::Erigon,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reth => "reth",
+            Self::Geth => "geth",
+            Self::Nethermind => "nethermind",
+            Self::Besu => "besu",
+            Self::Erigon => "erigon",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|client| client.as_str() == value)
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Reth => "Reth",
+            Self::Geth => "Geth",
+            Self::Nethermind => "Nethermind",
+            Self::Besu => "Besu",
+            Self::Erigon => "Erigon",
+        }
+    }
+
+    pub fn image(self) -> &'static str {
+        match self {
+            Self::Reth => "ghcr.io/paradigmxyz/reth",
+            Self::Geth => "ethereum/client-go",
+            Self::Nethermind => "nethermind/nethermind",
+            Self::Besu => "hyperledger/besu",
+            Self::Erigon => "erigontech/erigon",
+        }
+    }
+
+    pub fn default_version(self) -> &'static str {
+        match self {
+            Self::Reth => DEFAULT_RETH_VERSION,
+            Self::Geth => DEFAULT_GETH_VERSION,
+            Self::Nethermind => DEFAULT_NETHERMIND_VERSION,
+            Self::Besu => DEFAULT_BESU_VERSION,
+            Self::Erigon => DEFAULT_ERIGON_VERSION,
+        }
+    }
+
+    pub fn container_name(self) -> &'static str {
+        match self {
+            Self::Reth => "kittynode-reth-node",
+            Self::Geth => "kittynode-geth-node",
+            Self::Nethermind => "kittynode-nethermind-node",
+            Self::Besu => "kittynode-besu-node",
+            Self::Erigon => "kittynode-erigon-node",
+        }
+    }
+
+    pub fn data_volume(self) -> &'static str {
+        match self {
+            Self::Reth => "kittynode-rethdata",
+            Self::Geth => "kittynode-gethdata",
+            Self::Nethermind => "kittynode-nethermind-data",
+            Self::Besu => "kittynode-besu-data",
+            Self::Erigon => "kittynode-erigon-data",
+        }
+    }
+
+    /// Directory the data volume is mounted on. Images that run as a non-root user need a
+    /// directory their user already owns.
+    pub fn data_dir(self, network: &str) -> String {
+        match self {
+            Self::Reth => format!("/root/.local/share/reth/{network}"),
+            Self::Geth => "/root/.ethereum".to_string(),
+            Self::Nethermind => "/data".to_string(),
+            Self::Besu => "/var/lib/besu".to_string(),
+            Self::Erigon => "/home/erigon/.local/share/erigon".to_string(),
+        }
+    }
+
+    /// The JWT secret is mounted read-only inside the data directory.
+    pub fn jwt_path(self, network: &str) -> String {
+        format!("{}/jwt.hex", self.data_dir(network))
+    }
+
+    pub fn command(
+        self,
+        network: &str,
+        ports: &ExecutionPorts,
+        ephemery: Option<&EphemeryConfig>,
+    ) -> ExecutionCommand {
+        let data_dir = self.data_dir(network);
+        let jwt_path = self.jwt_path(network);
+        let bootnodes = ephemery
+            .map(|ephemery| ephemery.execution_bootnodes.join(","))
+            .filter(|bootnodes| !bootnodes.is_empty());
+        match self {
+            Self::Reth => reth_command(network, &data_dir, &jwt_path, ports, ephemery, bootnodes),
+            Self::Geth => geth_command(network, &data_dir, &jwt_path, ports, ephemery, bootnodes),
+            Self::Nethermind => {
+                nethermind_command(network, &data_dir, &jwt_path, ports, ephemery, bootnodes)
+            }
+            Self::Besu => besu_command(network, &data_dir, &jwt_path, ports, ephemery, bootnodes),
+            Self::Erigon => {
+                erigon_command(network, &data_dir, &jwt_path, ports, ephemery, bootnodes)
+            }
+        }
+    }
+}
+
+fn args<const N: usize>(values: [&str; N]) -> Vec<String> {
+    values.into_iter().map(str::to_string).collect()
+}
+
+fn reth_command(
+    network: &str,
+    data_dir: &str,
+    jwt_path: &str,
+    ports: &ExecutionPorts,
+    ephemery: Option<&EphemeryConfig>,
+    bootnodes: Option<String>,
+) -> ExecutionCommand {
+    let mut command = args(["node", "--chain"]);
+    if ephemery.is_some() {
+        command.push(format!("{EPHEMERY_MOUNT}/genesis.json"));
+        command.push("--datadir".to_string());
+        command.push(data_dir.to_string());
+    } else {
+        command.push(network.to_string());
+    }
+    command.extend([
+        "--port".to_string(),
+        ports.p2p.to_string(),
+        "--discovery.port".to_string(),
+        ports.p2p.to_string(),
+        "--metrics".to_string(),
+        format!("0.0.0.0:{}", ports.metrics),
+        "--http".to_string(),
+        "--http.addr".to_string(),
+        "0.0.0.0".to_string(),
+        "--http.port".to_string(),
+        ports.rpc.to_string(),
+        "--http.api".to_string(),
+        "eth,net".to_string(),
+        "--authrpc.addr".to_string(),
+        "0.0.0.0".to_string(),
+        "--authrpc.port".to_string(),
+        AUTHRPC_PORT.to_string(),
+        "--authrpc.jwtsecret".to_string(),
+        jwt_path.to_string(),
+    ]);
+    if let Some(bootnodes) = bootnodes {
+        command.push("--bootnodes".to_string());
+        command.push(bootnodes);
+    }
+    ExecutionCommand {
+        entrypoint: Vec::new(),
+        args: command,
+    }
+}
+
+fn geth_command(
+    network: &str,
+    data_dir: &str,
+    jwt_path: &str,
+    ports: &ExecutionPorts,
+    ephemery: Option<&EphemeryConfig>,
+    bootnodes: Option<String>,
+) -> ExecutionCommand {
+    let mut command = Vec::new();
+    if ephemery.is_none() {
+        command.push(format!("--{network}"));
+    }
+    command.extend([
+        "--datadir".to_string(),
+        data_dir.to_string(),
+        "--port".to_string(),
+        ports.p2p.to_string(),
+        "--discovery.port".to_string(),
+        ports.p2p.to_string(),
+        "--metrics".to_string(),
+        "--metrics.addr".to_string(),
+        "0.0.0.0".to_string(),
+        "--metrics.port".to_string(),
+        ports.metrics.to_string(),
+        "--http".to_string(),
+        "--http.addr".to_string(),
+        "0.0.0.0".to_string(),
+        "--http.port".to_string(),
+        ports.rpc.to_string(),
+        "--http.api".to_string(),
+        "eth,net".to_string(),
+        "--http.vhosts".to_string(),
+        "*".to_string(),
+        "--authrpc.addr".to_string(),
+        "0.0.0.0".to_string(),
+        "--authrpc.port".to_string(),
+        AUTHRPC_PORT.to_string(),
+        "--authrpc.vhosts".to_string(),
+        "*".to_string(),
+        "--authrpc.jwtsecret".to_string(),
+        jwt_path.to_string(),
+    ]);
+    if let Some(bootnodes) = bootnodes {
+        command.push("--bootnodes".to_string());
+        command.push(bootnodes);
+    }
+    ExecutionCommand {
+        entrypoint: ephemery
+            .map(|_| init_then_run("geth", data_dir))
+            .unwrap_or_default(),
+        args: command,
+    }
+}
+
+fn nethermind_command(
+    network: &str,
+    data_dir: &str,
+    jwt_path: &str,
+    ports: &ExecutionPorts,
+    ephemery: Option<&EphemeryConfig>,
+    bootnodes: Option<String>,
+) -> ExecutionCommand {
+    let mut command = Vec::new();
+    if ephemery.is_some() {
+        command.extend([
+            "--config".to_string(),
+            "none".to_string(),
+            "--Init.ChainSpecPath".to_string(),
+            format!("{EPHEMERY_MOUNT}/chainspec.json"),
+        ]);
+    } else {
+        command.extend(["--config".to_string(), network.to_string()]);
+    }
+    command.extend([
+        "--datadir".to_string(),
+        data_dir.to_string(),
+        "--Network.P2PPort".to_string(),
+        ports.p2p.to_string(),
+        "--Network.DiscoveryPort".to_string(),
+        ports.p2p.to_string(),
+        "--Metrics.Enabled".to_string(),
+        "true".to_string(),
+        "--Metrics.ExposeHost".to_string(),
+        "0.0.0.0".to_string(),
+        "--Metrics.ExposePort".to_string(),
+        ports.metrics.to_string(),
+        "--JsonRpc.Enabled".to_string(),
+        "true".to_string(),
+        "--JsonRpc.Host".to_string(),
+        "0.0.0.0".to_string(),
+        "--JsonRpc.Port".to_string(),
+        ports.rpc.to_string(),
+        "--JsonRpc.EnabledModules".to_string(),
+        "Eth,Net".to_string(),
+        "--JsonRpc.EngineHost".to_string(),
+        "0.0.0.0".to_string(),
+        "--JsonRpc.EnginePort".to_string(),
+        AUTHRPC_PORT.to_string(),
+        "--JsonRpc.JwtSecretFile".to_string(),
+        jwt_path.to_string(),
+    ]);
+    if let Some(bootnodes) = bootnodes {
+        command.push("--Discovery.Bootnodes".to_string());
+        command.push(bootnodes);
+    }
+    ExecutionCommand {
+        entrypoint: Vec::new(),
+        args: command,
+    }
+}
+
+fn besu_command(
+    network: &str,
+    data_dir: &str,
+    jwt_path: &str,
+    ports: &ExecutionPorts,
+    ephemery: Option<&EphemeryConfig>,
+    bootnodes: Option<String>,
+) -> ExecutionCommand {
+    let mut command = vec![match ephemery {
+        Some(_) => format!("--genesis-file={EPHEMERY_MOUNT}/besu.json"),
+        None => format!("--network={network}"),
+    }];
+    command.extend([
+        format!("--data-path={data_dir}"),
+        format!("--p2p-port={}", ports.p2p),
+        "--metrics-enabled".to_string(),
+        "--metrics-host=0.0.0.0".to_string(),
+        format!("--metrics-port={}", ports.metrics),
+        "--rpc-http-enabled".to_string(),
+        "--rpc-http-host=0.0.0.0".to_string(),
+        format!("--rpc-http-port={}", ports.rpc),
+        "--rpc-http-api=ETH,NET".to_string(),
+        "--host-allowlist=*".to_string(),
+        "--engine-host-allowlist=*".to_string(),
+        format!("--engine-rpc-port={AUTHRPC_PORT}"),
+        format!("--engine-jwt-secret={jwt_path}"),
+    ]);
+    if let Some(bootnodes) = bootnodes {
+        command.push(format!("--bootnodes={bootnodes}"));
+    }
+    ExecutionCommand {
+        entrypoint: Vec::new(),
+        args: command,
+    }
+}
+
+fn erigon_command(
+    network: &str,
+    data_dir: &str,
+    jwt_path: &str,
+    ports: &ExecutionPorts,
+    ephemery: Option<&EphemeryConfig>,
+    bootnodes: Option<String>,
+) -> ExecutionCommand {
+    let mut command = Vec::new();
+    if ephemery.is_none() {
+        command.push(format!("--chain={network}"));
+    }
+    command.extend([
+        format!("--datadir={data_dir}"),
+        format!("--port={}", ports.p2p),
+        "--metrics".to_string(),
+        "--metrics.addr=0.0.0.0".to_string(),
+        format!("--metrics.port={}", ports.metrics),
+        "--http.addr=0.0.0.0".to_string(),
+        format!("--http.port={}", ports.rpc),
+        "--http.api=eth,net".to_string(),
+        "--http.vhosts=*".to_string(),
+        "--authrpc.addr=0.0.0.0".to_string(),
+        format!("--authrpc.port={AUTHRPC_PORT}"),
+        "--authrpc.vhosts=*".to_string(),
+        format!("--authrpc.jwtsecret={jwt_path}"),
+        // Kittynode runs a separate consensus client, so Erigon's embedded Caplin stays off.
+        "--externalcl".to_string(),
+    ]);
+    if let Some(bootnodes) = bootnodes {
+        command.push(format!("--bootnodes={bootnodes}"));
+    }
+    ExecutionCommand {
+        entrypoint: ephemery
+            .map(|_| init_then_run("erigon", data_dir))
+            .unwrap_or_default(),
+        args: command,
+    }
+}
+
+/// Geth and Erigon only take custom genesis files through `init`, so for Ephemery the container
+/// initializes its database and then runs the client with the container arguments.
+fn init_then_run(binary: &str, data_dir: &str) -> Vec<String> {
+    vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        format!(
+            "{binary} init --datadir {data_dir} {EPHEMERY_MOUNT}/genesis.json && exec {binary} \"$@\""
+        ),
+        binary.to_string(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EPHEMERY_MOUNT, ExecutionClient, ExecutionPorts};
+    use crate::ethereum::EphemeryConfig;
+    use std::path::PathBuf;
+
+    const PORTS: ExecutionPorts = ExecutionPorts {
+        p2p: 30303,
+        metrics: 9001,
+        rpc: 8545,
+    };
+
+    fn ephemery() -> EphemeryConfig {
+        EphemeryConfig {
+            tag: "ephemery-1".to_string(),
+            metadata_dir: PathBuf::from("/tmp/ephemery"),
+            execution_bootnodes: vec!["enode://a".to_string(), "enode://b".to_string()],
+            consensus_bootnodes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn client_names_round_trip() {
+        for client in ExecutionClient::ALL {
+            assert_eq!(ExecutionClient::parse(client.as_str()), Some(client));
+        }
+        assert_eq!(ExecutionClient::parse("parity"), None);
+    }
+
+    #[test]
+    fn every_client_serves_the_engine_api_with_the_jwt_secret() {
+        for client in ExecutionClient::ALL {
+            let command = client.command("hoodi", &PORTS, None);
+            let args = command.args.join(" ");
+            assert!(args.contains("8551"), "{client:?}: {args}");
+            assert!(
+                args.contains(&client.jwt_path("hoodi")),
+                "{client:?}: {args}"
+            );
+            assert!(args.contains("30303"), "{client:?}: {args}");
+            assert!(args.contains("9001"), "{client:?}: {args}");
+            assert!(args.contains("8545"), "{client:?}: {args}");
+            assert!(command.entrypoint.is_empty(), "{client:?}");
+        }
+    }
+
+    #[test]
+    fn named_networks_map_to_client_flags() {
+        let first_arg = |client: ExecutionClient| client.command("sepolia", &PORTS, None).args;
+        assert_eq!(
+            first_arg(ExecutionClient::Reth)[..3],
+            ["node", "--chain", "sepolia"]
+        );
+        assert_eq!(first_arg(ExecutionClient::Geth)[0], "--sepolia");
+        assert_eq!(
+            first_arg(ExecutionClient::Nethermind)[..2],
+            ["--config", "sepolia"]
+        );
+        assert_eq!(first_arg(ExecutionClient::Besu)[0], "--network=sepolia");
+        assert_eq!(first_arg(ExecutionClient::Erigon)[0], "--chain=sepolia");
+    }
+
+    #[test]
+    fn ephemery_uses_each_clients_genesis_format_and_bootnodes() {
+        let ephemery = ephemery();
+        let command = |client: ExecutionClient| client.command("ephemery", &PORTS, Some(&ephemery));
+
+        let reth = command(ExecutionClient::Reth).args.join(" ");
+        assert!(reth.contains(&format!("--chain {EPHEMERY_MOUNT}/genesis.json")));
+        assert!(reth.contains("--bootnodes enode://a,enode://b"));
+
+        let nethermind = command(ExecutionClient::Nethermind).args.join(" ");
+        assert!(nethermind.contains(&format!("{EPHEMERY_MOUNT}/chainspec.json")));
+        assert!(nethermind.contains("--Discovery.Bootnodes enode://a,enode://b"));
+
+        let besu = command(ExecutionClient::Besu).args.join(" ");
+        assert!(besu.contains(&format!("--genesis-file={EPHEMERY_MOUNT}/besu.json")));
+
+        for client in [ExecutionClient::Geth, ExecutionClient::Erigon] {
+            let command = command(client);
+            assert_eq!(command.entrypoint[..2], ["/bin/sh", "-c"]);
+            assert!(command.entrypoint[2].contains(&format!(
+                "init --datadir {} {EPHEMERY_MOUNT}/genesis.json",
+                client.data_dir("ephemery")
+            )));
+            assert!(
+                !command.args.iter().any(|arg| arg.contains("ephemery")),
+                "{client:?} should not pass Ephemery as a named network"
+            );
+        }
+    }
+}
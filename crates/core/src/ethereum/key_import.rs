@@ -0,0 +1,242 @@
+use super::validator::ValidatorClient;
+use super::{ETHEREUM_NAME, containers, resources, settings};
+use crate::docker::{
+    get_docker_instance, log_pull_progress, pull_image, remove_container,
+    run_container_to_completion,
+};
+use crate::ethereum::{EPHEMERY_NETWORK_NAME, ensure_ephemery_config, is_supported_network};
+use crate::packages::{PackageConfig, PackageConfigStore, instance_name, ownership_labels};
+use crate::paths::kittynode_path;
+use eyre::{Context, Result, eyre};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// Validator keys moved into a validator client's data volume.
+#[derive(Clone, Debug)]
+pub struct ValidatorKeyImport {
+    /// Display name of the validator client that received the keys.
+    pub client: String,
+    pub keystores: usize,
+    /// Docker volume holding the keys and the slashing protection database.
+    pub data_volume: String,
+    /// Where the client keeps its slashing protection database inside `data_volume`.
+    pub slashing_protection_path: String,
+}
+
+/// An EIP-2335 keystore found in the keys directory.
+pub(super) struct KeystoreFile {
+    path: PathBuf,
+    file_name: String,
+    pub(super) pubkey: String,
+}
+
+pub(crate) async fn import_validator_keys(
+    instance: Option<&str>,
+    config: &PackageConfig,
+    keys_dir: &Path,
+    password: &str,
+) -> Result<ValidatorKeyImport> {
+    let network = settings::selected_network(config)
+        .ok_or_else(|| eyre!("Select an Ethereum network before importing validator keys"))?;
+    if !is_supported_network(network) {
+        return Err(eyre!("Unsupported Ethereum network: {network}"));
+    }
+    let keystores = find_keystores(keys_dir)?;
+    if keystores.is_empty() {
+        return Err(eyre!("No keystores found in {}", keys_dir.display()));
+    }
+
+    let settings = settings::ethereum_settings_from_config(config);
+    let client = settings.validator_client;
+    let root = kittynode_path()?;
+    let resources = resources(&root, instance, &settings);
+    let ephemery = if network == EPHEMERY_NETWORK_NAME {
+        Some(ensure_ephemery_config()?)
+    } else {
+        None
+    };
+
+    let package_name = instance_name(ETHEREUM_NAME, instance);
+    let package_dir = PackageConfigStore::package_dir(&root, &package_name);
+    fs::create_dir_all(&package_dir)
+        .wrap_err_with(|| format!("Failed to create {}", package_dir.display()))?;
+    // The staged password files are deleted along with the directory when it drops.
+    let staging = tempfile::Builder::new()
+        .prefix("validator-import-")
+        .tempdir_in(&package_dir)
+        .wrap_err("Failed to create the validator key staging directory")?;
+    stage_keystores(client, &keystores, password, staging.path())?;
+
+    let mut container = containers::build_key_import_container(
+        network,
+        &settings,
+        &resources,
+        staging.path(),
+        ephemery.as_ref(),
+    );
+    container.labels = ownership_labels(&package_name, config);
+
+    let docker = get_docker_instance().await?;
+    // The validator client must not run while its keys and slashing protection change.
+    remove_container(&docker, &resources.names.validator_node).await?;
+    pull_image(&docker, &container, &mut log_pull_progress).await?;
+    info!(
+        "Importing {} keystore(s) into the {} validator client",
+        keystores.len(),
+        client.display_name()
+    );
+    run_container_to_completion(&docker, &container).await?;
+
+    Ok(ValidatorKeyImport {
+        client: client.display_name().to_string(),
+        keystores: keystores.len(),
+        data_volume: resources.names.validator_data,
+        slashing_protection_path: client.slashing_protection_path(network),
+    })
+}
+
+/// Finds the keystores in `keys_dir`, skipping the deposit data written next to them.
+pub(super) fn find_keystores(keys_dir: &Path) -> Result<Vec<KeystoreFile>> {
+    let entries = fs::read_dir(keys_dir)
+        .wrap_err_with(|| format!("Failed to read {}", keys_dir.display()))?;
+    let mut keystores = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if !file_name.starts_with("keystore") || !file_name.ends_with(".json") {
+            continue;
+        }
+        let file_name = file_name.to_string();
+
+        let contents = fs::read_to_string(&path)
+            .wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+        let json: serde_json::Value = serde_json::from_str(&contents)
+            .wrap_err_with(|| format!("{} is not a valid keystore", path.display()))?;
+        let pubkey = json
+            .get("pubkey")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| eyre!("Keystore {} has no pubkey", path.display()))?
+            .to_string();
+        keystores.push(KeystoreFile {
+            path,
+            file_name,
+            pubkey,
+        });
+    }
+    keystores.sort_by(|left, right| left.file_name.cmp(&right.file_name));
+    Ok(keystores)
+}
+
+/// Lays the keystores and their password out under `staging_dir` the way `client` imports them.
+fn stage_keystores(
+    client: ValidatorClient,
+    keystores: &[KeystoreFile],
+    password: &str,
+    staging_dir: &Path,
+) -> Result<()> {
+    for keystore in keystores {
+        let staged = client.staged_key(&keystore.file_name, &keystore.pubkey);
+        let keystore_path = staging_dir.join(&staged.keystore);
+        write_private_file(
+            &keystore_path,
+            &fs::read(&keystore.path)
+                .wrap_err_with(|| format!("Failed to read {}", keystore.path.display()))?,
+        )?;
+        write_private_file(&staging_dir.join(&staged.password), password.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Writes `contents` to `path`, creating parent directories, readable only by the owner.
+fn write_private_file(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder
+            .create(parent)
+            .wrap_err_with(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents))
+        .wrap_err_with(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_keystores, stage_keystores};
+    use crate::ethereum::validator::ValidatorClient;
+    use std::fs;
+    use tempfile::tempdir;
+
+    const PUBKEY: &str = "a1b2";
+
+    fn write_keys(dir: &std::path::Path) {
+        fs::write(
+            dir.join("keystore-m_12381_3600_0_0_0-1700000000.json"),
+            format!(r#"{{"pubkey":"{PUBKEY}","version":4}}"#),
+        )
+        .unwrap();
+        fs::write(dir.join("deposit_data-1700000000.json"), "[]").unwrap();
+    }
+
+    #[test]
+    fn keystores_skip_deposit_data() {
+        let dir = tempdir().unwrap();
+        write_keys(dir.path());
+
+        let keystores = find_keystores(dir.path()).unwrap();
+        assert_eq!(keystores.len(), 1);
+        assert_eq!(keystores[0].pubkey, PUBKEY);
+    }
+
+    #[test]
+    fn staging_writes_one_private_password_file_per_keystore() {
+        let keys = tempdir().unwrap();
+        write_keys(keys.path());
+        let keystores = find_keystores(keys.path()).unwrap();
+        let staging = tempdir().unwrap();
+
+        stage_keystores(
+            ValidatorClient::Nimbus,
+            &keystores,
+            "correct horse",
+            staging.path(),
+        )
+        .unwrap();
+
+        let secret = staging.path().join(format!("secrets/0x{PUBKEY}"));
+        assert_eq!(fs::read_to_string(&secret).unwrap(), "correct horse");
+        assert!(
+            staging
+                .path()
+                .join(format!("validators/0x{PUBKEY}/keystore.json"))
+                .exists()
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&secret).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::ethereum::EPHEMERY_NETWORK_NAME;
+use eyre::{Result, eyre};
+use tracing::warn;
+use url::Url;
+
+pub(crate) const MEV_BOOST_IMAGE: &str = "flashbots/mev-boost";
+pub(crate) const MEV_BOOST_CONTAINER_NAME: &str = "kittynode-mev-boost";
+/// Port MEV-Boost serves the builder API on inside the package network.
+pub(crate) const MEV_BOOST_PORT: u16 = 18550;
+/// MEV-Boost refuses to start with a higher minimum bid.
+const MAX_MIN_BID_ETH: f64 = 1.0;
+/// Networks relays are run for. Testnet relays usually name their network in the host name.
+const RELAY_NETWORKS: [&str; 4] = ["mainnet", "hoodi", "sepolia", "holesky"];
+
+pub(crate) fn command(network: &str, relays: &[String], min_bid: Option<f64>) -> Vec<String> {
+    let mut command = vec![
+        "-addr".to_string(),
+        format!("0.0.0.0:{MEV_BOOST_PORT}"),
+        format!("-{network}"),
+        "-relay-check".to_string(),
+        "-relays".to_string(),
+        relays.join(","),
+    ];
+    if let Some(min_bid) = min_bid {
+        command.extend(["-min-bid".to_string(), min_bid.to_string()]);
+    }
+    command
+}
+
+/// Checks the builder API from inside the container. With `-relay-check` it only succeeds while
+/// at least one relay is reachable.
+pub(crate) fn health_command() -> Vec<String> {
+    vec![
+        "wget".to_string(),
+        "-q".to_string(),
+        "-O".to_string(),
+        "/dev/null".to_string(),
+        format!("http://127.0.0.1:{MEV_BOOST_PORT}/eth/v1/builder/status"),
+    ]
+}
+
+/// Checks that MEV-Boost can run on `network` with these relays and minimum bid.
+pub(crate) fn validate(network: &str, relays: &[String], min_bid: Option<f64>) -> Result<()> {
+    if network == EPHEMERY_NETWORK_NAME {
+        return Err(eyre!(
+            "MEV-Boost relays are not available on Ephemery; disable mev_boost_enabled"
+        ));
+    }
+    if relays.is_empty() {
+        return Err(eyre!(
+            "Set mev_boost_relays to at least one relay URL to enable MEV-Boost"
+        ));
+    }
+    if let Some(min_bid) = min_bid
+        && min_bid > MAX_MIN_BID_ETH
+    {
+        return Err(eyre!(
+            "mev_boost_min_bid must be at most {MAX_MIN_BID_ETH} ETH, got {min_bid}"
+        ));
+    }
+
+    for relay in relays {
+        let url = Url::parse(relay).map_err(|error| eyre!("Invalid relay URL {relay}: {error}"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(eyre!("Relay URL {relay} must use http or https"));
+        }
+        if !is_relay_pubkey(url.username()) {
+            return Err(eyre!(
+                "Relay URL {relay} must include the relay public key, as in https://0x<pubkey>@relay.example"
+            ));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| eyre!("Relay URL {relay} has no host"))?;
+        match relay_network(host) {
+            Some(relay_network) if relay_network != network => {
+                return Err(eyre!(
+                    "Relay {host} serves {relay_network}, not the selected {network} network"
+                ));
+            }
+            None if network != "mainnet" => warn!(
+                "Relay {host} doesn't name its network; MEV-Boost checks that it serves {network} when it starts"
+            ),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn is_relay_pubkey(value: &str) -> bool {
+    value
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 96 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Returns the network a relay's host name labels it with, if any.
+fn relay_network(host: &str) -> Option<&'static str> {
+    host.split(['.', '-']).find_map(|label| {
+        RELAY_NETWORKS
+            .into_iter()
+            .find(|network| label.eq_ignore_ascii_case(network))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MEV_BOOST_PORT, command, validate};
+
+    fn relay(host: &str) -> String {
+        format!("https://0x{}@{host}", "a".repeat(96))
+    }
+
+    #[test]
+    fn relays_must_not_name_another_network() {
+        validate(
+            "hoodi",
+            &[
+                relay("boost-relay-hoodi.flashbots.net"),
+                relay("hoodi.aestus.live"),
+            ],
+            Some(0.05),
+        )
+        .unwrap();
+        validate("mainnet", &[relay("boost-relay.flashbots.net")], None).unwrap();
+        // Relays on plain host names are left to MEV-Boost's relay check.
+        validate("hoodi", &[relay("relay.example")], None).unwrap();
+
+        let error = validate("hoodi", &[relay("boost-relay-mainnet.example")], None).unwrap_err();
+        assert!(error.to_string().contains("serves mainnet"), "{error}");
+        let error =
+            validate("mainnet", &[relay("relay-sepolia.ultrasound.money")], None).unwrap_err();
+        assert!(error.to_string().contains("serves sepolia"), "{error}");
+    }
+
+    #[test]
+    fn invalid_relay_settings_are_rejected() {
+        assert!(validate("hoodi", &[], None).is_err());
+        assert!(validate("ephemery", &[relay("ephemery.example")], None).is_err());
+        assert!(validate("mainnet", &["https://relay.example".to_string()], None).is_err());
+        assert!(validate("mainnet", &[relay("relay.example")], Some(1.5)).is_err());
+    }
+
+    #[test]
+    fn command_lists_every_relay_and_the_min_bid() {
+        let relays = vec![relay("a.example"), relay("b.example")];
+        let command = command("mainnet", &relays, Some(0.05));
+        assert!(command.contains(&format!("0.0.0.0:{MEV_BOOST_PORT}")));
+        assert!(command.contains(&"-mainnet".to_string()));
+        assert!(command.contains(&relays.join(",")));
+        assert!(command.ends_with(&["-min-bid".to_string(), "0.05".to_string()]));
+    }
+}
@@ -0,0 +1,314 @@
+use super::beacon_api::{self, agent, parse_decimal};
+use super::consensus::BEACON_HEALTH_PATH;
+use super::settings::EthereumSettings;
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::task;
+
+/// Whether an Ethereum node's clients are reachable, synced and connected to peers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeHealth {
+    pub execution: ExecutionHealth,
+    pub consensus: ConsensusHealth,
+}
+
+/// What the execution client reports over JSON-RPC.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionHealth {
+    pub endpoint: String,
+    /// Set when the endpoint comes from `execution_endpoint` rather than a Kittynode container.
+    pub external: bool,
+    pub reachable: bool,
+    pub syncing: Option<bool>,
+    pub current_block: Option<u64>,
+    /// Highest block seen on the network, known only while syncing.
+    pub highest_block: Option<u64>,
+    pub peer_count: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// What the beacon node reports through the beacon API.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusHealth {
+    pub endpoint: String,
+    /// Set when the endpoint comes from `consensus_endpoint` rather than a Kittynode container.
+    pub external: bool,
+    pub reachable: bool,
+    /// Whether the health endpoint reports the node ready rather than syncing or not initialized.
+    pub ready: Option<bool>,
+    pub syncing: Option<bool>,
+    pub head_slot: Option<u64>,
+    /// Slots between the head and the current slot.
+    pub sync_distance: Option<u64>,
+    /// Set when the beacon node has lost its execution client.
+    pub execution_offline: Option<bool>,
+    pub peer_count: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl NodeHealth {
+    /// Describes each problem that keeps the node from following the chain.
+    pub fn diagnostics(&self) -> Vec<String> {
+        let mut diagnostics = Vec::new();
+        let execution = &self.execution;
+        if !execution.reachable {
+            diagnostics.push(format!(
+                "Execution client is unreachable at {}",
+                execution.endpoint
+            ));
+        } else {
+            if execution.syncing == Some(true) {
+                diagnostics.push(match (execution.current_block, execution.highest_block) {
+                    (Some(current), Some(highest)) => {
+                        format!("Execution client is syncing (block {current} of {highest})")
+                    }
+                    _ => "Execution client is syncing".to_string(),
+                });
+            }
+            if execution.peer_count == Some(0) {
+                diagnostics.push("Execution client has no peers".to_string());
+            }
+        }
+
+        let consensus = &self.consensus;
+        if !consensus.reachable {
+            diagnostics.push(format!(
+                "Consensus client is unreachable at {}",
+                consensus.endpoint
+            ));
+        } else {
+            if consensus.syncing == Some(true) {
+                diagnostics.push(match consensus.sync_distance {
+                    Some(distance) => {
+                        format!("Consensus client is syncing ({distance} slots behind)")
+                    }
+                    None => "Consensus client is syncing".to_string(),
+                });
+            }
+            if consensus.execution_offline == Some(true) {
+                diagnostics.push("Consensus client cannot reach its execution client".to_string());
+            }
+            if consensus.peer_count == Some(0) {
+                diagnostics.push("Consensus client has no peers".to_string());
+            }
+        }
+        diagnostics
+    }
+}
+
+/// Queries the execution and beacon endpoints `settings` resolve to.
+pub(crate) async fn node_health(settings: &EthereumSettings) -> NodeHealth {
+    let (execution_endpoint, execution_external) = match &settings.execution_endpoint {
+        Some(endpoint) => (endpoint.clone(), true),
+        None => (settings.ports.execution_rpc.local_url(), false),
+    };
+    let (consensus_endpoint, consensus_external) = match &settings.consensus_endpoint {
+        Some(endpoint) => (endpoint.clone(), true),
+        None => (settings.ports.consensus_http.local_url(), false),
+    };
+
+    let execution = task::spawn_blocking(move || {
+        let mut health = query_execution(&execution_endpoint);
+        health.external = execution_external;
+        health
+    });
+    let consensus = task::spawn_blocking(move || {
+        let mut health = query_consensus(&consensus_endpoint);
+        health.external = consensus_external;
+        health
+    });
+    NodeHealth {
+        execution: execution.await.unwrap_or_default(),
+        consensus: consensus.await.unwrap_or_default(),
+    }
+}
+
+fn query_execution(endpoint: &str) -> ExecutionHealth {
+    let mut health = ExecutionHealth {
+        endpoint: endpoint.to_string(),
+        ..Default::default()
+    };
+    let agent = agent();
+    let call = |method: &str| json_rpc(&agent, endpoint, method);
+
+    // The first call tells whether the node is reachable at all.
+    match call("eth_syncing") {
+        Ok(syncing) => {
+            health.reachable = true;
+            match parse_execution_syncing(&syncing) {
+                Ok((syncing, current, highest)) => {
+                    health.syncing = Some(syncing);
+                    health.current_block = current;
+                    health.highest_block = highest;
+                }
+                Err(error) => health.error = Some(error.to_string()),
+            }
+        }
+        Err(error) => {
+            health.error = Some(error.to_string());
+            return health;
+        }
+    }
+    if health.current_block.is_none() {
+        match call("eth_blockNumber").and_then(|value| parse_quantity(&value)) {
+            Ok(block) => health.current_block = Some(block),
+            Err(error) => health.error = Some(error.to_string()),
+        }
+    }
+    match call("net_peerCount").and_then(|value| parse_quantity(&value)) {
+        Ok(peers) => health.peer_count = Some(peers),
+        Err(error) => health.error = Some(error.to_string()),
+    }
+    health
+}
+
+fn json_rpc(agent: &ureq::Agent, endpoint: &str, method: &str) -> Result<Value> {
+    let response: Value = agent
+        .post(endpoint)
+        .send_json(json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": []}))
+        .map_err(|error| eyre!("{method} failed: {error}"))?
+        .into_json()
+        .map_err(|error| eyre!("{method} returned invalid JSON: {error}"))?;
+    if let Some(error) = response.get("error") {
+        return Err(eyre!("{method} failed: {error}"));
+    }
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| eyre!("{method} returned no result"))
+}
+
+fn query_consensus(endpoint: &str) -> ConsensusHealth {
+    let mut health = ConsensusHealth {
+        endpoint: endpoint.to_string(),
+        ..Default::default()
+    };
+    let agent = agent();
+    let base = endpoint.trim_end_matches('/');
+
+    // The health endpoint answers 206 while syncing and 503 before the node is initialized.
+    match agent.get(&format!("{base}{BEACON_HEALTH_PATH}")).call() {
+        Ok(response) => {
+            health.reachable = true;
+            health.ready = Some(response.status() == 200);
+        }
+        Err(ureq::Error::Status(_, _)) => {
+            health.reachable = true;
+            health.ready = Some(false);
+        }
+        Err(error) => {
+            health.error = Some(error.to_string());
+            return health;
+        }
+    }
+
+    match beacon_api::get_data(&agent, base, "/eth/v1/node/syncing") {
+        Ok(data) => {
+            health.syncing = data.get("is_syncing").and_then(Value::as_bool);
+            health.head_slot = data.get("head_slot").and_then(parse_decimal);
+            health.sync_distance = data.get("sync_distance").and_then(parse_decimal);
+            health.execution_offline = data.get("el_offline").and_then(Value::as_bool);
+        }
+        Err(error) => health.error = Some(error.to_string()),
+    }
+    match beacon_api::get_data(&agent, base, "/eth/v1/node/peer_count") {
+        Ok(data) => health.peer_count = data.get("connected").and_then(parse_decimal),
+        Err(error) => health.error = Some(error.to_string()),
+    }
+    health
+}
+
+/// Reads an `eth_syncing` result: `false` once synced, otherwise the sync progress.
+fn parse_execution_syncing(value: &Value) -> Result<(bool, Option<u64>, Option<u64>)> {
+    match value {
+        Value::Bool(false) => Ok((false, None, None)),
+        Value::Object(progress) => {
+            let block = |key: &str| progress.get(key).map(parse_quantity).transpose();
+            Ok((true, block("currentBlock")?, block("highestBlock")?))
+        }
+        other => Err(eyre!("eth_syncing returned an unexpected result: {other}")),
+    }
+}
+
+/// Parses a hex-encoded JSON-RPC quantity such as `"0x1b4"`.
+fn parse_quantity(value: &Value) -> Result<u64> {
+    value
+        .as_str()
+        .and_then(|hex| hex.strip_prefix("0x"))
+        .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+        .ok_or_else(|| eyre!("Expected a hex quantity, got {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ConsensusHealth, ExecutionHealth, NodeHealth, parse_execution_syncing, parse_quantity,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn execution_sync_progress_is_read_from_hex_quantities() {
+        assert_eq!(
+            parse_execution_syncing(&json!(false)).unwrap(),
+            (false, None, None)
+        );
+        assert_eq!(
+            parse_execution_syncing(&json!({
+                "startingBlock": "0x0",
+                "currentBlock": "0x1b4",
+                "highestBlock": "0x3e8",
+            }))
+            .unwrap(),
+            (true, Some(436), Some(1000))
+        );
+        assert!(parse_execution_syncing(&json!(true)).is_err());
+        assert!(parse_quantity(&json!("12")).is_err());
+    }
+
+    #[test]
+    fn diagnostics_describe_each_problem() {
+        let healthy = NodeHealth {
+            execution: ExecutionHealth {
+                reachable: true,
+                syncing: Some(false),
+                peer_count: Some(25),
+                ..Default::default()
+            },
+            consensus: ConsensusHealth {
+                reachable: true,
+                ready: Some(true),
+                syncing: Some(false),
+                execution_offline: Some(false),
+                peer_count: Some(80),
+                ..Default::default()
+            },
+        };
+        assert!(healthy.diagnostics().is_empty());
+
+        let unhealthy = NodeHealth {
+            execution: ExecutionHealth {
+                endpoint: "http://10.0.0.2:8545".to_string(),
+                ..Default::default()
+            },
+            consensus: ConsensusHealth {
+                reachable: true,
+                syncing: Some(true),
+                sync_distance: Some(320),
+                peer_count: Some(0),
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            unhealthy.diagnostics(),
+            vec![
+                "Execution client is unreachable at http://10.0.0.2:8545",
+                "Consensus client is syncing (320 slots behind)",
+                "Consensus client has no peers",
+            ]
+        );
+    }
+}
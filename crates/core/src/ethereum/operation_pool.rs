@@ -0,0 +1,82 @@
+use super::beacon_api::{self, agent};
+use super::validator_status::VALIDATORS_PATH;
+use crate::validator::{SignedBlsToExecutionChange, SignedVoluntaryExit};
+use alloy_primitives::B256;
+use eyre::{Result, eyre};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use tokio::task;
+
+const GENESIS_PATH: &str = "/eth/v1/beacon/genesis";
+const VOLUNTARY_EXITS_PATH: &str = "/eth/v1/beacon/pool/voluntary_exits";
+const BLS_TO_EXECUTION_CHANGES_PATH: &str = "/eth/v1/beacon/pool/bls_to_execution_changes";
+
+/// Reads the genesis validators root of the chain the beacon node at `beacon_url` follows.
+pub(crate) async fn genesis_validators_root(beacon_url: String) -> Result<B256> {
+    task::spawn_blocking(move || {
+        let data = beacon_api::get_data(&agent(), &beacon_url, GENESIS_PATH)?;
+        let root = data
+            .get("genesis_validators_root")
+            .and_then(Value::as_str)
+            .ok_or_else(|| eyre!("{GENESIS_PATH} returned no genesis validators root"))?;
+        root.parse()
+            .map_err(|error| eyre!("{GENESIS_PATH} returned an invalid root {root}: {error}"))
+    })
+    .await?
+}
+
+/// Reads the withdrawal credentials of the validators with `indices` from the head state of the
+/// beacon node at `beacon_url`. Validators the chain doesn't know are left out.
+pub(crate) async fn withdrawal_credentials(
+    beacon_url: String,
+    indices: Vec<u64>,
+) -> Result<HashMap<u64, String>> {
+    task::spawn_blocking(move || {
+        let ids: Vec<String> = indices.iter().map(u64::to_string).collect();
+        let data = beacon_api::post_data(
+            &agent(),
+            &beacon_url,
+            VALIDATORS_PATH,
+            &json!({ "ids": ids }),
+        )?;
+        let entries = data
+            .as_array()
+            .ok_or_else(|| eyre!("{VALIDATORS_PATH} returned an unexpected response"))?;
+        Ok(entries
+            .iter()
+            .filter_map(|entry| {
+                let index = entry.get("index").and_then(beacon_api::parse_decimal)?;
+                let credentials = entry
+                    .get("validator")?
+                    .get("withdrawal_credentials")?
+                    .as_str()?;
+                Some((index, credentials.to_ascii_lowercase()))
+            })
+            .collect())
+    })
+    .await?
+}
+
+/// Adds `exit` to the operation pool of the beacon node at `beacon_url`, which gossips it on.
+pub(crate) async fn submit_voluntary_exit(
+    beacon_url: String,
+    exit: SignedVoluntaryExit,
+) -> Result<()> {
+    task::spawn_blocking(move || {
+        let body = serde_json::to_value(&exit)?;
+        beacon_api::post(&agent(), &beacon_url, VOLUNTARY_EXITS_PATH, &body).map(|_| ())
+    })
+    .await?
+}
+
+/// Adds `changes` to the operation pool of the beacon node at `beacon_url`.
+pub(crate) async fn submit_bls_to_execution_changes(
+    beacon_url: String,
+    changes: Vec<SignedBlsToExecutionChange>,
+) -> Result<()> {
+    task::spawn_blocking(move || {
+        let body = serde_json::to_value(&changes)?;
+        beacon_api::post(&agent(), &beacon_url, BLS_TO_EXECUTION_CHANGES_PATH, &body).map(|_| ())
+    })
+    .await?
+}
@@ -1,22 +1,410 @@
-use crate::packages::PackageConfig;
+use super::consensus::ConsensusClient;
+use super::execution::ExecutionClient;
+use super::validator::{BuilderOptions, ValidatorClient};
+use crate::ethereum::DEFAULT_MEV_BOOST_VERSION;
+use crate::packages::{PackageConfig, RestartPolicy, Ulimit, split_arguments};
+use std::collections::HashSet;
+
+pub(crate) const DEFAULT_EXECUTION_P2P_PORT: u16 = 30303;
+pub(crate) const DEFAULT_EXECUTION_METRICS_PORT: u16 = 9001;
+pub(crate) const DEFAULT_EXECUTION_RPC_PORT: u16 = 8545;
+pub(crate) const DEFAULT_CONSENSUS_P2P_PORT: u16 = 9000;
+pub(crate) const DEFAULT_CONSENSUS_QUIC_PORT: u16 = 9001;
+pub(crate) const DEFAULT_CONSENSUS_HTTP_PORT: u16 = 5052;
+/// Host address that publishes a port on every interface.
+pub(crate) const ALL_INTERFACES: &str = "0.0.0.0";
+pub(crate) const LOCALHOST: &str = "127.0.0.1";
+pub(crate) const DEFAULT_RESTART_POLICY: RestartPolicy = RestartPolicy::UnlessStopped;
+/// Gives the execution client and Lighthouse time to flush their databases before Docker kills them.
+pub(crate) const DEFAULT_STOP_TIMEOUT_SECS: u64 = 300;
+const BYTES_PER_MIB: u64 = 1024 * 1024;
+/// Distance between the default ports of consecutive named instances.
+const INSTANCE_PORT_STRIDE: u16 = 10;
+const MAX_INSTANCE_PORT_OFFSETS: u16 = 100;
+/// Config keys of the ports a new named instance is assigned, with their defaults.
+const INSTANCE_PORT_KEYS: [(&str, u16); 6] = [
+    ("execution_p2p_port", DEFAULT_EXECUTION_P2P_PORT),
+    ("execution_metrics_port", DEFAULT_EXECUTION_METRICS_PORT),
+    ("execution_rpc_port", DEFAULT_EXECUTION_RPC_PORT),
+    ("consensus_p2p_port", DEFAULT_CONSENSUS_P2P_PORT),
+    ("consensus_quic_port", DEFAULT_CONSENSUS_QUIC_PORT),
+    ("consensus_http_port", DEFAULT_CONSENSUS_HTTP_PORT),
+];
 
 #[derive(Clone)]
 pub(crate) struct EthereumSettings {
     pub uses_local_execution: bool,
     pub uses_local_consensus: bool,
     pub validator: Option<ValidatorSettings>,
+    pub execution_endpoint: Option<String>,
     pub consensus_endpoint: Option<String>,
+    pub execution_client: ExecutionClient,
+    /// Image pin of the selected execution client.
+    pub execution_image: ImagePin,
+    pub consensus_client: ConsensusClient,
+    /// Image pin of the selected beacon node.
+    pub consensus_image: ImagePin,
+    pub validator_client: ValidatorClient,
+    /// Image pin of the selected validator client, shared with its beacon node.
+    pub validator_image: ImagePin,
+    pub mev_boost: Option<MevBoostSettings>,
+    pub ports: PortSettings,
+    pub runtime: RuntimeSettings,
+    pub extra_args: ExtraArgs,
+}
+
+/// The tag and optional digest a client image is pinned to.
+#[derive(Clone)]
+pub(crate) struct ImagePin {
+    pub tag: String,
+    pub digest: Option<String>,
+}
+
+impl ImagePin {
+    fn from_config(config: &PackageConfig, client: &str, default_tag: &str) -> Self {
+        let tag = config
+            .values
+            .get(&format!("{client}_version"))
+            .filter(|value| !value.is_empty())
+            .cloned()
+            .unwrap_or_else(|| default_tag.to_string());
+        let digest = config
+            .values
+            .get(&format!("{client}_digest"))
+            .filter(|value| !value.is_empty())
+            .cloned();
+        Self { tag, digest }
+    }
+}
+
+/// A port a client listens on and the host address Docker publishes it on.
+///
+/// The container and host port are the same so that clients advertise the port peers can reach.
+#[derive(Clone)]
+pub(crate) struct PublishedPort {
+    pub port: u16,
+    pub address: String,
+}
+
+impl PublishedPort {
+    /// Host address this machine reaches the port on.
+    pub fn local_address(&self) -> &str {
+        if self.address == ALL_INTERFACES {
+            LOCALHOST
+        } else {
+            &self.address
+        }
+    }
+
+    /// URL this machine reaches an HTTP API served on the port at.
+    pub fn local_url(&self) -> String {
+        format!("http://{}:{}", self.local_address(), self.port)
+    }
+
+    fn from_config(
+        config: &PackageConfig,
+        port_key: &str,
+        default_port: u16,
+        address_key: &str,
+        default_address: &str,
+    ) -> Self {
+        let port = config
+            .values
+            .get(port_key)
+            .and_then(|value| value.parse::<u16>().ok())
+            .filter(|port| *port != 0)
+            .unwrap_or(default_port);
+        let address = config
+            .values
+            .get(address_key)
+            .filter(|value| !value.is_empty())
+            .cloned()
+            .unwrap_or_else(|| default_address.to_string());
+        Self { port, address }
+    }
+}
+
+/// Ports published by the execution client and beacon node containers.
+#[derive(Clone)]
+pub(crate) struct PortSettings {
+    pub execution_p2p: PublishedPort,
+    pub execution_metrics: PublishedPort,
+    pub execution_rpc: PublishedPort,
+    pub consensus_p2p: PublishedPort,
+    pub consensus_quic: PublishedPort,
+    pub consensus_http: PublishedPort,
+}
+
+impl PortSettings {
+    /// Returns every host port number these settings publish.
+    pub fn host_ports(&self) -> HashSet<u16> {
+        [
+            &self.execution_p2p,
+            &self.execution_metrics,
+            &self.execution_rpc,
+            &self.consensus_p2p,
+            &self.consensus_quic,
+            &self.consensus_http,
+        ]
+        .into_iter()
+        .map(|published| published.port)
+        .collect()
+    }
+
+    fn from_config(config: &PackageConfig) -> Self {
+        Self {
+            execution_p2p: PublishedPort::from_config(
+                config,
+                "execution_p2p_port",
+                DEFAULT_EXECUTION_P2P_PORT,
+                "execution_p2p_address",
+                ALL_INTERFACES,
+            ),
+            execution_metrics: PublishedPort::from_config(
+                config,
+                "execution_metrics_port",
+                DEFAULT_EXECUTION_METRICS_PORT,
+                "execution_metrics_address",
+                ALL_INTERFACES,
+            ),
+            execution_rpc: PublishedPort::from_config(
+                config,
+                "execution_rpc_port",
+                DEFAULT_EXECUTION_RPC_PORT,
+                "execution_rpc_address",
+                LOCALHOST,
+            ),
+            consensus_p2p: PublishedPort::from_config(
+                config,
+                "consensus_p2p_port",
+                DEFAULT_CONSENSUS_P2P_PORT,
+                "consensus_p2p_address",
+                ALL_INTERFACES,
+            ),
+            // QUIC shares the P2P exposure; only its port can differ.
+            consensus_quic: PublishedPort::from_config(
+                config,
+                "consensus_quic_port",
+                DEFAULT_CONSENSUS_QUIC_PORT,
+                "consensus_p2p_address",
+                ALL_INTERFACES,
+            ),
+            consensus_http: PublishedPort::from_config(
+                config,
+                "consensus_http_port",
+                DEFAULT_CONSENSUS_HTTP_PORT,
+                "consensus_http_address",
+                LOCALHOST,
+            ),
+        }
+    }
+}
+
+/// Returns true when `config` sets any of the ports a named instance is assigned.
+pub(crate) fn has_port_overrides(config: &PackageConfig) -> bool {
+    INSTANCE_PORT_KEYS
+        .iter()
+        .any(|(key, _)| config.values.contains_key(*key))
+}
+
+/// Picks port config values for a named instance that avoid every port in `taken`.
+///
+/// The client defaults are shifted by the smallest multiple of the instance stride that clears
+/// all taken ports, so each instance keeps the familiar port layout.
+pub(crate) fn instance_port_config(taken: &HashSet<u16>) -> Option<Vec<(String, String)>> {
+    (1..=MAX_INSTANCE_PORT_OFFSETS).find_map(|step| {
+        let offset = step * INSTANCE_PORT_STRIDE;
+        let ports: Vec<(String, u16)> = INSTANCE_PORT_KEYS
+            .iter()
+            .map(|(key, default)| Some(((*key).to_string(), default.checked_add(offset)?)))
+            .collect::<Option<_>>()?;
+        if ports.iter().any(|(_, port)| taken.contains(port)) {
+            return None;
+        }
+        Some(
+            ports
+                .into_iter()
+                .map(|(key, port)| (key, port.to_string()))
+                .collect(),
+        )
+    })
+}
+
+/// Memory and CPU limits of one client container.
+#[derive(Clone, Default)]
+pub(crate) struct ResourceLimits {
+    pub memory_limit_bytes: Option<u64>,
+    pub cpu_limit: Option<f64>,
+}
+
+impl ResourceLimits {
+    fn from_config(config: &PackageConfig, client: &str) -> Self {
+        let memory_limit_bytes = config
+            .values
+            .get(&format!("{client}_memory_limit_mib"))
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|mib| *mib > 0)
+            .map(|mib| mib.saturating_mul(BYTES_PER_MIB));
+        let cpu_limit = config
+            .values
+            .get(&format!("{client}_cpu_limit"))
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|cpus| cpus.is_finite() && *cpus > 0.0);
+        Self {
+            memory_limit_bytes,
+            cpu_limit,
+        }
+    }
+}
+
+/// How Docker runs, limits and stops the client containers.
+#[derive(Clone)]
+pub(crate) struct RuntimeSettings {
+    pub restart_policy: RestartPolicy,
+    pub stop_timeout_secs: u64,
+    /// Open file limit applied as both the soft and hard `nofile` ulimit.
+    pub nofile_limit: Option<i64>,
+    /// Limits of the execution client, read from the `execution_` keys.
+    pub execution: ResourceLimits,
+    /// Limits of the beacon node, read from the `consensus_` keys.
+    pub consensus: ResourceLimits,
+}
+
+impl RuntimeSettings {
+    fn from_config(config: &PackageConfig) -> Self {
+        let restart_policy = config
+            .values
+            .get("restart_policy")
+            .and_then(|value| RestartPolicy::parse(value))
+            .unwrap_or(DEFAULT_RESTART_POLICY);
+        let stop_timeout_secs = config
+            .values
+            .get("stop_timeout_secs")
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_STOP_TIMEOUT_SECS);
+        let nofile_limit = config
+            .values
+            .get("nofile_limit")
+            .and_then(|value| value.parse::<i64>().ok())
+            .filter(|limit| *limit > 0);
+        Self {
+            restart_policy,
+            stop_timeout_secs,
+            nofile_limit,
+            execution: ResourceLimits::from_config(config, "execution"),
+            consensus: ResourceLimits::from_config(config, "consensus"),
+        }
+    }
+
+    pub fn ulimits(&self) -> Vec<Ulimit> {
+        self.nofile_limit
+            .map(|limit| Ulimit::new("nofile", limit, limit))
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Arguments appended to each client's command line.
+#[derive(Clone, Default)]
+pub(crate) struct ExtraArgs {
+    /// Execution client arguments, read from `execution_extra_args`.
+    pub execution: Vec<String>,
+    /// Beacon node arguments, read from `consensus_extra_args`.
+    pub consensus: Vec<String>,
+    /// Validator client arguments, read from `validator_extra_args`.
+    pub validator: Vec<String>,
+}
+
+impl ExtraArgs {
+    fn from_config(config: &PackageConfig) -> Self {
+        let parse = |container: &str| {
+            config
+                .values
+                .get(&format!("{container}_extra_args"))
+                .and_then(|value| split_arguments(value).ok())
+                .unwrap_or_default()
+        };
+        Self {
+            execution: parse("execution"),
+            consensus: parse("consensus"),
+            validator: parse("validator"),
+        }
+    }
 }
 
 impl EthereumSettings {
     pub fn runs_local_node(&self) -> bool {
         self.uses_local_execution || self.uses_local_consensus
     }
+
+    /// Beacon API URL of the external beacon node, or of the local one on this machine.
+    pub fn beacon_api_url(&self) -> String {
+        self.consensus_endpoint
+            .clone()
+            .unwrap_or_else(|| self.ports.consensus_http.local_url())
+    }
 }
 
 #[derive(Clone)]
 pub(crate) struct ValidatorSettings {
     pub fee_recipient: String,
+    pub graffiti: Option<String>,
+}
+
+/// The MEV-Boost sidecar and the builder preferences of the validator client, present when
+/// `mev_boost_enabled` is set.
+#[derive(Clone)]
+pub(crate) struct MevBoostSettings {
+    pub relays: Vec<String>,
+    /// Minimum bid in ETH; lower bids fall back to a locally built block.
+    pub min_bid: Option<f64>,
+    pub image: ImagePin,
+    pub builder: BuilderOptions,
+}
+
+impl MevBoostSettings {
+    fn from_config(config: &PackageConfig) -> Option<Self> {
+        let enabled = config
+            .values
+            .get("mev_boost_enabled")
+            .and_then(|value| value.parse::<bool>().ok())
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let relays = config
+            .values
+            .get("mev_boost_relays")
+            .map(|value| {
+                value
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|relay| !relay.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let min_bid = config
+            .values
+            .get("mev_boost_min_bid")
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|bid| bid.is_finite() && *bid > 0.0);
+        let parse_u64 = |key: &str| {
+            config
+                .values
+                .get(key)
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+        Some(Self {
+            relays,
+            min_bid,
+            image: ImagePin::from_config(config, "mev_boost", DEFAULT_MEV_BOOST_VERSION),
+            builder: BuilderOptions {
+                gas_limit: parse_u64("validator_gas_limit").filter(|limit| *limit > 0),
+                boost_factor: parse_u64("validator_builder_boost_factor"),
+            },
+        })
+    }
 }
 
 pub(crate) fn selected_network(config: &PackageConfig) -> Option<&str> {
@@ -38,22 +426,289 @@ pub(crate) fn ethereum_settings_from_config(config: &PackageConfig) -> EthereumS
     let validator_enabled = config
         .values
         .get("validator_enabled")
-        .map(|value| value == "true")
+        .and_then(|value| value.parse::<bool>().ok())
         .unwrap_or(false);
     let validator_fee_recipient = config
         .values
         .get("validator_fee_recipient")
         .filter(|value| !value.is_empty())
         .cloned();
+    let validator_graffiti = config
+        .values
+        .get("validator_graffiti")
+        .filter(|value| !value.is_empty())
+        .cloned();
+
+    let execution_client = config
+        .values
+        .get("execution_client")
+        .and_then(|value| ExecutionClient::parse(value))
+        .unwrap_or_default();
+    let consensus_client = config
+        .values
+        .get("consensus_client")
+        .and_then(|value| ConsensusClient::parse(value))
+        .unwrap_or_default();
+    let validator_client = config
+        .values
+        .get("validator_client")
+        .and_then(|value| ValidatorClient::parse(value))
+        .unwrap_or_default();
 
     EthereumSettings {
         uses_local_execution: execution_endpoint.is_none(),
         uses_local_consensus: consensus_endpoint.is_none(),
         validator: if validator_enabled {
-            validator_fee_recipient.map(|fee_recipient| ValidatorSettings { fee_recipient })
+            validator_fee_recipient.map(|fee_recipient| ValidatorSettings {
+                fee_recipient,
+                graffiti: validator_graffiti,
+            })
         } else {
             None
         },
+        execution_endpoint,
         consensus_endpoint,
+        execution_client,
+        execution_image: ImagePin::from_config(
+            config,
+            execution_client.as_str(),
+            execution_client.default_version(),
+        ),
+        consensus_client,
+        consensus_image: ImagePin::from_config(
+            config,
+            consensus_client.as_str(),
+            consensus_client.default_version(),
+        ),
+        validator_client,
+        validator_image: ImagePin::from_config(
+            config,
+            validator_client.as_str(),
+            validator_client.default_version(),
+        ),
+        mev_boost: MevBoostSettings::from_config(config),
+        ports: PortSettings::from_config(config),
+        runtime: RuntimeSettings::from_config(config),
+        extra_args: ExtraArgs::from_config(config),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ALL_INTERFACES, DEFAULT_EXECUTION_P2P_PORT, DEFAULT_RESTART_POLICY,
+        DEFAULT_STOP_TIMEOUT_SECS, LOCALHOST, ethereum_settings_from_config, has_port_overrides,
+        instance_port_config,
+    };
+    use crate::ethereum::consensus::ConsensusClient;
+    use crate::ethereum::execution::ExecutionClient;
+    use crate::ethereum::validator::ValidatorClient;
+    use crate::ethereum::{
+        DEFAULT_GETH_VERSION, DEFAULT_LIGHTHOUSE_VERSION, DEFAULT_MEV_BOOST_VERSION,
+        DEFAULT_NIMBUS_VERSION, DEFAULT_RETH_VERSION, DEFAULT_TEKU_VERSION,
+    };
+    use crate::packages::{PackageConfig, RestartPolicy};
+
+    #[test]
+    fn image_pins_default_to_known_good_versions() {
+        let settings = ethereum_settings_from_config(&PackageConfig::new());
+        assert_eq!(settings.execution_client, ExecutionClient::Reth);
+        assert_eq!(settings.execution_image.tag, DEFAULT_RETH_VERSION);
+        assert_eq!(settings.validator_client, ValidatorClient::Lighthouse);
+        assert_eq!(settings.validator_image.tag, DEFAULT_LIGHTHOUSE_VERSION);
+        assert!(settings.execution_image.digest.is_none());
+    }
+
+    #[test]
+    fn image_pins_honor_config_overrides() {
+        let mut config = PackageConfig::new();
+        config
+            .values
+            .insert("lighthouse_version".to_string(), "v8.0.0".to_string());
+        config.values.insert(
+            "reth_digest".to_string(),
+            format!("sha256:{}", "a".repeat(64)),
+        );
+        config
+            .values
+            .insert("reth_version".to_string(), String::new());
+
+        let settings = ethereum_settings_from_config(&config);
+        assert_eq!(settings.consensus_image.tag, "v8.0.0");
+        assert_eq!(settings.validator_image.tag, "v8.0.0");
+        assert_eq!(settings.execution_image.tag, DEFAULT_RETH_VERSION);
+        assert_eq!(
+            settings.execution_image.digest,
+            Some(format!("sha256:{}", "a".repeat(64)))
+        );
+    }
+
+    #[test]
+    fn execution_client_selects_its_own_image_pin() {
+        let mut config = PackageConfig::new();
+        config
+            .values
+            .insert("execution_client".to_string(), "geth".to_string());
+        config
+            .values
+            .insert("reth_version".to_string(), "v9.9.9".to_string());
+
+        let settings = ethereum_settings_from_config(&config);
+        assert_eq!(settings.execution_client, ExecutionClient::Geth);
+        assert_eq!(settings.execution_image.tag, DEFAULT_GETH_VERSION);
+
+        config
+            .values
+            .insert("geth_version".to_string(), "v1.99.0".to_string());
+        let settings = ethereum_settings_from_config(&config);
+        assert_eq!(settings.execution_image.tag, "v1.99.0");
+    }
+
+    #[test]
+    fn consensus_and_validator_clients_are_chosen_independently() {
+        let mut config = PackageConfig::new();
+        config
+            .values
+            .insert("consensus_client".to_string(), "teku".to_string());
+
+        let settings = ethereum_settings_from_config(&config);
+        assert_eq!(settings.consensus_client, ConsensusClient::Teku);
+        assert_eq!(settings.consensus_image.tag, DEFAULT_TEKU_VERSION);
+        assert_eq!(settings.validator_client, ValidatorClient::Lighthouse);
+        assert_eq!(settings.validator_image.tag, DEFAULT_LIGHTHOUSE_VERSION);
+
+        config
+            .values
+            .insert("validator_client".to_string(), "nimbus".to_string());
+        let settings = ethereum_settings_from_config(&config);
+        assert_eq!(settings.validator_client, ValidatorClient::Nimbus);
+        assert_eq!(settings.validator_image.tag, DEFAULT_NIMBUS_VERSION);
+
+        let settings = ethereum_settings_from_config(&PackageConfig::new());
+        assert_eq!(settings.consensus_client, ConsensusClient::Lighthouse);
+        assert_eq!(settings.consensus_image.tag, DEFAULT_LIGHTHOUSE_VERSION);
+    }
+
+    #[test]
+    fn mev_boost_is_off_until_enabled_and_reads_relays_and_builder_options() {
+        let mut config = PackageConfig::new();
+        config.values.insert(
+            "mev_boost_relays".to_string(),
+            "https://a.example, https://b.example\nhttps://c.example".to_string(),
+        );
+        assert!(ethereum_settings_from_config(&config).mev_boost.is_none());
+
+        for (key, value) in [
+            ("mev_boost_enabled", "true"),
+            ("mev_boost_min_bid", "0.05"),
+            ("validator_builder_boost_factor", "0"),
+        ] {
+            config.values.insert(key.to_string(), value.to_string());
+        }
+        let mev_boost = ethereum_settings_from_config(&config)
+            .mev_boost
+            .expect("MEV-Boost should be enabled");
+        assert_eq!(
+            mev_boost.relays,
+            vec![
+                "https://a.example",
+                "https://b.example",
+                "https://c.example"
+            ]
+        );
+        assert_eq!(mev_boost.min_bid, Some(0.05));
+        assert_eq!(mev_boost.image.tag, DEFAULT_MEV_BOOST_VERSION);
+        assert_eq!(mev_boost.builder.gas_limit, None);
+        assert_eq!(mev_boost.builder.boost_factor, Some(0));
+    }
+
+    #[test]
+    fn ports_default_to_client_defaults_and_honor_overrides() {
+        let settings = ethereum_settings_from_config(&PackageConfig::new());
+        assert_eq!(
+            settings.ports.execution_p2p.port,
+            DEFAULT_EXECUTION_P2P_PORT
+        );
+        assert_eq!(settings.ports.execution_metrics.address, ALL_INTERFACES);
+        assert_eq!(settings.ports.consensus_http.address, LOCALHOST);
+
+        let mut config = PackageConfig::new();
+        for (key, value) in [
+            ("execution_p2p_port", "30304"),
+            ("execution_metrics_address", "127.0.0.1"),
+            ("consensus_p2p_address", "10.0.0.2"),
+            ("consensus_quic_port", "9101"),
+        ] {
+            config.values.insert(key.to_string(), value.to_string());
+        }
+
+        let ports = ethereum_settings_from_config(&config).ports;
+        assert_eq!(ports.execution_p2p.port, 30304);
+        assert_eq!(ports.execution_metrics.address, "127.0.0.1");
+        assert_eq!(ports.execution_p2p.local_address(), LOCALHOST);
+        assert_eq!(ports.consensus_p2p.local_address(), "10.0.0.2");
+        assert_eq!(ports.consensus_quic.port, 9101);
+        assert_eq!(ports.consensus_quic.address, "10.0.0.2");
+    }
+
+    #[test]
+    fn runtime_defaults_restart_clients_and_allow_overrides() {
+        let runtime = ethereum_settings_from_config(&PackageConfig::new()).runtime;
+        assert_eq!(runtime.restart_policy, DEFAULT_RESTART_POLICY);
+        assert_eq!(runtime.stop_timeout_secs, DEFAULT_STOP_TIMEOUT_SECS);
+        assert!(runtime.execution.memory_limit_bytes.is_none());
+        assert!(runtime.ulimits().is_empty());
+
+        let mut config = PackageConfig::new();
+        for (key, value) in [
+            ("restart_policy", "on-failure"),
+            ("execution_memory_limit_mib", "16384"),
+            ("consensus_cpu_limit", "2.5"),
+            ("nofile_limit", "65536"),
+        ] {
+            config.values.insert(key.to_string(), value.to_string());
+        }
+
+        let runtime = ethereum_settings_from_config(&config).runtime;
+        assert_eq!(runtime.restart_policy, RestartPolicy::OnFailure);
+        assert_eq!(
+            runtime.execution.memory_limit_bytes,
+            Some(16384 * 1024 * 1024)
+        );
+        assert_eq!(runtime.consensus.cpu_limit, Some(2.5));
+        assert_eq!(runtime.ulimits().len(), 1);
+    }
+
+    #[test]
+    fn extra_args_are_split_per_container() {
+        let mut config = PackageConfig::new();
+        config.values.insert(
+            "consensus_extra_args".to_string(),
+            "--graffiti 'kitty node' --target-peers 80".to_string(),
+        );
+
+        let extra_args = ethereum_settings_from_config(&config).extra_args;
+        assert!(extra_args.execution.is_empty());
+        assert_eq!(
+            extra_args.consensus,
+            vec!["--graffiti", "kitty node", "--target-peers", "80"]
+        );
+        assert!(extra_args.validator.is_empty());
+    }
+
+    #[test]
+    fn instance_ports_skip_offsets_that_collide() {
+        let defaults = ethereum_settings_from_config(&PackageConfig::new()).ports;
+        let mut taken = defaults.host_ports();
+        let first = instance_port_config(&taken).expect("ports should be found");
+        assert!(first.contains(&("execution_p2p_port".to_string(), "30313".to_string())));
+        assert!(first.contains(&("consensus_http_port".to_string(), "5062".to_string())));
+
+        let mut config = PackageConfig::new();
+        config.values.extend(first);
+        assert!(has_port_overrides(&config));
+        taken.extend(ethereum_settings_from_config(&config).ports.host_ports());
+        let second = instance_port_config(&taken).expect("ports should be found");
+        assert!(second.contains(&("execution_p2p_port".to_string(), "30323".to_string())));
     }
 }
@@ -0,0 +1,524 @@
+use super::execution::EPHEMERY_MOUNT;
+use crate::ethereum::{
+    DEFAULT_LIGHTHOUSE_VERSION, DEFAULT_LODESTAR_VERSION, DEFAULT_NIMBUS_VERSION,
+    DEFAULT_TEKU_VERSION, EPHEMERY_NETWORK_NAME, EphemeryConfig, LIGHTHOUSE_DATA_DIR,
+    LIGHTHOUSE_DATA_VOLUME, LIGHTHOUSE_IMAGE, LIGHTHOUSE_VALIDATOR_CONTAINER_NAME,
+};
+
+/// Where the key import job mounts the staged keystores and passwords.
+pub(crate) const IMPORT_MOUNT: &str = "/import";
+
+/// Validator clients the Ethereum package can run.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum ValidatorClient {
+    #[default]
+    Lighthouse,
+    Teku,
+    Nimbus,
+    Lodestar,
+}
+
+/// Where the validator client finds its beacon node and what it puts in the blocks it proposes.
+pub(crate) struct ValidatorOptions<'a> {
+    pub beacon_endpoint: &'a str,
+    pub fee_recipient: &'a str,
+    pub graffiti: Option<&'a str>,
+    /// Set when MEV-Boost is enabled, so the client proposes blocks from external builders.
+    pub builder: Option<&'a BuilderOptions>,
+}
+
+/// Block builder preferences the validator client registers with MEV-Boost relays.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BuilderOptions {
+    pub gas_limit: Option<u64>,
+    /// Percentage a builder bid is weighed by against the local payload; 0 always builds
+    /// locally.
+    pub boost_factor: Option<u64>,
+}
+
+/// Paths, relative to the import directory, that a keystore and its password are staged at.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct StagedKey {
+    pub keystore: String,
+    pub password: String,
+}
+
+/// How to run the key import job: an optional entrypoint override and its arguments.
+pub(crate) struct ImportCommand {
+    pub entrypoint: Vec<String>,
+    pub args: Vec<String>,
+}
+
+impl ValidatorClient {
+    pub const ALL: [Self; 4] = [Self::Lighthouse, Self::Teku, Self::Nimbus, Self::Lodestar];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lighthouse => "lighthouse",
+            Self::Teku => "teku",
+            Self::Nimbus => "nimbus",
+            Self::Lodestar => "lodestar",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|client| client.as_str() == value)
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Lighthouse => "Lighthouse",
+            Self::Teku => "Teku",
+            Self::Nimbus => "Nimbus",
+            Self::Lodestar => "Lodestar",
+        }
+    }
+
+    /// Nimbus ships its validator client as a separate image; the others reuse the beacon node
+    /// image, and every client shares its `{client}_version` pin with the beacon node.
+    pub fn image(self) -> &'static str {
+        match self {
+            Self::Lighthouse => LIGHTHOUSE_IMAGE,
+            Self::Teku => "consensys/teku",
+            Self::Nimbus => "statusim/nimbus-validator-client",
+            Self::Lodestar => "chainsafe/lodestar",
+        }
+    }
+
+    pub fn default_version(self) -> &'static str {
+        match self {
+            Self::Lighthouse => DEFAULT_LIGHTHOUSE_VERSION,
+            Self::Teku => DEFAULT_TEKU_VERSION,
+            Self::Nimbus => DEFAULT_NIMBUS_VERSION,
+            Self::Lodestar => DEFAULT_LODESTAR_VERSION,
+        }
+    }
+
+    pub fn container_name(self) -> &'static str {
+        match self {
+            Self::Lighthouse => LIGHTHOUSE_VALIDATOR_CONTAINER_NAME,
+            Self::Teku => "kittynode-teku-validator",
+            Self::Nimbus => "kittynode-nimbus-validator",
+            Self::Lodestar => "kittynode-lodestar-validator",
+        }
+    }
+
+    /// Lighthouse keeps the volume it has always shared with the Lighthouse beacon node, so
+    /// keys imported before other clients existed stay where the validator client looks.
+    pub fn data_volume(self) -> &'static str {
+        match self {
+            Self::Lighthouse => LIGHTHOUSE_DATA_VOLUME,
+            Self::Teku => "kittynode-teku-validator-data",
+            Self::Nimbus => "kittynode-nimbus-validator-data",
+            Self::Lodestar => "kittynode-lodestar-validator-data",
+        }
+    }
+
+    pub fn data_dir(self) -> &'static str {
+        match self {
+            Self::Lighthouse => LIGHTHOUSE_DATA_DIR,
+            Self::Teku => "/var/lib/teku",
+            Self::Nimbus => "/home/user/data",
+            Self::Lodestar => "/data",
+        }
+    }
+
+    /// The user the image runs as when it is not root, who has to own the imported files.
+    fn image_user(self) -> Option<&'static str> {
+        match self {
+            Self::Teku => Some("teku"),
+            Self::Nimbus => Some("user"),
+            Self::Lighthouse | Self::Lodestar => None,
+        }
+    }
+
+    /// Where the client keeps the slashing protection database for `network`.
+    pub fn slashing_protection_path(self, network: &str) -> String {
+        match self {
+            Self::Lighthouse => {
+                // Lighthouse names its network directory `custom` when run with --testnet-dir.
+                let network_dir = if network == EPHEMERY_NETWORK_NAME {
+                    "custom"
+                } else {
+                    network
+                };
+                format!("{LIGHTHOUSE_DATA_DIR}/{network_dir}/validators/slashing_protection.sqlite")
+            }
+            Self::Teku => format!("{}/validator/slashprotection", self.data_dir()),
+            Self::Nimbus => format!("{}/validators/slashing_protection.sqlite3", self.data_dir()),
+            Self::Lodestar => format!("{}/validator-db", self.data_dir()),
+        }
+    }
+
+    /// Where a keystore named `file_name` for `pubkey` and its password are staged.
+    ///
+    /// Lighthouse and Lodestar read every keystore from one directory with a shared password
+    /// file. Teku and Nimbus have no import command, so their keys are staged in the layout they
+    /// load from the data directory, with one password file per keystore.
+    pub fn staged_key(self, file_name: &str, pubkey: &str) -> StagedKey {
+        let pubkey = pubkey.trim_start_matches("0x");
+        match self {
+            Self::Lighthouse | Self::Lodestar => StagedKey {
+                keystore: format!("keystores/{file_name}"),
+                password: "password".to_string(),
+            },
+            Self::Teku => StagedKey {
+                keystore: format!("validator-keys/{file_name}"),
+                password: format!(
+                    "validator-passwords/{}.txt",
+                    file_name.trim_end_matches(".json")
+                ),
+            },
+            Self::Nimbus => StagedKey {
+                keystore: format!("validators/0x{pubkey}/keystore.json"),
+                password: format!("secrets/0x{pubkey}"),
+            },
+        }
+    }
+
+    /// Returns the job that moves the staged keys under [`IMPORT_MOUNT`] into the data volume.
+    pub fn import_command(self, network: &str, ephemery: bool) -> ImportCommand {
+        match self {
+            Self::Lighthouse => {
+                let mut args = vec!["lighthouse".to_string()];
+                args.extend(lighthouse_network_args(network, ephemery));
+                args.extend([
+                    "account".to_string(),
+                    "validator".to_string(),
+                    "import".to_string(),
+                    "--directory".to_string(),
+                    format!("{IMPORT_MOUNT}/keystores"),
+                    "--password-file".to_string(),
+                    format!("{IMPORT_MOUNT}/password"),
+                ]);
+                ImportCommand {
+                    entrypoint: Vec::new(),
+                    args,
+                }
+            }
+            Self::Lodestar => {
+                let mut args = vec!["validator".to_string(), "import".to_string()];
+                args.extend(lodestar_network_args(network, ephemery));
+                args.extend([
+                    format!("--dataDir={}", self.data_dir()),
+                    format!("--importKeystores={IMPORT_MOUNT}/keystores"),
+                    format!("--importKeystoresPassword={IMPORT_MOUNT}/password"),
+                ]);
+                ImportCommand {
+                    entrypoint: Vec::new(),
+                    args,
+                }
+            }
+            Self::Teku | Self::Nimbus => {
+                let data_dir = self.data_dir();
+                let mut script = format!("cp -R {IMPORT_MOUNT}/. {data_dir}/");
+                if let Some(user) = self.image_user() {
+                    script.push_str(&format!(" && chown -R {user}:{user} {data_dir}"));
+                }
+                ImportCommand {
+                    entrypoint: vec!["sh".to_string(), "-c".to_string()],
+                    args: vec![script],
+                }
+            }
+        }
+    }
+
+    pub fn command(
+        self,
+        network: &str,
+        options: &ValidatorOptions<'_>,
+        ephemery: Option<&EphemeryConfig>,
+    ) -> Vec<String> {
+        let data_dir = self.data_dir();
+        let mut command = match self {
+            Self::Lighthouse => {
+                let mut command = vec!["lighthouse".to_string()];
+                command.extend(lighthouse_network_args(network, ephemery.is_some()));
+                command.extend([
+                    "vc".to_string(),
+                    "--beacon-nodes".to_string(),
+                    options.beacon_endpoint.to_string(),
+                    "--suggested-fee-recipient".to_string(),
+                    options.fee_recipient.to_string(),
+                ]);
+                command
+            }
+            Self::Teku => vec![
+                "validator-client".to_string(),
+                match ephemery {
+                    Some(_) => format!("--network={EPHEMERY_MOUNT}/config.yaml"),
+                    None => format!("--network={network}"),
+                },
+                format!("--data-path={data_dir}"),
+                format!("--beacon-node-api-endpoint={}", options.beacon_endpoint),
+                format!(
+                    "--validators-proposer-default-fee-recipient={}",
+                    options.fee_recipient
+                ),
+                format!(
+                    "--validator-keys={data_dir}/validator-keys:{data_dir}/validator-passwords"
+                ),
+            ],
+            // The Nimbus validator client takes the network configuration from its beacon node.
+            Self::Nimbus => vec![
+                format!("--data-dir={data_dir}"),
+                format!("--beacon-node={}", options.beacon_endpoint),
+                format!("--suggested-fee-recipient={}", options.fee_recipient),
+            ],
+            Self::Lodestar => {
+                let mut command = vec!["validator".to_string()];
+                command.extend(lodestar_network_args(network, ephemery.is_some()));
+                command.extend([
+                    format!("--dataDir={data_dir}"),
+                    format!("--beaconNodes={}", options.beacon_endpoint),
+                    format!("--suggestedFeeRecipient={}", options.fee_recipient),
+                ]);
+                command
+            }
+        };
+        if let Some(graffiti) = options.graffiti {
+            match self {
+                Self::Lighthouse => {
+                    command.extend(["--graffiti".to_string(), graffiti.to_string()]);
+                }
+                Self::Teku => command.push(format!("--validators-graffiti={graffiti}")),
+                Self::Nimbus | Self::Lodestar => command.push(format!("--graffiti={graffiti}")),
+            }
+        }
+        if let Some(builder) = options.builder {
+            command.extend(self.builder_args(builder));
+        }
+        command
+    }
+
+    fn builder_args(self, builder: &BuilderOptions) -> Vec<String> {
+        let mut args = Vec::new();
+        match self {
+            Self::Lighthouse => {
+                args.push("--builder-proposals".to_string());
+                if let Some(gas_limit) = builder.gas_limit {
+                    args.extend(["--gas-limit".to_string(), gas_limit.to_string()]);
+                }
+                if let Some(boost_factor) = builder.boost_factor {
+                    args.extend([
+                        "--builder-boost-factor".to_string(),
+                        boost_factor.to_string(),
+                    ]);
+                }
+            }
+            // Teku only takes a boost factor from a proposer config file, which Kittynode
+            // doesn't write.
+            Self::Teku => {
+                args.push("--validators-builder-registration-default-enabled=true".to_string());
+                if let Some(gas_limit) = builder.gas_limit {
+                    args.push(format!(
+                        "--validators-builder-registration-default-gas-limit={gas_limit}"
+                    ));
+                }
+            }
+            Self::Nimbus => {
+                args.push("--payload-builder=true".to_string());
+                if let Some(gas_limit) = builder.gas_limit {
+                    args.push(format!("--suggested-gas-limit={gas_limit}"));
+                }
+                if let Some(boost_factor) = builder.boost_factor {
+                    args.push(format!("--builder-boost-factor={boost_factor}"));
+                }
+            }
+            Self::Lodestar => {
+                args.push("--builder".to_string());
+                if let Some(gas_limit) = builder.gas_limit {
+                    args.push(format!("--defaultGasLimit={gas_limit}"));
+                }
+                if let Some(boost_factor) = builder.boost_factor {
+                    args.push(format!("--builder.boostFactor={boost_factor}"));
+                }
+            }
+        }
+        args
+    }
+}
+
+/// Returns true when `volume` is a validator client's data volume, for the default package or a
+/// named instance. These volumes hold the validator keys and the slashing protection database.
+pub(crate) fn is_validator_data_volume(volume: &str) -> bool {
+    ValidatorClient::ALL.iter().any(|client| {
+        volume
+            .strip_prefix(client.data_volume())
+            .is_some_and(|suffix| suffix.is_empty() || suffix.starts_with('-'))
+    })
+}
+
+fn lighthouse_network_args(network: &str, ephemery: bool) -> [String; 2] {
+    if ephemery {
+        ["--testnet-dir".to_string(), EPHEMERY_MOUNT.to_string()]
+    } else {
+        ["--network".to_string(), network.to_string()]
+    }
+}
+
+fn lodestar_network_args(network: &str, ephemery: bool) -> Vec<String> {
+    if ephemery {
+        vec![format!("--paramsFile={EPHEMERY_MOUNT}/config.yaml")]
+    } else {
+        vec![format!("--network={network}")]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BuilderOptions, IMPORT_MOUNT, StagedKey, ValidatorClient, ValidatorOptions,
+        is_validator_data_volume,
+    };
+    use crate::ethereum::EphemeryConfig;
+    use crate::ethereum::execution::EPHEMERY_MOUNT;
+    use std::path::PathBuf;
+
+    const OPTIONS: ValidatorOptions<'static> = ValidatorOptions {
+        beacon_endpoint: "http://kittynode-lighthouse-node:5052",
+        fee_recipient: "0x00000000000000000000000000000000000000aa",
+        graffiti: Some("kitty node"),
+        builder: None,
+    };
+
+    #[test]
+    fn client_names_round_trip() {
+        for client in ValidatorClient::ALL {
+            assert_eq!(ValidatorClient::parse(client.as_str()), Some(client));
+        }
+        assert_eq!(ValidatorClient::parse("prysm"), None);
+    }
+
+    #[test]
+    fn every_client_sets_the_beacon_node_fee_recipient_and_graffiti() {
+        for client in ValidatorClient::ALL {
+            let command = client.command("hoodi", &OPTIONS, None);
+            let joined = command.join(" ");
+            assert!(
+                joined.contains(OPTIONS.beacon_endpoint),
+                "{client:?}: {joined}"
+            );
+            assert!(
+                joined.contains(OPTIONS.fee_recipient),
+                "{client:?}: {joined}"
+            );
+            assert!(
+                command.iter().any(|arg| arg.ends_with("kitty node")),
+                "{client:?} should keep the graffiti as one argument: {command:?}"
+            );
+        }
+
+        let options = ValidatorOptions {
+            graffiti: None,
+            ..OPTIONS
+        };
+        for client in ValidatorClient::ALL {
+            let joined = client.command("hoodi", &options, None).join(" ");
+            assert!(!joined.contains("graffiti"), "{client:?}: {joined}");
+        }
+    }
+
+    #[test]
+    fn builder_options_only_apply_when_mev_boost_is_enabled() {
+        let builder = BuilderOptions {
+            gas_limit: Some(60_000_000),
+            boost_factor: None,
+        };
+        let options = ValidatorOptions {
+            builder: Some(&builder),
+            ..OPTIONS
+        };
+        for client in ValidatorClient::ALL {
+            let plain = client.command("hoodi", &OPTIONS, None);
+            let with_builder = client.command("hoodi", &options, None);
+            assert!(with_builder.len() > plain.len(), "{client:?}");
+            assert!(
+                with_builder.join(" ").contains("60000000"),
+                "{client:?}: {with_builder:?}"
+            );
+            assert!(
+                !plain.join(" ").contains("builder"),
+                "{client:?}: {plain:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn ephemery_points_clients_at_the_testnet_files() {
+        let ephemery = EphemeryConfig {
+            tag: "ephemery-1".to_string(),
+            metadata_dir: PathBuf::from("/tmp/ephemery"),
+            execution_bootnodes: Vec::new(),
+            consensus_bootnodes: Vec::new(),
+        };
+        for client in [
+            ValidatorClient::Lighthouse,
+            ValidatorClient::Teku,
+            ValidatorClient::Lodestar,
+        ] {
+            let command = client.command("ephemery", &OPTIONS, Some(&ephemery));
+            assert!(
+                command.iter().any(|arg| arg.contains(EPHEMERY_MOUNT)),
+                "{client:?}: {command:?}"
+            );
+            let import = client.import_command("ephemery", true);
+            assert!(
+                !import.args.iter().any(|arg| arg == "ephemery"),
+                "{client:?} should not import for a named Ephemery network: {:?}",
+                import.args
+            );
+        }
+    }
+
+    #[test]
+    fn staged_keys_match_each_client_layout() {
+        let file_name = "keystore-m_12381_3600_0_0_0-1700000000.json";
+        assert_eq!(
+            ValidatorClient::Lighthouse.staged_key(file_name, "ab12"),
+            StagedKey {
+                keystore: format!("keystores/{file_name}"),
+                password: "password".to_string(),
+            }
+        );
+        assert_eq!(
+            ValidatorClient::Teku.staged_key(file_name, "ab12"),
+            StagedKey {
+                keystore: format!("validator-keys/{file_name}"),
+                password: "validator-passwords/keystore-m_12381_3600_0_0_0-1700000000.txt"
+                    .to_string(),
+            }
+        );
+        assert_eq!(
+            ValidatorClient::Nimbus.staged_key(file_name, "0xab12"),
+            StagedKey {
+                keystore: "validators/0xab12/keystore.json".to_string(),
+                password: "secrets/0xab12".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn import_jobs_read_the_staged_keys() {
+        for client in ValidatorClient::ALL {
+            let import = client.import_command("hoodi", false);
+            assert!(
+                import.args.iter().any(|arg| arg.contains(IMPORT_MOUNT)),
+                "{client:?}: {:?}",
+                import.args
+            );
+        }
+    }
+
+    #[test]
+    fn validator_data_volumes_include_named_instances() {
+        assert!(is_validator_data_volume("kittynode-lighthouse-data"));
+        assert!(is_validator_data_volume(
+            "kittynode-teku-validator-data-solo"
+        ));
+        assert!(!is_validator_data_volume("kittynode-teku-data"));
+        assert!(!is_validator_data_volume("kittynode-lighthouse-database"));
+    }
+}
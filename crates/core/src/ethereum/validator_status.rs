@@ -0,0 +1,327 @@
+use super::beacon_api::{self, agent, parse_decimal};
+use super::key_import::find_keystores;
+use eyre::{Context, Result, eyre};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{collections::HashMap, fs, path::Path};
+use tokio::task;
+
+pub(super) const VALIDATORS_PATH: &str = "/eth/v1/beacon/states/head/validators";
+/// Keeps each request body small enough for every beacon node to accept it.
+const IDS_PER_REQUEST: usize = 64;
+
+/// Where a validator key stands on the beacon chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorStatus {
+    pub pubkey: String,
+    /// Assigned once the beacon chain processes a deposit for the key.
+    pub index: Option<u64>,
+    pub state: ValidatorState,
+    pub balance_gwei: Option<u64>,
+    pub effective_balance_gwei: Option<u64>,
+    pub withdrawal_credentials: Option<WithdrawalCredentialsType>,
+}
+
+/// The validator lifecycle states of the beacon API, plus keys the chain has no deposit for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorState {
+    NotDeposited,
+    PendingInitialized,
+    PendingQueued,
+    ActiveOngoing,
+    ActiveExiting,
+    ActiveSlashed,
+    ExitedUnslashed,
+    ExitedSlashed,
+    WithdrawalPossible,
+    WithdrawalDone,
+}
+
+impl ValidatorState {
+    pub const ALL: [Self; 10] = [
+        Self::NotDeposited,
+        Self::PendingInitialized,
+        Self::PendingQueued,
+        Self::ActiveOngoing,
+        Self::ActiveExiting,
+        Self::ActiveSlashed,
+        Self::ExitedUnslashed,
+        Self::ExitedSlashed,
+        Self::WithdrawalPossible,
+        Self::WithdrawalDone,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::NotDeposited => "not_deposited",
+            Self::PendingInitialized => "pending_initialized",
+            Self::PendingQueued => "pending_queued",
+            Self::ActiveOngoing => "active_ongoing",
+            Self::ActiveExiting => "active_exiting",
+            Self::ActiveSlashed => "active_slashed",
+            Self::ExitedUnslashed => "exited_unslashed",
+            Self::ExitedSlashed => "exited_slashed",
+            Self::WithdrawalPossible => "withdrawal_possible",
+            Self::WithdrawalDone => "withdrawal_done",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|state| state.as_str() == value)
+    }
+}
+
+/// The kind of withdrawal credentials a validator has, named after their prefix byte.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum WithdrawalCredentialsType {
+    /// Withdrawals are still locked to a BLS key.
+    #[serde(rename = "0x00")]
+    Bls,
+    /// Balances above 32 ETH are swept to an execution address.
+    #[serde(rename = "0x01")]
+    Execution,
+    /// Balances compound up to 2048 ETH before being swept to an execution address.
+    #[serde(rename = "0x02")]
+    Compounding,
+}
+
+impl WithdrawalCredentialsType {
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::Bls => "0x00",
+            Self::Execution => "0x01",
+            Self::Compounding => "0x02",
+        }
+    }
+
+    /// Reads the type from hex-encoded withdrawal credentials.
+    pub(crate) fn from_credentials(credentials: &str) -> Option<Self> {
+        match credentials.get(..4)? {
+            "0x00" => Some(Self::Bls),
+            "0x01" => Some(Self::Execution),
+            "0x02" => Some(Self::Compounding),
+            _ => None,
+        }
+    }
+}
+
+/// Reads the validator pubkeys in `path`: a directory of keystores or deposit data files, or a
+/// single keystore or deposit data file.
+pub(crate) fn read_validator_pubkeys(path: &Path) -> Result<Vec<String>> {
+    let mut pubkeys = Vec::new();
+    if path.is_dir() {
+        pubkeys.extend(
+            find_keystores(path)?
+                .into_iter()
+                .map(|keystore| keystore.pubkey),
+        );
+        if pubkeys.is_empty() {
+            let mut deposit_files = Vec::new();
+            for entry in
+                fs::read_dir(path).wrap_err_with(|| format!("Failed to read {}", path.display()))?
+            {
+                let file = entry?.path();
+                if file
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("deposit_data") && name.ends_with(".json"))
+                {
+                    deposit_files.push(file);
+                }
+            }
+            deposit_files.sort();
+            for file in deposit_files {
+                pubkeys.extend(read_key_file(&file)?);
+            }
+        }
+    } else {
+        pubkeys.extend(read_key_file(path)?);
+    }
+
+    let mut normalized: Vec<String> = Vec::new();
+    for pubkey in pubkeys {
+        let pubkey = normalize_pubkey(&pubkey)?;
+        if !normalized.contains(&pubkey) {
+            normalized.push(pubkey);
+        }
+    }
+    if normalized.is_empty() {
+        return Err(eyre!(
+            "No validator keystores or deposit data found in {}",
+            path.display()
+        ));
+    }
+    Ok(normalized)
+}
+
+/// Reads the pubkeys of a deposit data file, which lists deposits, or of a keystore.
+fn read_key_file(path: &Path) -> Result<Vec<String>> {
+    let contents =
+        fs::read_to_string(path).wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+    let json: Value = serde_json::from_str(&contents)
+        .wrap_err_with(|| format!("{} is not valid JSON", path.display()))?;
+    let entries: Vec<&Value> = match &json {
+        Value::Array(deposits) => deposits.iter().collect(),
+        keystore => vec![keystore],
+    };
+    entries
+        .into_iter()
+        .map(|entry| {
+            entry
+                .get("pubkey")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| eyre!("{} has an entry without a pubkey", path.display()))
+        })
+        .collect()
+}
+
+fn normalize_pubkey(pubkey: &str) -> Result<String> {
+    let hex = pubkey.strip_prefix("0x").unwrap_or(pubkey);
+    if hex.len() != 96 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(eyre!("Invalid validator pubkey {pubkey}"));
+    }
+    Ok(format!("0x{}", hex.to_ascii_lowercase()))
+}
+
+/// Looks `pubkeys` up in the head state of the beacon node at `beacon_url`, in the same order.
+pub(crate) async fn validator_statuses(
+    beacon_url: String,
+    pubkeys: Vec<String>,
+) -> Result<Vec<ValidatorStatus>> {
+    task::spawn_blocking(move || query_validators(&beacon_url, &pubkeys)).await?
+}
+
+fn query_validators(beacon_url: &str, pubkeys: &[String]) -> Result<Vec<ValidatorStatus>> {
+    let agent = agent();
+    let mut found = HashMap::new();
+    for ids in pubkeys.chunks(IDS_PER_REQUEST) {
+        let data =
+            beacon_api::post_data(&agent, beacon_url, VALIDATORS_PATH, &json!({ "ids": ids }))
+                .wrap_err_with(|| format!("Failed to query the beacon node at {beacon_url}"))?;
+        let entries = data
+            .as_array()
+            .ok_or_else(|| eyre!("{VALIDATORS_PATH} returned an unexpected response"))?;
+        for entry in entries {
+            let status = parse_validator(entry)?;
+            found.insert(status.pubkey.clone(), status);
+        }
+    }
+
+    Ok(pubkeys
+        .iter()
+        .map(|pubkey| {
+            found.remove(pubkey).unwrap_or_else(|| ValidatorStatus {
+                pubkey: pubkey.clone(),
+                index: None,
+                state: ValidatorState::NotDeposited,
+                balance_gwei: None,
+                effective_balance_gwei: None,
+                withdrawal_credentials: None,
+            })
+        })
+        .collect())
+}
+
+/// Reads one entry of the beacon API validators response.
+fn parse_validator(entry: &Value) -> Result<ValidatorStatus> {
+    let validator = entry
+        .get("validator")
+        .ok_or_else(|| eyre!("{VALIDATORS_PATH} returned an entry without a validator"))?;
+    let pubkey = validator
+        .get("pubkey")
+        .and_then(Value::as_str)
+        .ok_or_else(|| eyre!("{VALIDATORS_PATH} returned a validator without a pubkey"))?;
+    let status = entry.get("status").and_then(Value::as_str).unwrap_or("");
+    let state = ValidatorState::parse(status)
+        .ok_or_else(|| eyre!("{VALIDATORS_PATH} returned an unknown status {status:?}"))?;
+    Ok(ValidatorStatus {
+        pubkey: normalize_pubkey(pubkey)?,
+        index: entry.get("index").and_then(parse_decimal),
+        state,
+        balance_gwei: entry.get("balance").and_then(parse_decimal),
+        effective_balance_gwei: validator.get("effective_balance").and_then(parse_decimal),
+        withdrawal_credentials: validator
+            .get("withdrawal_credentials")
+            .and_then(Value::as_str)
+            .and_then(WithdrawalCredentialsType::from_credentials),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ValidatorState, WithdrawalCredentialsType, parse_validator, read_validator_pubkeys,
+    };
+    use serde_json::json;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn pubkey(digit: char) -> String {
+        format!("0x{}", digit.to_string().repeat(96))
+    }
+
+    #[test]
+    fn pubkeys_come_from_keystores_or_deposit_data() {
+        let dir = tempdir().unwrap();
+        let deposits = json!([
+            { "pubkey": "A".repeat(96), "amount": 32000000000u64 },
+            { "pubkey": "b".repeat(96), "amount": 32000000000u64 },
+        ]);
+        fs::write(
+            dir.path().join("deposit_data-1700000000.json"),
+            deposits.to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            read_validator_pubkeys(dir.path()).unwrap(),
+            vec![pubkey('a'), pubkey('b')]
+        );
+
+        // Keystores take precedence over the deposit data written next to them.
+        fs::write(
+            dir.path()
+                .join("keystore-m_12381_3600_0_0_0-1700000000.json"),
+            json!({ "pubkey": "a".repeat(96), "version": 4 }).to_string(),
+        )
+        .unwrap();
+        assert_eq!(
+            read_validator_pubkeys(dir.path()).unwrap(),
+            vec![pubkey('a')]
+        );
+
+        let empty = tempdir().unwrap();
+        assert!(read_validator_pubkeys(empty.path()).is_err());
+    }
+
+    #[test]
+    fn validator_entries_are_read_from_the_beacon_api() {
+        let entry = json!({
+            "index": "1234",
+            "balance": "32001000000",
+            "status": "active_ongoing",
+            "validator": {
+                "pubkey": pubkey('c'),
+                "withdrawal_credentials": format!("0x01{}", "0".repeat(62)),
+                "effective_balance": "32000000000",
+                "slashed": false,
+            },
+        });
+        let status = parse_validator(&entry).unwrap();
+        assert_eq!(status.pubkey, pubkey('c'));
+        assert_eq!(status.index, Some(1234));
+        assert_eq!(status.state, ValidatorState::ActiveOngoing);
+        assert_eq!(status.balance_gwei, Some(32_001_000_000));
+        assert_eq!(status.effective_balance_gwei, Some(32_000_000_000));
+        assert_eq!(
+            status.withdrawal_credentials,
+            Some(WithdrawalCredentialsType::Execution)
+        );
+
+        let mut unknown = entry;
+        unknown["status"] = json!("retired");
+        assert!(parse_validator(&unknown).is_err());
+    }
+}
@@ -1,11 +1,12 @@
 use crate::config::{Config, get_config, write_config};
 use crate::docker::{is_docker_running, start_docker};
+use crate::ethereum::node_diagnostics;
 use eyre::Result;
 use std::{
     io::ErrorKind,
     sync::{LazyLock, Mutex},
 };
-use tracing::info;
+use tracing::{info, warn};
 
 #[derive(Copy, Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -69,7 +70,15 @@ pub async fn get_operational_state() -> Result<OperationalState> {
         OperationalMode::Remote => true,
     };
 
-    Ok(compose_operational_state(mode, docker_running))
+    let mut state = compose_operational_state(mode, docker_running);
+    // Remote nodes report their own diagnostics through the server.
+    if mode == OperationalMode::Local && docker_running {
+        match node_diagnostics().await {
+            Ok(diagnostics) => state.diagnostics.extend(diagnostics),
+            Err(error) => warn!("Failed to check Ethereum node health: {error}"),
+        }
+    }
+    Ok(state)
 }
 
 pub async fn start_docker_if_needed() -> Result<DockerStartStatus> {
@@ -1,22 +1,55 @@
 #[path = "packages/catalog.rs"]
 mod catalog;
+#[path = "packages/gc.rs"]
+mod gc;
+#[path = "packages/health.rs"]
+mod health;
+#[path = "packages/instance.rs"]
+mod instance;
 #[path = "packages/lifecycle.rs"]
 mod lifecycle;
+#[path = "packages/manifest.rs"]
+mod manifest;
+#[path = "packages/plan.rs"]
+mod plan;
+#[path = "packages/ports.rs"]
+mod ports;
+#[path = "packages/schema.rs"]
+mod schema;
 #[path = "packages/state.rs"]
 mod state;
 #[path = "packages/store.rs"]
 mod store;
+#[path = "packages/transaction.rs"]
+mod transaction;
 #[path = "packages/types.rs"]
 mod types;
+#[path = "packages/upgrade.rs"]
+mod upgrade;
 
+pub use gc::{OrphanedResource, ResourceKind};
+pub use instance::{INSTANCE_SEPARATOR, instance_name};
+pub use plan::{ContainerAction, Plan, PlanOperation, PlannedContainer, PlannedPort};
+pub use ports::{PortConflict, PortConflictError, PortHolder};
+pub use schema::{ConfigField, ConfigFieldKind, ConfigValidationError};
 pub use store::PackageConfigStore;
 pub use types::{
-    Binding, Container, InstallStatus, Package, PackageConfig, PackageState, PortBinding,
-    RuntimeStatus,
+    Binding, Container, ContainerHealth, ContainerState, HealthCheck, HealthProbe, InstallStatus,
+    Package, PackageConfig, PackageState, PortBinding, RestartPolicy, RuntimeStatus, Ulimit,
 };
 
+pub use upgrade::ContainerUpgrade;
+
+pub(crate) use catalog::ownership_labels;
+pub(crate) use instance::{
+    list_instances, resolve_instance_name, scoped_resource_name, split_instance_name,
+    validate_instance_id,
+};
+pub(crate) use lifecycle::merge_config_update;
+pub(crate) use schema::split_arguments;
 pub(crate) use types::PackageDefinition;
 
+use crate::docker::PullProgress;
 use eyre::Result;
 use std::collections::HashMap;
 
@@ -24,8 +57,9 @@ pub fn get_package_catalog() -> Result<HashMap<String, Package>> {
     catalog::get_package_catalog()
 }
 
-pub async fn get_package(name: &str) -> Result<PackageState> {
-    state::get_package(name).await
+/// Returns the state of a package, or of its named `instance`.
+pub async fn get_package(name: &str, instance: Option<&str>) -> Result<PackageState> {
+    state::get_package(&resolve_instance_name(name, instance)?).await
 }
 
 pub async fn get_packages(names: &[&str]) -> Result<HashMap<String, PackageState>> {
@@ -36,30 +70,110 @@ pub async fn get_installed_packages() -> Result<Vec<Package>> {
     state::get_installed_packages().await
 }
 
-pub async fn get_package_config(package_name: &str) -> Result<PackageConfig> {
-    PackageConfigStore::load(package_name)
+pub async fn get_package_config(
+    package_name: &str,
+    instance: Option<&str>,
+) -> Result<PackageConfig> {
+    PackageConfigStore::load(&resolve_instance_name(package_name, instance)?)
+}
+
+pub fn get_package_config_schema(package_name: &str) -> Result<Vec<ConfigField>> {
+    catalog::get_package_config_schema(package_name)
 }
 
 pub async fn install_package(name: &str) -> Result<()> {
     lifecycle::install_package(name).await
 }
 
-pub async fn install_package_with_network(name: &str, network: Option<&str>) -> Result<()> {
-    lifecycle::install_package_with_network(name, network).await
+/// Installs a package, or a named `instance` of it that runs alongside the default one.
+pub async fn install_package_with_network(
+    name: &str,
+    instance: Option<&str>,
+    network: Option<&str>,
+) -> Result<()> {
+    let name = resolve_instance_name(name, instance)?;
+    lifecycle::install_package_with_network(&name, network).await
+}
+
+/// Installs a package like [`install_package_with_network`], reporting image pull progress.
+pub async fn install_package_with_progress(
+    name: &str,
+    instance: Option<&str>,
+    network: Option<&str>,
+    mut on_progress: impl FnMut(PullProgress) + Send,
+) -> Result<()> {
+    let name = resolve_instance_name(name, instance)?;
+    lifecycle::install_package_with_progress(&name, network, &mut on_progress).await
+}
+
+/// Describes what [`install_package_with_network`] would change without changing anything.
+pub async fn plan_install_package(
+    name: &str,
+    instance: Option<&str>,
+    network: Option<&str>,
+) -> Result<Plan> {
+    plan::plan_install(&resolve_instance_name(name, instance)?, network).await
+}
+
+pub async fn start_package(name: &str, instance: Option<&str>) -> Result<()> {
+    lifecycle::start_package(&resolve_instance_name(name, instance)?).await
+}
+
+pub async fn stop_package(name: &str, instance: Option<&str>) -> Result<()> {
+    lifecycle::stop_package(&resolve_instance_name(name, instance)?).await
+}
+
+pub async fn delete_package(
+    name: &str,
+    instance: Option<&str>,
+    include_images: bool,
+) -> Result<()> {
+    lifecycle::delete_package(&resolve_instance_name(name, instance)?, include_images).await
+}
+
+/// Describes what [`delete_package`] would change without changing anything.
+pub async fn plan_delete_package(
+    name: &str,
+    instance: Option<&str>,
+    include_images: bool,
+) -> Result<Plan> {
+    plan::plan_delete(&resolve_instance_name(name, instance)?, include_images).await
+}
+
+pub async fn upgrade_package(
+    name: &str,
+    instance: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<ContainerUpgrade>> {
+    upgrade::upgrade_package(&resolve_instance_name(name, instance)?, to).await
 }
 
-pub async fn start_package(name: &str) -> Result<()> {
-    lifecycle::start_package(name).await
+pub async fn update_package_config(
+    package_name: &str,
+    instance: Option<&str>,
+    config: PackageConfig,
+) -> Result<()> {
+    lifecycle::update_package_config(&resolve_instance_name(package_name, instance)?, config).await
 }
 
-pub async fn stop_package(name: &str) -> Result<()> {
-    lifecycle::stop_package(name).await
+/// Describes what [`update_package_config`] would change without changing anything.
+pub async fn plan_update_package_config(
+    package_name: &str,
+    instance: Option<&str>,
+    config: &PackageConfig,
+) -> Result<Plan> {
+    plan::plan_update_config(&resolve_instance_name(package_name, instance)?, config).await
 }
 
-pub async fn delete_package(name: &str, include_images: bool) -> Result<()> {
-    lifecycle::delete_package(name, include_images).await
+/// Lists Kittynode containers, volumes and networks that no installed package refers to.
+pub async fn find_orphaned_resources() -> Result<Vec<OrphanedResource>> {
+    gc::find_orphaned_resources().await
 }
 
-pub async fn update_package_config(package_name: &str, config: PackageConfig) -> Result<()> {
-    lifecycle::update_package_config(package_name, config).await
+/// Removes the containers and networks [`find_orphaned_resources`] reports, along with the
+/// orphaned volumes listed in `volumes`, and returns what was removed.
+///
+/// Volumes are only removed when listed. Volumes marked protected are never removed.
+pub async fn remove_orphaned_resources(volumes: &[String]) -> Result<Vec<OrphanedResource>> {
+    gc::remove_orphaned_resources(volumes).await
 }
@@ -1,24 +1,135 @@
-use crate::ethereum::Ethereum;
-use crate::packages::{Package, PackageDefinition};
+use crate::docker::{MANAGED_LABEL, MANIFEST_VERSION_LABEL, NETWORK_LABEL, PACKAGE_LABEL};
+use crate::ethereum::{self, Ethereum};
+use crate::packages::manifest::{load_manifest_packages, preview_manifest_package};
+use crate::packages::{
+    ConfigField, Package, PackageConfig, PackageConfigStore, PackageDefinition, list_instances,
+    split_instance_name,
+};
+use crate::paths::kittynode_path;
 use eyre::{Result, eyre};
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
+use tracing::warn;
 
 pub(crate) fn get_package_catalog() -> Result<HashMap<String, Package>> {
+    let base_dir = kittynode_path()?;
     let mut packages = HashMap::new();
     packages.insert(Ethereum::NAME.to_string(), Ethereum::get_package()?);
+    for instance in list_instances(&base_dir, Ethereum::NAME)? {
+        let package = ethereum::instance_package(Some(&instance))?;
+        packages.insert(package.name().to_string(), package);
+    }
+    add_manifest_packages(&mut packages, &base_dir)?;
+    for package in packages.values_mut() {
+        let config = PackageConfigStore::load_from(&base_dir, package.name())?;
+        label_containers(package, &config);
+    }
     Ok(packages)
 }
 
+/// Only the Ethereum package can run as several named instances.
+pub(crate) fn validate_instance_support(package_name: &str) -> Result<()> {
+    if package_name == Ethereum::NAME {
+        Ok(())
+    } else {
+        Err(eyre!(
+            "Package '{package_name}' does not support named instances"
+        ))
+    }
+}
+
+/// Returns the labels that tie a Docker resource to the package that created it.
+pub(crate) fn ownership_labels(
+    package_name: &str,
+    config: &PackageConfig,
+) -> HashMap<String, String> {
+    let mut labels = HashMap::from([
+        (MANAGED_LABEL.to_string(), "true".to_string()),
+        (PACKAGE_LABEL.to_string(), package_name.to_string()),
+        (
+            MANIFEST_VERSION_LABEL.to_string(),
+            env!("CARGO_PKG_VERSION").to_string(),
+        ),
+    ]);
+    if let Some(network) = config
+        .values
+        .get("network")
+        .filter(|value| !value.is_empty())
+    {
+        labels.insert(NETWORK_LABEL.to_string(), network.clone());
+    }
+    labels
+}
+
+/// Adds ownership labels to every container, replacing any a manifest set under the same keys.
+fn label_containers(package: &mut Package, config: &PackageConfig) {
+    let labels = ownership_labels(package.name(), config);
+    for container in &mut package.containers {
+        container.labels.extend(labels.clone());
+    }
+}
+
+/// Adds packages described by `manifest.toml` files, never letting them shadow built-in packages.
+fn add_manifest_packages(packages: &mut HashMap<String, Package>, base_dir: &Path) -> Result<()> {
+    for package in load_manifest_packages(base_dir)? {
+        if packages.contains_key(package.name()) {
+            warn!(
+                "Skipping package manifest for '{}' because a built-in package uses that name",
+                package.name()
+            );
+            continue;
+        }
+        packages.insert(package.name().to_string(), package);
+    }
+    Ok(())
+}
+
+/// Looks up a package by instance name, building named Ethereum instances on demand so that a
+/// new instance can be installed before it has any saved configuration.
 pub(crate) fn get_package_by_name(name: &str) -> Result<Package> {
+    if let (package_name, Some(instance)) = split_instance_name(name) {
+        validate_instance_support(package_name)?;
+        let base_dir = kittynode_path()?;
+        let mut package = ethereum::instance_package(Some(instance))?;
+        let config = PackageConfigStore::load_from(&base_dir, name)?;
+        label_containers(&mut package, &config);
+        return Ok(package);
+    }
+
     let mut catalog = get_package_catalog()?;
     catalog
         .remove(name)
         .ok_or_else(|| eyre!("Package '{}' not found", name))
 }
 
+/// Returns a package's config schema without requiring its saved config to build containers.
+pub(crate) fn get_package_config_schema(name: &str) -> Result<Vec<ConfigField>> {
+    if split_instance_name(name).0 == Ethereum::NAME {
+        return Ok(Ethereum::config_schema());
+    }
+    Ok(get_package_by_name(name)?.config_schema)
+}
+
+/// Builds a package as it would look with `config`, without writing anything to disk.
+pub(crate) fn preview_package(name: &str, config: &PackageConfig) -> Result<Package> {
+    let mut package = if let (Ethereum::NAME, instance) = split_instance_name(name) {
+        ethereum::preview_package(config, instance)?
+    } else {
+        preview_manifest_package(&kittynode_path()?, name, config)?
+            .ok_or_else(|| eyre!("Package '{}' not found", name))?
+    };
+    label_containers(&mut package, config);
+    Ok(package)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{get_package_by_name, get_package_catalog};
+    use super::{
+        add_manifest_packages, get_package_by_name, get_package_catalog, label_containers,
+    };
+    use crate::docker::{MANAGED_LABEL, NETWORK_LABEL, PACKAGE_LABEL};
+    use crate::packages::{Container, Package, PackageConfig, PackageConfigStore};
+    use std::{collections::HashMap, fs};
+    use tempfile::tempdir;
 
     #[test]
     fn get_package_by_name_is_case_sensitive_with_lowercase_canonical() {
@@ -28,4 +139,67 @@ mod tests {
         assert!(get_package_by_name("Ethereum").is_err());
         assert!(get_package_by_name("does-not-exist").is_err());
     }
+
+    #[test]
+    fn manifest_packages_cannot_shadow_builtin_packages() {
+        let temp_dir = tempdir().expect("tempdir");
+        let base_dir = temp_dir.path().join(".config").join("kittynode");
+        let manifest = r#"
+name = "ethereum"
+description = "Impostor"
+
+[[containers]]
+name = "kittynode-impostor"
+image = "example/impostor"
+"#;
+        let package_dir = PackageConfigStore::package_dir(&base_dir, "ethereum");
+        fs::create_dir_all(&package_dir).expect("create package dir");
+        fs::write(package_dir.join("manifest.toml"), manifest).expect("write manifest");
+
+        let mut packages = HashMap::new();
+        packages.insert(
+            "ethereum".to_string(),
+            Package {
+                name: "ethereum".to_string(),
+                description: "builtin".to_string(),
+                network_name: "test".to_string(),
+                containers: Vec::new(),
+                default_config: PackageConfig::default(),
+                config_schema: Vec::new(),
+                manifest_path: None,
+            },
+        );
+
+        add_manifest_packages(&mut packages, &base_dir).expect("manifests should load");
+        assert_eq!(packages["ethereum"].description(), "builtin");
+    }
+
+    #[test]
+    fn label_containers_marks_ownership_over_manifest_labels() {
+        let mut package = Package {
+            name: "grafana".to_string(),
+            description: "Dashboards".to_string(),
+            network_name: "kittynode-grafana-network".to_string(),
+            containers: vec![Container {
+                name: "kittynode-grafana".to_string(),
+                labels: HashMap::from([
+                    (PACKAGE_LABEL.to_string(), "ethereum".to_string()),
+                    ("org.example.team".to_string(), "ops".to_string()),
+                ]),
+                ..Default::default()
+            }],
+            default_config: PackageConfig::default(),
+            config_schema: Vec::new(),
+            manifest_path: None,
+        };
+        let mut config = PackageConfig::new();
+        config.values.insert("network".into(), "hoodi".into());
+
+        label_containers(&mut package, &config);
+        let labels = &package.containers[0].labels;
+        assert_eq!(labels[MANAGED_LABEL], "true");
+        assert_eq!(labels[PACKAGE_LABEL], "grafana");
+        assert_eq!(labels[NETWORK_LABEL], "hoodi");
+        assert_eq!(labels["org.example.team"], "ops");
+    }
 }
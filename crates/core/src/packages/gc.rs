@@ -0,0 +1,378 @@
+use crate::docker::{
+    MANAGED_LABEL, NETWORK_LABEL, PACKAGE_LABEL, RESOURCE_PREFIX, container_names,
+    get_docker_instance, remove_container,
+};
+use crate::ethereum::is_validator_data_volume;
+use crate::packages::catalog::get_package_catalog;
+use crate::packages::lifecycle::remove_network;
+use crate::packages::state::get_concrete_package;
+use crate::packages::{InstallStatus, Package};
+use bollard::{
+    Docker,
+    errors::Error as DockerError,
+    query_parameters::{ListContainersOptionsBuilder, ListNetworksOptions, ListVolumesOptions},
+};
+use eyre::{Context, Result, eyre};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+use tracing::info;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResourceKind {
+    Container,
+    Volume,
+    Network,
+}
+
+impl ResourceKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Container => "container",
+            Self::Volume => "volume",
+            Self::Network => "network",
+        }
+    }
+}
+
+/// A Kittynode Docker resource that no installed package refers to.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedResource {
+    pub kind: ResourceKind,
+    pub name: String,
+    /// The package recorded in the resource's labels; `None` for unlabeled legacy resources.
+    pub package: Option<String>,
+    pub network: Option<String>,
+    /// Set for volumes holding validator keys or slashing protection data, which are never removed.
+    pub protected: bool,
+}
+
+impl fmt::Display for OrphanedResource {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{} '{}'", self.kind.as_str(), self.name)?;
+        match (&self.package, &self.network) {
+            (Some(package), Some(network)) => {
+                write!(formatter, " (package {package}, network {network})")
+            }
+            (Some(package), None) => write!(formatter, " (package {package})"),
+            (None, Some(network)) => write!(formatter, " (network {network})"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// A resource found in Docker together with its labels.
+struct FoundResource {
+    kind: ResourceKind,
+    name: String,
+    labels: HashMap<String, String>,
+}
+
+pub(crate) async fn find_orphaned_resources() -> Result<Vec<OrphanedResource>> {
+    let docker = get_docker_instance().await?;
+    find_orphans_with(&docker).await
+}
+
+/// Removes orphaned containers and networks, and the orphaned volumes named in `volumes`, and
+/// returns what was removed.
+///
+/// Volumes hold chain and key data, so each one has to be named by the caller. Containers are
+/// removed before volumes and networks so that nothing is still in use.
+pub(crate) async fn remove_orphaned_resources(volumes: &[String]) -> Result<Vec<OrphanedResource>> {
+    let docker = get_docker_instance().await?;
+    let orphans = removable_orphans(find_orphans_with(&docker).await?, volumes)?;
+
+    for orphan in &orphans {
+        info!("Removing orphaned {orphan}...");
+        let result = match orphan.kind {
+            ResourceKind::Container => remove_container(&docker, &orphan.name).await,
+            ResourceKind::Volume => remove_volume(&docker, &orphan.name).await,
+            ResourceKind::Network => remove_network(&docker, &orphan.name).await,
+        };
+        result.wrap_err_with(|| format!("Failed to remove orphaned {orphan}"))?;
+    }
+
+    Ok(orphans)
+}
+
+/// Keeps the orphaned containers and networks and the orphaned volumes named in `volumes`.
+///
+/// Errors before anything is removed when a named volume is protected or isn't an orphan.
+fn removable_orphans(
+    orphans: Vec<OrphanedResource>,
+    volumes: &[String],
+) -> Result<Vec<OrphanedResource>> {
+    for volume in volumes {
+        let orphan = orphans
+            .iter()
+            .find(|orphan| orphan.kind == ResourceKind::Volume && orphan.name == *volume)
+            .ok_or_else(|| eyre!("Volume '{volume}' is not an orphaned Kittynode volume"))?;
+        if orphan.protected {
+            return Err(eyre!(
+                "Volume '{volume}' holds validator keys or slashing protection data and is never removed"
+            ));
+        }
+    }
+    Ok(orphans
+        .into_iter()
+        .filter(|orphan| orphan.kind != ResourceKind::Volume || volumes.contains(&orphan.name))
+        .collect())
+}
+
+async fn find_orphans_with(docker: &Docker) -> Result<Vec<OrphanedResource>> {
+    let mut packages = Vec::new();
+    for package in get_package_catalog()?.into_values() {
+        if get_concrete_package(&package).await?.install != InstallStatus::NotInstalled {
+            packages.push(package);
+        }
+    }
+    let referenced = referenced_resources(&packages);
+    let found = list_kittynode_resources(docker).await?;
+    Ok(orphans(found, &referenced))
+}
+
+/// Names every container, volume and network an installed package uses.
+fn referenced_resources(packages: &[Package]) -> HashSet<(ResourceKind, String)> {
+    let mut referenced = HashSet::new();
+    for package in packages {
+        if package.containers.is_empty() {
+            continue;
+        }
+        referenced.insert((ResourceKind::Network, package.network_name.clone()));
+        for container in &package.containers {
+            referenced.insert((ResourceKind::Container, container.name.clone()));
+            for binding in &container.volume_bindings {
+                referenced.insert((ResourceKind::Volume, binding.source.clone()));
+            }
+        }
+    }
+    referenced
+}
+
+/// Keeps resources Kittynode manages, by label or by legacy name prefix, that nothing refers to.
+fn orphans(
+    found: Vec<FoundResource>,
+    referenced: &HashSet<(ResourceKind, String)>,
+) -> Vec<OrphanedResource> {
+    let mut orphans: Vec<OrphanedResource> = found
+        .into_iter()
+        .filter(|resource| {
+            resource.labels.contains_key(MANAGED_LABEL)
+                || resource.name.starts_with(RESOURCE_PREFIX)
+        })
+        .filter(|resource| !referenced.contains(&(resource.kind, resource.name.clone())))
+        .map(|resource| OrphanedResource {
+            kind: resource.kind,
+            package: resource.labels.get(PACKAGE_LABEL).cloned(),
+            network: resource.labels.get(NETWORK_LABEL).cloned(),
+            protected: resource.kind == ResourceKind::Volume
+                && is_validator_data_volume(&resource.name),
+            name: resource.name,
+        })
+        .collect();
+    orphans.sort_by(|left, right| (left.kind, &left.name).cmp(&(right.kind, &right.name)));
+    orphans
+}
+
+async fn list_kittynode_resources(docker: &Docker) -> Result<Vec<FoundResource>> {
+    let mut found = Vec::new();
+
+    let options = ListContainersOptionsBuilder::default().all(true).build();
+    for container in docker.list_containers(Some(options)).await? {
+        let Some(name) = container_names(&container).next() else {
+            continue;
+        };
+        found.push(FoundResource {
+            kind: ResourceKind::Container,
+            name: name.to_string(),
+            labels: container.labels.clone().unwrap_or_default(),
+        });
+    }
+
+    let volumes = docker
+        .list_volumes(None::<ListVolumesOptions>)
+        .await?
+        .volumes
+        .unwrap_or_default();
+    for volume in volumes {
+        found.push(FoundResource {
+            kind: ResourceKind::Volume,
+            name: volume.name,
+            labels: volume.labels,
+        });
+    }
+
+    for network in docker.list_networks(None::<ListNetworksOptions>).await? {
+        let Some(name) = network.name else {
+            continue;
+        };
+        found.push(FoundResource {
+            kind: ResourceKind::Network,
+            name,
+            labels: network.labels.unwrap_or_default(),
+        });
+    }
+
+    Ok(found)
+}
+
+async fn remove_volume(docker: &Docker, name: &str) -> Result<()> {
+    match docker
+        .remove_volume(name, None::<bollard::query_parameters::RemoveVolumeOptions>)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(DockerError::DockerResponseServerError {
+            status_code: 404, ..
+        }) => Ok(()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FoundResource, ResourceKind, orphans, referenced_resources, removable_orphans};
+    use crate::docker::{MANAGED_LABEL, NETWORK_LABEL, PACKAGE_LABEL};
+    use crate::packages::{Binding, Container, Package, PackageConfig};
+
+    fn found(kind: ResourceKind, name: &str, labels: &[(&str, &str)]) -> FoundResource {
+        FoundResource {
+            kind,
+            name: name.to_string(),
+            labels: labels
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    fn installed_package() -> Package {
+        Package {
+            name: "ethereum".to_string(),
+            description: "Ethereum".to_string(),
+            network_name: "kittynode-ethereum-network".to_string(),
+            containers: vec![Container {
+                name: "kittynode-reth-node".to_string(),
+                volume_bindings: vec![Binding {
+                    source: "kittynode-rethdata".to_string(),
+                    destination: "/root/.local/share/reth".to_string(),
+                    options: None,
+                }],
+                ..Default::default()
+            }],
+            default_config: PackageConfig::default(),
+            config_schema: Vec::new(),
+            manifest_path: None,
+        }
+    }
+
+    #[test]
+    fn orphans_skip_referenced_and_foreign_resources() {
+        let referenced = referenced_resources(&[installed_package()]);
+        let labels = [
+            (MANAGED_LABEL, "true"),
+            (PACKAGE_LABEL, "ethereum"),
+            (NETWORK_LABEL, "hoodi"),
+        ];
+        let found = vec![
+            found(ResourceKind::Container, "kittynode-reth-node", &labels),
+            found(ResourceKind::Volume, "kittynode-rethdata", &labels),
+            found(ResourceKind::Volume, "kittynode-rethdata-sepolia", &labels),
+            found(ResourceKind::Network, "kittynode-ethereum-network", &[]),
+            found(ResourceKind::Network, "kittynode-grafana-network", &[]),
+            found(ResourceKind::Volume, "postgres-data", &[]),
+            found(ResourceKind::Network, "bridge", &[]),
+        ];
+
+        let orphans = orphans(found, &referenced);
+        let names: Vec<_> = orphans
+            .iter()
+            .map(|orphan| (orphan.kind, orphan.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (ResourceKind::Volume, "kittynode-rethdata-sepolia"),
+                (ResourceKind::Network, "kittynode-grafana-network"),
+            ]
+        );
+        assert_eq!(orphans[0].network.as_deref(), Some("hoodi"));
+        assert_eq!(
+            orphans[0].to_string(),
+            "volume 'kittynode-rethdata-sepolia' (package ethereum, network hoodi)"
+        );
+        assert_eq!(
+            orphans[1].to_string(),
+            "network 'kittynode-grafana-network'"
+        );
+    }
+
+    #[test]
+    fn labeled_resources_without_prefix_are_still_managed() {
+        let found = vec![found(
+            ResourceKind::Volume,
+            "renamed-data",
+            &[(MANAGED_LABEL, "true")],
+        )];
+        let orphans = orphans(found, &referenced_resources(&[]));
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].package, None);
+    }
+
+    #[test]
+    fn only_named_unprotected_volumes_are_removed() {
+        let managed = [(MANAGED_LABEL, "true")];
+        let found = vec![
+            found(
+                ResourceKind::Container,
+                "kittynode-teku-validator",
+                &managed,
+            ),
+            found(ResourceKind::Volume, "kittynode-rethdata-sepolia", &managed),
+            found(ResourceKind::Volume, "kittynode-besu-data", &managed),
+            found(ResourceKind::Volume, "kittynode-lighthouse-data", &managed),
+            found(
+                ResourceKind::Volume,
+                "kittynode-teku-validator-data-solo",
+                &managed,
+            ),
+        ];
+        let orphans = orphans(found, &referenced_resources(&[]));
+        let protected: Vec<_> = orphans
+            .iter()
+            .filter(|orphan| orphan.protected)
+            .map(|orphan| orphan.name.as_str())
+            .collect();
+        assert_eq!(
+            protected,
+            vec![
+                "kittynode-lighthouse-data",
+                "kittynode-teku-validator-data-solo"
+            ]
+        );
+
+        let removable = removable_orphans(orphans.clone(), &[]).unwrap();
+        assert_eq!(removable.len(), 1);
+        assert_eq!(removable[0].kind, ResourceKind::Container);
+
+        let confirmed = vec!["kittynode-besu-data".to_string()];
+        let removable = removable_orphans(orphans.clone(), &confirmed).unwrap();
+        let names: Vec<_> = removable
+            .iter()
+            .map(|orphan| orphan.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["kittynode-teku-validator", "kittynode-besu-data"]
+        );
+
+        let protected = vec!["kittynode-lighthouse-data".to_string()];
+        assert!(removable_orphans(orphans.clone(), &protected).is_err());
+        let unknown = vec!["postgres-data".to_string()];
+        assert!(removable_orphans(orphans, &unknown).is_err());
+    }
+}
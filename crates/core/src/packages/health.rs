@@ -0,0 +1,312 @@
+use crate::docker::{container_is_running, exec_in_container, find_container};
+use crate::packages::{Container, ContainerHealth, HealthProbe};
+use bollard::Docker;
+use eyre::{Result, eyre};
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+use tokio::{net::TcpStream, task, time};
+use tracing::info;
+
+const PROBE_INTERVAL: Duration = Duration::from_secs(2);
+const PROBE_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Orders containers so that every container comes after the containers it depends on.
+///
+/// Containers without dependency edges keep their relative order. Unknown dependencies and cycles
+/// are rejected.
+pub(crate) fn startup_order(containers: &[Container]) -> Result<Vec<&Container>> {
+    let nodes: Vec<(&str, &[String])> = containers
+        .iter()
+        .map(|container| (container.name.as_str(), container.depends_on.as_slice()))
+        .collect();
+    Ok(dependency_order(&nodes)?
+        .into_iter()
+        .map(|index| &containers[index])
+        .collect())
+}
+
+/// Returns the indices of `nodes` (name and dependency names) in dependency order.
+pub(crate) fn dependency_order(nodes: &[(&str, &[String])]) -> Result<Vec<usize>> {
+    for (name, depends_on) in nodes {
+        for dependency in *depends_on {
+            if dependency.as_str() == *name {
+                return Err(eyre!("Container '{name}' cannot depend on itself"));
+            }
+            if !nodes.iter().any(|(other, _)| *other == dependency.as_str()) {
+                return Err(eyre!(
+                    "Container '{name}' depends on unknown container '{dependency}'"
+                ));
+            }
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(nodes.len());
+    let mut placed: HashSet<&str> = HashSet::new();
+    while ordered.len() < nodes.len() {
+        let next = nodes.iter().enumerate().find(|(_, (name, depends_on))| {
+            !placed.contains(name)
+                && depends_on
+                    .iter()
+                    .all(|dependency| placed.contains(dependency.as_str()))
+        });
+        let Some((index, (name, _))) = next else {
+            let blocked: Vec<&str> = nodes
+                .iter()
+                .map(|(name, _)| *name)
+                .filter(|name| !placed.contains(name))
+                .collect();
+            return Err(eyre!(
+                "Container dependencies form a cycle between: {}",
+                blocked.join(", ")
+            ));
+        };
+        placed.insert(*name);
+        ordered.push(index);
+    }
+
+    Ok(ordered)
+}
+
+/// Waits for a started container's health probe to pass, failing if it stops or times out.
+///
+/// Containers without a health check are considered ready as soon as they are started.
+pub(crate) async fn wait_until_healthy(docker: &Docker, container: &Container) -> Result<()> {
+    let Some(check) = &container.health_check else {
+        return Ok(());
+    };
+    ensure_probe_is_published(container, &check.probe)?;
+
+    info!(
+        "Waiting for container '{}' to become healthy...",
+        container.name
+    );
+    let timeout = Duration::from_secs(check.timeout_secs);
+    let started = Instant::now();
+    loop {
+        let summaries = find_container(docker, &container.name).await?;
+        if !summaries.iter().any(container_is_running) {
+            return Err(eyre!(
+                "Container '{}' stopped before becoming healthy",
+                container.name
+            ));
+        }
+
+        if probe_passes(docker, container, &check.probe).await {
+            info!("Container '{}' is healthy", container.name);
+            return Ok(());
+        }
+
+        if started.elapsed() >= timeout {
+            return Err(eyre!(
+                "Container '{}' did not become healthy within {}s",
+                container.name,
+                check.timeout_secs
+            ));
+        }
+        time::sleep(PROBE_INTERVAL).await;
+    }
+}
+
+/// Runs a container's health probe once.
+pub(crate) async fn check_container_health(
+    docker: &Docker,
+    container: &Container,
+    running: bool,
+) -> ContainerHealth {
+    match &container.health_check {
+        None => ContainerHealth::NotChecked,
+        Some(_) if !running => ContainerHealth::Unhealthy,
+        Some(check) => {
+            if probe_passes(docker, container, &check.probe).await {
+                ContainerHealth::Healthy
+            } else {
+                ContainerHealth::Unhealthy
+            }
+        }
+    }
+}
+
+async fn probe_passes(docker: &Docker, container: &Container, probe: &HealthProbe) -> bool {
+    match probe {
+        HealthProbe::Exec { command } => exec_in_container(docker, &container.name, command)
+            .await
+            .is_ok_and(|code| code == 0),
+        HealthProbe::Tcp { port } => {
+            let Some(address) = published_address(container, *port) else {
+                return false;
+            };
+            matches!(
+                time::timeout(PROBE_ATTEMPT_TIMEOUT, TcpStream::connect(address)).await,
+                Ok(Ok(_))
+            )
+        }
+        HealthProbe::Http { port, path } => {
+            let Some(address) = published_address(container, *port) else {
+                return false;
+            };
+            let url = format!("http://{address}{path}");
+            // ureq treats 4xx and 5xx responses as errors, so any Ok response is healthy.
+            task::spawn_blocking(move || {
+                ureq::AgentBuilder::new()
+                    .timeout(PROBE_ATTEMPT_TIMEOUT)
+                    .build()
+                    .get(&url)
+                    .call()
+                    .is_ok()
+            })
+            .await
+            .unwrap_or(false)
+        }
+    }
+}
+
+/// TCP and HTTP probes run from the host, so they can only reach ports published on it. Container
+/// addresses on the package network are not reachable from Docker Desktop hosts.
+fn ensure_probe_is_published(container: &Container, probe: &HealthProbe) -> Result<()> {
+    match probe {
+        HealthProbe::Exec { .. } => Ok(()),
+        HealthProbe::Tcp { port } | HealthProbe::Http { port, .. } => {
+            if published_address(container, *port).is_some() {
+                Ok(())
+            } else {
+                Err(eyre!(
+                    "Health probe of container '{}' targets port {port}, which is not published on the host",
+                    container.name
+                ))
+            }
+        }
+    }
+}
+
+/// Resolves where the host can reach a published container port.
+fn published_address(container: &Container, port: u16) -> Option<String> {
+    let binding = container
+        .port_bindings
+        .get(&format!("{port}/tcp"))?
+        .iter()
+        .find(|binding| binding.host_port.is_some())?;
+    let host_port = binding.host_port.as_deref()?;
+    let host_ip = match binding.host_ip.as_deref() {
+        None | Some("") | Some("0.0.0.0") => "127.0.0.1",
+        Some("::") => "[::1]",
+        Some(ip) if ip.contains(':') => return Some(format!("[{ip}]:{host_port}")),
+        Some(ip) => ip,
+    };
+    Some(format!("{host_ip}:{host_port}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dependency_order, ensure_probe_is_published, published_address, startup_order};
+    use crate::packages::{Container, HealthProbe, PortBinding};
+    use std::collections::HashMap;
+
+    fn container(name: &str, depends_on: &[&str]) -> Container {
+        Container {
+            name: name.to_string(),
+            image: "example/image".to_string(),
+            tag: "latest".to_string(),
+            depends_on: depends_on.iter().map(|name| name.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn startup_order_places_dependencies_first() {
+        let containers = vec![
+            container("validator", &["beacon"]),
+            container("beacon", &["execution"]),
+            container("execution", &[]),
+        ];
+        let names: Vec<&str> = startup_order(&containers)
+            .expect("order")
+            .iter()
+            .map(|container| container.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["execution", "beacon", "validator"]);
+    }
+
+    #[test]
+    fn startup_order_keeps_independent_containers_in_place() {
+        let containers = vec![
+            container("a", &[]),
+            container("b", &[]),
+            container("c", &[]),
+        ];
+        let names: Vec<&str> = startup_order(&containers)
+            .expect("order")
+            .iter()
+            .map(|container| container.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn dependency_order_rejects_cycles_and_unknown_names() {
+        let a = vec!["b".to_string()];
+        let b = vec!["a".to_string()];
+        let error =
+            dependency_order(&[("a", a.as_slice()), ("b", b.as_slice())]).expect_err("cycle");
+        assert!(error.to_string().contains("cycle"), "{error}");
+
+        let missing = vec!["ghost".to_string()];
+        assert!(dependency_order(&[("a", missing.as_slice())]).is_err());
+
+        let itself = vec!["a".to_string()];
+        assert!(dependency_order(&[("a", itself.as_slice())]).is_err());
+    }
+
+    #[test]
+    fn published_address_uses_loopback_for_wildcard_bindings() {
+        let mut container = container("beacon", &[]);
+        container.port_bindings = HashMap::from([
+            (
+                "5052/tcp".to_string(),
+                vec![PortBinding {
+                    host_ip: Some("0.0.0.0".to_string()),
+                    host_port: Some("15052".to_string()),
+                }],
+            ),
+            (
+                "9000/tcp".to_string(),
+                vec![PortBinding {
+                    host_ip: Some("192.168.1.5".to_string()),
+                    host_port: Some("9000".to_string()),
+                }],
+            ),
+        ]);
+
+        assert_eq!(
+            published_address(&container, 5052).as_deref(),
+            Some("127.0.0.1:15052")
+        );
+        assert_eq!(
+            published_address(&container, 9000).as_deref(),
+            Some("192.168.1.5:9000")
+        );
+        assert_eq!(published_address(&container, 8551), None);
+    }
+
+    #[test]
+    fn host_probes_must_target_published_ports() {
+        let mut container = container("execution", &[]);
+        container.port_bindings = HashMap::from([(
+            "8545/tcp".to_string(),
+            vec![PortBinding {
+                host_ip: Some("127.0.0.1".to_string()),
+                host_port: Some("8545".to_string()),
+            }],
+        )]);
+
+        assert!(ensure_probe_is_published(&container, &HealthProbe::Tcp { port: 8545 }).is_ok());
+        let error =
+            ensure_probe_is_published(&container, &HealthProbe::Tcp { port: 8551 }).unwrap_err();
+        assert!(error.to_string().contains("not published"), "{error}");
+        let exec = HealthProbe::Exec {
+            command: vec!["true".to_string()],
+        };
+        assert!(ensure_probe_is_published(&container, &exec).is_ok());
+    }
+}
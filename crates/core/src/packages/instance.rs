@@ -0,0 +1,149 @@
+use eyre::{Result, eyre};
+use std::{fs, path::Path};
+
+/// Separates the package name from the instance id in instance names such as `ethereum@hoodi`.
+pub const INSTANCE_SEPARATOR: char = '@';
+const MAX_INSTANCE_ID_LEN: usize = 32;
+
+/// Returns the name an instance is stored and looked up under.
+///
+/// The default instance keeps the plain package name so that existing installs are unaffected.
+pub fn instance_name(package_name: &str, instance: Option<&str>) -> String {
+    match instance {
+        Some(instance) => format!("{package_name}{INSTANCE_SEPARATOR}{instance}"),
+        None => package_name.to_string(),
+    }
+}
+
+/// Splits an instance name into the package name and the instance id, if any.
+pub(crate) fn split_instance_name(name: &str) -> (&str, Option<&str>) {
+    match name.split_once(INSTANCE_SEPARATOR) {
+        Some((package_name, instance)) => (package_name, Some(instance)),
+        None => (name, None),
+    }
+}
+
+/// Validates `instance` and returns the instance name for `package_name`.
+pub(crate) fn resolve_instance_name(package_name: &str, instance: Option<&str>) -> Result<String> {
+    if let Some(instance) = instance {
+        validate_instance_id(instance)?;
+    }
+    Ok(instance_name(package_name, instance))
+}
+
+/// Instance ids end up in Docker resource names, so they are limited to lowercase letters, digits
+/// and inner dashes.
+pub(crate) fn validate_instance_id(instance: &str) -> Result<()> {
+    let valid = !instance.is_empty()
+        && instance.len() <= MAX_INSTANCE_ID_LEN
+        && instance
+            .chars()
+            .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-')
+        && !instance.starts_with('-')
+        && !instance.ends_with('-');
+    if valid {
+        Ok(())
+    } else {
+        Err(eyre!(
+            "Invalid instance id '{instance}': use up to {MAX_INSTANCE_ID_LEN} lowercase letters, digits and dashes"
+        ))
+    }
+}
+
+/// Suffixes a Docker resource name with the instance id.
+pub(crate) fn scoped_resource_name(name: &str, instance: Option<&str>) -> String {
+    match instance {
+        Some(instance) => format!("{name}-{instance}"),
+        None => name.to_string(),
+    }
+}
+
+/// Lists the ids of the named instances of `package_name` that have a package directory.
+pub(crate) fn list_instances(base_dir: &Path, package_name: &str) -> Result<Vec<String>> {
+    let packages_dir = base_dir.join("packages");
+    let entries = match fs::read_dir(&packages_dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut instances = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(name) = file_name.to_str() else {
+            continue;
+        };
+        if let (found, Some(instance)) = split_instance_name(name)
+            && found == package_name
+            && validate_instance_id(instance).is_ok()
+            && entry.file_type()?.is_dir()
+        {
+            instances.push(instance.to_string());
+        }
+    }
+    instances.sort();
+    Ok(instances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        instance_name, list_instances, resolve_instance_name, scoped_resource_name,
+        split_instance_name,
+    };
+    use crate::packages::PackageConfigStore;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn instance_names_round_trip() {
+        assert_eq!(instance_name("ethereum", None), "ethereum");
+        assert_eq!(instance_name("ethereum", Some("hoodi")), "ethereum@hoodi");
+        assert_eq!(
+            split_instance_name("ethereum@hoodi"),
+            ("ethereum", Some("hoodi"))
+        );
+        assert_eq!(split_instance_name("ethereum"), ("ethereum", None));
+        assert_eq!(
+            scoped_resource_name("kittynode-reth-node", Some("hoodi")),
+            "kittynode-reth-node-hoodi"
+        );
+    }
+
+    #[test]
+    fn resolve_instance_name_rejects_ids_docker_cannot_use() {
+        assert!(resolve_instance_name("ethereum", Some("test-2")).is_ok());
+        for invalid in ["", "Hoodi", "a_b", "-x", "x-", "a/b", &"a".repeat(33)] {
+            assert!(
+                resolve_instance_name("ethereum", Some(invalid)).is_err(),
+                "{invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn list_instances_finds_instance_directories() {
+        let temp_dir = tempdir().expect("tempdir");
+        let base_dir = temp_dir.path().join(".config").join("kittynode");
+        for name in [
+            "ethereum",
+            "ethereum@hoodi",
+            "ethereum@ephemery",
+            "grafana@x",
+        ] {
+            fs::create_dir_all(PackageConfigStore::package_dir(&base_dir, name))
+                .expect("create package dir");
+        }
+
+        assert_eq!(
+            list_instances(&base_dir, "ethereum").expect("instances should list"),
+            vec!["ephemery", "hoodi"]
+        );
+        assert!(
+            list_instances(&temp_dir.path().join("missing"), "ethereum")
+                .expect("missing directory is empty")
+                .is_empty()
+        );
+    }
+}
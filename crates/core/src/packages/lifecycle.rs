@@ -1,17 +1,29 @@
 use crate::docker::{
-    create_or_recreate_network, get_docker_instance, pull_and_start_container, remove_container,
-    start_named_container, stop_named_container,
+    PullProgress, create_or_recreate_network, get_docker_instance, log_pull_progress,
+    pull_and_start_container, remove_container, start_named_container, stop_named_container,
 };
-use crate::ethereum::{self, EPHEMERY_NETWORK_NAME, Ethereum};
-use crate::packages::catalog::get_package_by_name;
+use crate::ethereum::{self, Ethereum};
+use crate::packages::catalog::{
+    get_package_by_name, get_package_config_schema, ownership_labels, validate_instance_support,
+};
+use crate::packages::health::{startup_order, wait_until_healthy};
+use crate::packages::ports::ensure_ports_available;
+use crate::packages::schema::validate_config_update;
 use crate::packages::state::get_concrete_package;
+use crate::packages::transaction::InstallTransaction;
 use crate::packages::{
     InstallStatus, Package, PackageConfig, PackageConfigStore, PackageDefinition,
+    split_instance_name,
 };
 use crate::paths::{generate_jwt_secret, kittynode_path};
 use bollard::errors::Error as DockerError;
 use eyre::{Context, Result, eyre};
-use std::{collections::HashSet, fs, io::ErrorKind, path::Path};
+use std::{
+    collections::HashSet,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
 use tracing::{error, info, warn};
 
 pub(crate) async fn install_package(name: &str) -> Result<()> {
@@ -19,11 +31,43 @@ pub(crate) async fn install_package(name: &str) -> Result<()> {
 }
 
 pub(crate) async fn install_package_with_network(name: &str, network: Option<&str>) -> Result<()> {
+    install_package_with_progress(name, network, &mut log_pull_progress).await
+}
+
+pub(crate) async fn install_package_with_progress(
+    name: &str,
+    network: Option<&str>,
+    on_progress: &mut (dyn FnMut(PullProgress) + Send),
+) -> Result<()> {
+    if let (package_name, Some(_)) = split_instance_name(name) {
+        validate_instance_support(package_name)?;
+    }
     if let Some(network) = network {
         persist_selected_network(name, network)?;
     }
+    if let (Ethereum::NAME, Some(instance)) = split_instance_name(name) {
+        let base_dir = kittynode_path()?;
+        if PackageConfigStore::config_file_path(&base_dir, name).exists() {
+            ethereum::assign_instance_ports(&base_dir, instance)?;
+        }
+    }
 
-    let package = get_package_by_name(name)?;
+    let mut transaction = InstallTransaction::begin(name)?;
+    match run_install(name, &mut transaction, on_progress).await {
+        Ok(()) => Ok(()),
+        Err(error) => Err(transaction.roll_back(error).await),
+    }
+}
+
+async fn run_install(
+    name: &str,
+    transaction: &mut InstallTransaction,
+    on_progress: &mut (dyn FnMut(PullProgress) + Send),
+) -> Result<()> {
+    transaction.step("resolving the package definition");
+    let package = get_package_by_name(name);
+    transaction.record_generated_files();
+    let package = package?;
     let state = get_concrete_package(&package).await?;
 
     match state.install {
@@ -36,6 +80,7 @@ pub(crate) async fn install_package_with_network(name: &str, network: Option<&st
             warn!(
                 "Package '{name}' is partially installed ({note}). Cleaning up before reinstalling"
             );
+            transaction.step("cleaning up the partial installation");
             delete_concrete_package(&package, false, false)
                 .await
                 .wrap_err_with(|| format!("Failed to clean up partial installation for {name}"))?;
@@ -43,8 +88,10 @@ pub(crate) async fn install_package_with_network(name: &str, network: Option<&st
         InstallStatus::NotInstalled => {}
     }
 
+    transaction.step("generating the JWT secret");
     ensure_jwt_secret_if_needed(name, &package)?;
-    install_concrete_package(&package).await?;
+    transaction.record_generated_files();
+    install_concrete_package(&package, transaction, on_progress).await?;
     info!("Package '{name}' installed successfully");
     Ok(())
 }
@@ -71,22 +118,9 @@ pub(crate) async fn delete_package(name: &str, include_images: bool) -> Result<(
 }
 
 pub(crate) async fn update_package_config(package_name: &str, config: PackageConfig) -> Result<()> {
-    let pre_update_package = match get_package_by_name(package_name) {
-        Ok(package) => Some(package),
-        Err(error) => {
-            let message = error.to_string();
-            if message.contains("Unsupported Ethereum network") {
-                None
-            } else {
-                return Err(error);
-            }
-        }
-    };
+    let merged = merge_config_update(package_name, &config)?;
+    let pre_update_package = ignore_unsupported_network(get_package_by_name(package_name))?;
 
-    let mut merged = PackageConfigStore::load(package_name)?;
-    for (key, value) in config.values {
-        merged.values.insert(key, value);
-    }
     PackageConfigStore::save(package_name, &merged)?;
 
     if let Some(package) = &pre_update_package {
@@ -100,17 +134,58 @@ pub(crate) async fn update_package_config(package_name: &str, config: PackageCon
     install_package(package_name).await
 }
 
-async fn install_concrete_package(package: &Package) -> Result<()> {
+/// Merges `config` into the saved configuration and validates the result against the schema.
+pub(crate) fn merge_config_update(
+    package_name: &str,
+    config: &PackageConfig,
+) -> Result<PackageConfig> {
+    let schema = get_package_config_schema(package_name)?;
+    let mut merged = PackageConfigStore::load(package_name)?;
+    for (key, value) in &config.values {
+        merged.values.insert(key.clone(), value.clone());
+    }
+    validate_config_update(package_name, &schema, config, &merged)?;
+    Ok(merged)
+}
+
+/// Treats a package whose saved network is no longer supported as having nothing to tear down.
+pub(crate) fn ignore_unsupported_network(package: Result<Package>) -> Result<Option<Package>> {
+    match package {
+        Ok(package) => Ok(Some(package)),
+        Err(error) if error.to_string().contains("Unsupported Ethereum network") => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+async fn install_concrete_package(
+    package: &Package,
+    transaction: &mut InstallTransaction,
+    on_progress: &mut (dyn FnMut(PullProgress) + Send),
+) -> Result<()> {
+    transaction.step("checking that the package can be installed");
     validate_package_installable(package)?;
     let docker = get_docker_instance().await?;
-    let containers = package.containers.clone();
+    let containers = startup_order(&package.containers)?;
+
+    transaction.step("checking that host ports are free");
+    ensure_ports_available(&docker, package).await?;
 
     info!("Creating network '{}'...", package.network_name);
-    create_or_recreate_network(&docker, &package.network_name).await?;
+    transaction.step(format!("creating network '{}'", package.network_name));
+    transaction.record_network(&package.network_name);
+    let labels = ownership_labels(&package.name, &PackageConfigStore::load(&package.name)?);
+    create_or_recreate_network(&docker, &package.network_name, &labels).await?;
 
-    for container in &containers {
+    for container in containers {
         info!("Starting container '{}'...", container.name);
-        pull_and_start_container(&docker, container, &package.network_name).await?;
+        transaction.step(format!("starting container '{}'", container.name));
+        transaction.record_container(&container.name);
+        pull_and_start_container(&docker, container, &package.network_name, on_progress).await?;
+        transaction.step(format!(
+            "waiting for container '{}' to become healthy",
+            container.name
+        ));
+        wait_until_healthy(&docker, container).await?;
         info!("Container '{}' started successfully", container.name);
     }
 
@@ -122,12 +197,15 @@ fn validate_package_installable(package: &Package) -> Result<()> {
         return Ok(());
     }
 
-    if package.name == Ethereum::NAME {
-        let config = PackageConfigStore::load(Ethereum::NAME)?;
+    if let (Ethereum::NAME, instance) = split_instance_name(&package.name) {
+        let config = PackageConfigStore::load(&package.name)?;
         if !config.values.contains_key("network") {
             let network_choices = ethereum::supported_networks_display("|");
+            let instance_flag = instance
+                .map(|instance| format!(" --instance {instance}"))
+                .unwrap_or_default();
             return Err(eyre!(
-                "Network must be selected before installing Ethereum. Install using `kittynode package install {} --network <{}>`",
+                "Network must be selected before installing Ethereum. Install using `kittynode package install {} --network <{}>{instance_flag}`",
                 Ethereum::NAME,
                 network_choices
             ));
@@ -143,7 +221,8 @@ fn validate_package_installable(package: &Package) -> Result<()> {
 async fn stop_concrete_package(package: &Package) -> Result<()> {
     let docker = get_docker_instance().await?;
 
-    for container in &package.containers {
+    // Stop dependents before the containers they rely on.
+    for container in startup_order(&package.containers)?.into_iter().rev() {
         info!("Stopping container '{}'", container.name);
         stop_named_container(&docker, &container.name).await?;
         info!("Container '{}' stopped", container.name);
@@ -155,9 +234,10 @@ async fn stop_concrete_package(package: &Package) -> Result<()> {
 async fn start_concrete_package(package: &Package) -> Result<()> {
     let docker = get_docker_instance().await?;
 
-    for container in &package.containers {
+    for container in startup_order(&package.containers)? {
         info!("Starting container '{}'", container.name);
         start_named_container(&docker, &container.name).await?;
+        wait_until_healthy(&docker, container).await?;
         info!("Container '{}' started", container.name);
     }
 
@@ -193,14 +273,14 @@ pub(crate) async fn delete_concrete_package(
     Ok(())
 }
 
-struct CleanupPlan<'a> {
-    image_names: Vec<&'a str>,
-    volume_names: Vec<&'a str>,
-    file_paths: HashSet<&'a str>,
-    directory_paths: HashSet<&'a str>,
+pub(crate) struct CleanupPlan<'a> {
+    pub(crate) image_names: Vec<&'a str>,
+    pub(crate) volume_names: Vec<&'a str>,
+    pub(crate) file_paths: HashSet<&'a str>,
+    pub(crate) directory_paths: HashSet<&'a str>,
 }
 
-fn collect_cleanup_plan<'a>(
+pub(crate) fn collect_cleanup_plan<'a>(
     package: &'a Package,
     include_images: bool,
     purge_ephemery_cache: bool,
@@ -209,6 +289,11 @@ fn collect_cleanup_plan<'a>(
     let mut volume_names = Vec::new();
     let mut file_paths = HashSet::new();
     let mut directory_paths = HashSet::new();
+    // Another Ethereum instance may still mount the shared Ephemery cache.
+    let purge_ephemery_mount = purge_ephemery_cache
+        && !kittynode_path()
+            .and_then(|root| ethereum::ephemery_cache_in_use(&root, &package.name))
+            .unwrap_or(false);
 
     for container in &package.containers {
         if include_images {
@@ -222,6 +307,11 @@ fn collect_cleanup_plan<'a>(
                 .map(|binding| binding.source.as_str()),
         );
 
+        // Files bound by manifest packages are authored by the user, so they are never removed.
+        if package.manifest_path.is_some() {
+            continue;
+        }
+
         for binding in &container.file_bindings {
             let is_read_only = binding
                 .options
@@ -236,7 +326,7 @@ fn collect_cleanup_plan<'a>(
             if let Ok(metadata) = fs::metadata(&binding.source) {
                 if metadata.is_dir() {
                     let is_ephemery_mount = binding.destination == "/root/networks/ephemery";
-                    if !is_ephemery_mount || purge_ephemery_cache {
+                    if !is_ephemery_mount || purge_ephemery_mount {
                         directory_paths.insert(binding.source.as_str());
                     }
                 } else {
@@ -270,7 +360,7 @@ async fn remove_images(docker: &bollard::Docker, image_names: &[&str]) -> Result
     Ok(())
 }
 
-fn remove_bound_files(file_paths: &HashSet<&str>) -> Result<()> {
+pub(crate) fn remove_bound_files(file_paths: &HashSet<&str>) -> Result<()> {
     for path in file_paths {
         info!("Removing file '{}'...", path);
         match fs::remove_file(path) {
@@ -288,7 +378,7 @@ fn remove_bound_files(file_paths: &HashSet<&str>) -> Result<()> {
     Ok(())
 }
 
-fn remove_bound_directories(directory_paths: &HashSet<&str>) -> Result<()> {
+pub(crate) fn remove_bound_directories(directory_paths: &HashSet<&str>) -> Result<()> {
     for path in directory_paths {
         info!("Removing directory '{}'...", path);
         match fs::remove_dir_all(path) {
@@ -341,7 +431,7 @@ async fn remove_volumes(
     Ok(())
 }
 
-async fn remove_network(docker: &bollard::Docker, network_name: &str) -> Result<()> {
+pub(crate) async fn remove_network(docker: &bollard::Docker, network_name: &str) -> Result<()> {
     info!("Removing network '{}'...", network_name);
     match docker.remove_network(network_name).await {
         Ok(_) => info!("Network '{}' removed successfully", network_name),
@@ -360,15 +450,17 @@ async fn remove_network(docker: &bollard::Docker, network_name: &str) -> Result<
 }
 
 fn remove_non_container_artifacts(package: &Package, purge_ephemery_cache: bool) -> Result<()> {
-    if purge_ephemery_cache
-        && package.name == Ethereum::NAME
-        && let Ok(root) = kittynode_path()
-    {
-        let root_dir = root
-            .join("packages")
-            .join("ethereum")
-            .join("networks")
-            .join(EPHEMERY_NETWORK_NAME);
+    if !purge_ephemery_cache {
+        return Ok(());
+    }
+    let Ok(root) = kittynode_path() else {
+        return Ok(());
+    };
+
+    let is_ethereum = split_instance_name(&package.name).0 == Ethereum::NAME;
+    let keep_ephemery_cache = is_ethereum && ethereum::ephemery_cache_in_use(&root, &package.name)?;
+    if is_ethereum && !keep_ephemery_cache {
+        let root_dir = ethereum::ephemery_dir(&root);
         if root_dir.exists() {
             info!("Removing directory '{}'...", root_dir.display());
             fs::remove_dir_all(&root_dir)?;
@@ -376,14 +468,41 @@ fn remove_non_container_artifacts(package: &Package, purge_ephemery_cache: bool)
         }
     }
 
-    if purge_ephemery_cache && let Ok(config_root) = kittynode_path() {
-        remove_package_config_artifacts(&config_root, package.name())?;
-    }
+    // The default Ethereum package directory also holds the Ephemery cache other instances share.
+    let keep_package_dir = package.manifest_path().is_some_and(Path::exists)
+        || (keep_ephemery_cache && package.name == Ethereum::NAME);
+    remove_package_config_artifacts(&root, package.name(), keep_package_dir)
+}
 
-    Ok(())
+/// Lists the existing paths that deleting a package removes besides its bound files: the Ethereum
+/// Ephemery cache, the package configuration, and the package directory.
+pub(crate) fn non_container_artifacts(base_dir: &Path, package: &Package) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let is_ethereum = split_instance_name(&package.name).0 == Ethereum::NAME;
+    let keep_ephemery_cache =
+        is_ethereum && ethereum::ephemery_cache_in_use(base_dir, &package.name).unwrap_or(true);
+    if is_ethereum && !keep_ephemery_cache {
+        paths.push(ethereum::ephemery_dir(base_dir));
+    }
+    paths.push(PackageConfigStore::config_file_path(
+        base_dir,
+        package.name(),
+    ));
+    // The package directory of a manifest package holds the manifest itself, so keep it around.
+    let keep_package_dir = package.manifest_path().is_some_and(Path::exists)
+        || (keep_ephemery_cache && package.name == Ethereum::NAME);
+    if !keep_package_dir {
+        paths.push(PackageConfigStore::package_dir(base_dir, package.name()));
+    }
+    paths.retain(|path| path.exists());
+    paths
 }
 
-fn remove_package_config_artifacts(base_dir: &Path, package_name: &str) -> Result<()> {
+fn remove_package_config_artifacts(
+    base_dir: &Path,
+    package_name: &str,
+    keep_package_dir: bool,
+) -> Result<()> {
     let config_path = PackageConfigStore::config_file_path(base_dir, package_name);
     if config_path.exists() {
         info!(
@@ -412,6 +531,10 @@ fn remove_package_config_artifacts(base_dir: &Path, package_name: &str) -> Resul
         }
     }
 
+    if keep_package_dir {
+        return Ok(());
+    }
+
     let package_dir = PackageConfigStore::package_dir(base_dir, package_name);
     if package_dir.exists() {
         info!("Removing package directory '{}'...", package_dir.display());
@@ -441,7 +564,20 @@ fn remove_package_config_artifacts(base_dir: &Path, package_name: &str) -> Resul
 }
 
 fn persist_selected_network(name: &str, network: &str) -> Result<()> {
-    if name != Ethereum::NAME {
+    validate_network_selection(name, network)?;
+
+    let mut config = PackageConfigStore::load(name)
+        .wrap_err_with(|| format!("Failed to load configuration for {name}"))?;
+    config
+        .values
+        .insert("network".to_string(), network.to_string());
+    PackageConfigStore::save(name, &config)
+        .wrap_err_with(|| format!("Failed to persist configuration for {name}"))
+}
+
+/// Checks that `name` accepts a network selection and that `network` is supported.
+pub(crate) fn validate_network_selection(name: &str, network: &str) -> Result<()> {
+    if split_instance_name(name).0 != Ethereum::NAME {
         return Err(eyre!(
             "Package '{name}' does not support selecting a network"
         ));
@@ -454,13 +590,7 @@ fn persist_selected_network(name: &str, network: &str) -> Result<()> {
         ));
     }
 
-    let mut config = PackageConfigStore::load(name)
-        .wrap_err_with(|| format!("Failed to load configuration for {name}"))?;
-    config
-        .values
-        .insert("network".to_string(), network.to_string());
-    PackageConfigStore::save(name, &config)
-        .wrap_err_with(|| format!("Failed to persist configuration for {name}"))
+    Ok(())
 }
 
 fn describe_partial_install(state: &crate::packages::PackageState) -> String {
@@ -492,7 +622,7 @@ fn ensure_jwt_secret_if_needed(name: &str, package: &Package) -> Result<()> {
 }
 
 fn package_requires_jwt_secret(package: &Package) -> bool {
-    !package.containers.is_empty()
+    package.manifest_path.is_none() && !package.containers.is_empty()
 }
 
 fn is_missing_docker_resource_error(error: &eyre::Report) -> bool {
@@ -509,7 +639,7 @@ mod tests {
         package_requires_jwt_secret, remove_package_config_artifacts, validate_package_installable,
     };
     use crate::packages::{Container, Package, PackageConfig};
-    use std::{collections::HashMap, fs};
+    use std::{fs, path::PathBuf};
     use tempfile::tempdir;
 
     #[test]
@@ -521,7 +651,7 @@ mod tests {
         let config_path = package_dir.join("config.toml");
         fs::write(&config_path, "network = \"ephemery\"").expect("failed to write config");
 
-        remove_package_config_artifacts(&base_dir, "ethereum")
+        remove_package_config_artifacts(&base_dir, "ethereum", false)
             .expect("config artifacts should be removed");
 
         assert!(!config_path.exists());
@@ -539,7 +669,7 @@ mod tests {
         fs::write(&config_path, "network = \"ephemery\"").expect("failed to write config");
         fs::write(&jwt_path, "abc123").expect("failed to write jwt secret");
 
-        remove_package_config_artifacts(&base_dir, "ethereum")
+        remove_package_config_artifacts(&base_dir, "ethereum", false)
             .expect("config artifacts should be removed");
 
         assert!(!config_path.exists());
@@ -547,6 +677,24 @@ mod tests {
         assert!(!jwt_path.exists());
     }
 
+    #[test]
+    fn remove_package_config_artifacts_keeps_manifest_packages() {
+        let temp_dir = tempdir().expect("failed to create temp dir");
+        let base_dir = temp_dir.path().join(".config").join("kittynode");
+        let package_dir = crate::packages::PackageConfigStore::package_dir(&base_dir, "grafana");
+        fs::create_dir_all(&package_dir).expect("failed to create package dir");
+        let config_path = package_dir.join("config.toml");
+        let manifest_path = package_dir.join("manifest.toml");
+        fs::write(&config_path, "[values]\n").expect("failed to write config");
+        fs::write(&manifest_path, "name = \"grafana\"").expect("failed to write manifest");
+
+        remove_package_config_artifacts(&base_dir, "grafana", true)
+            .expect("config artifacts should be removed");
+
+        assert!(!config_path.exists());
+        assert!(manifest_path.exists());
+    }
+
     #[test]
     fn validate_package_installable_allows_packages_with_containers() {
         let package = Package {
@@ -556,12 +704,12 @@ mod tests {
             containers: vec![Container {
                 name: "dummy".to_string(),
                 image: "dummy".to_string(),
-                cmd: Vec::new(),
-                port_bindings: HashMap::new(),
-                volume_bindings: Vec::new(),
-                file_bindings: Vec::new(),
+                tag: "latest".to_string(),
+                ..Default::default()
             }],
             default_config: PackageConfig::default(),
+            config_schema: Vec::new(),
+            manifest_path: None,
         };
 
         validate_package_installable(&package).expect("should be installable");
@@ -576,17 +724,37 @@ mod tests {
             containers: vec![Container {
                 name: "dummy".to_string(),
                 image: "dummy".to_string(),
-                cmd: Vec::new(),
-                port_bindings: HashMap::new(),
-                volume_bindings: Vec::new(),
-                file_bindings: Vec::new(),
+                tag: "latest".to_string(),
+                ..Default::default()
             }],
             default_config: PackageConfig::default(),
+            config_schema: Vec::new(),
+            manifest_path: None,
         };
 
         assert!(package_requires_jwt_secret(&package));
     }
 
+    #[test]
+    fn package_requires_jwt_secret_skips_manifest_packages() {
+        let package = Package {
+            name: "grafana".to_string(),
+            description: "test".to_string(),
+            network_name: "test".to_string(),
+            containers: vec![Container {
+                name: "kittynode-grafana".to_string(),
+                image: "grafana/grafana".to_string(),
+                tag: "latest".to_string(),
+                ..Default::default()
+            }],
+            default_config: PackageConfig::default(),
+            config_schema: Vec::new(),
+            manifest_path: Some(PathBuf::from("/tmp/grafana/manifest.toml")),
+        };
+
+        assert!(!package_requires_jwt_secret(&package));
+    }
+
     #[test]
     fn package_requires_jwt_secret_skips_packages_without_containers() {
         let package = Package {
@@ -595,6 +763,8 @@ mod tests {
             network_name: "test".to_string(),
             containers: Vec::new(),
             default_config: PackageConfig::default(),
+            config_schema: Vec::new(),
+            manifest_path: None,
         };
 
         assert!(!package_requires_jwt_secret(&package));
@@ -0,0 +1,990 @@
+use crate::docker::RESOURCE_PREFIX;
+use crate::packages::health::dependency_order;
+use crate::packages::schema::{is_valid_image_digest, is_valid_image_tag, split_arguments};
+use crate::packages::{
+    Binding, ConfigField, ConfigFieldKind, Container, HealthCheck, HealthProbe, Package,
+    PackageConfig, PackageConfigStore, PortBinding, RestartPolicy,
+};
+use eyre::{Context, Result, eyre};
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::warn;
+
+pub(crate) const MANIFEST_FILE_NAME: &str = "manifest.toml";
+
+/// Template variables that are always available to manifests in addition to their config keys.
+const PACKAGE_DIR_VARIABLE: &str = "package_dir";
+/// Seconds to wait for a container's health check when the manifest does not set `timeout`.
+const DEFAULT_HEALTH_TIMEOUT_SECS: u64 = 120;
+
+/// A package described declaratively in `manifest.toml` instead of a Rust `PackageDefinition`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PackageManifest {
+    name: String,
+    description: String,
+    #[serde(default)]
+    network_name: Option<String>,
+    #[serde(default)]
+    config: Vec<ManifestConfigKey>,
+    #[serde(default)]
+    containers: Vec<ManifestContainer>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestConfigKey {
+    key: String,
+    #[serde(rename = "type", default = "default_config_kind")]
+    kind: ConfigFieldKind,
+    #[serde(default)]
+    default: Option<String>,
+    #[serde(default)]
+    required: bool,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    values: Vec<String>,
+}
+
+fn default_config_kind() -> ConfigFieldKind {
+    ConfigFieldKind::String
+}
+
+impl ManifestConfigKey {
+    fn to_field(&self) -> ConfigField {
+        ConfigField {
+            key: self.key.clone(),
+            kind: self.kind,
+            default: self.default.clone(),
+            required: self.required,
+            description: self.description.clone(),
+            values: self.values.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestContainer {
+    name: String,
+    image: String,
+    tag: String,
+    #[serde(default)]
+    digest: Option<String>,
+    #[serde(default)]
+    cmd: Vec<String>,
+    /// Appended to `cmd` after rendering, split into arguments like a shell would.
+    #[serde(default)]
+    extra_args: Option<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+    #[serde(default)]
+    ports: HashMap<String, Vec<ManifestPortBinding>>,
+    #[serde(default)]
+    volumes: Vec<ManifestBinding>,
+    #[serde(default)]
+    files: Vec<ManifestBinding>,
+    #[serde(default)]
+    depends_on: Vec<String>,
+    #[serde(default)]
+    health: Option<ManifestHealthCheck>,
+    #[serde(default)]
+    restart: RestartPolicy,
+    /// Seconds Docker waits after `SIGTERM` before it kills the container.
+    #[serde(default)]
+    stop_timeout: Option<u64>,
+    #[serde(default)]
+    memory_limit_mib: Option<u64>,
+    #[serde(default)]
+    cpus: Option<f64>,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ManifestProbeKind {
+    Http,
+    Tcp,
+    Exec,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestHealthCheck {
+    #[serde(rename = "type")]
+    kind: ManifestProbeKind,
+    #[serde(default)]
+    port: Option<u16>,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    command: Vec<String>,
+    #[serde(default)]
+    timeout: Option<u64>,
+}
+
+impl ManifestHealthCheck {
+    fn to_health_check(&self) -> Result<HealthCheck> {
+        let probe = match self.kind {
+            ManifestProbeKind::Http => HealthProbe::Http {
+                port: self.required_port()?,
+                path: self.path.clone().unwrap_or_else(|| "/".to_string()),
+            },
+            ManifestProbeKind::Tcp => HealthProbe::Tcp {
+                port: self.required_port()?,
+            },
+            ManifestProbeKind::Exec => {
+                if self.command.is_empty() {
+                    return Err(eyre!("exec health checks need a `command`"));
+                }
+                HealthProbe::Exec {
+                    command: self.command.clone(),
+                }
+            }
+        };
+        if let Some(path) = &self.path
+            && !matches!(self.kind, ManifestProbeKind::Http)
+        {
+            return Err(eyre!("`path` '{path}' only applies to http health checks"));
+        }
+        if matches!(self.timeout, Some(0)) {
+            return Err(eyre!("health check `timeout` must be at least 1 second"));
+        }
+        Ok(HealthCheck::new(
+            probe,
+            self.timeout.unwrap_or(DEFAULT_HEALTH_TIMEOUT_SECS),
+        ))
+    }
+
+    fn required_port(&self) -> Result<u16> {
+        self.port
+            .filter(|port| *port > 0)
+            .ok_or_else(|| eyre!("http and tcp health checks need a non-zero `port`"))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestPortBinding {
+    #[serde(default)]
+    host_ip: Option<String>,
+    #[serde(default)]
+    host_port: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestBinding {
+    source: String,
+    destination: String,
+    #[serde(default)]
+    options: Option<String>,
+}
+
+impl PackageManifest {
+    pub(crate) fn from_toml_str(raw: &str) -> Result<Self> {
+        let manifest: Self = toml::from_str(raw).wrap_err("Failed to parse package manifest")?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn validate(&self) -> Result<()> {
+        validate_package_name(&self.name)?;
+
+        let mut config_keys = HashSet::new();
+        for entry in &self.config {
+            if entry.key.trim().is_empty() {
+                return Err(eyre!(
+                    "Manifest '{}' declares an empty config key",
+                    self.name
+                ));
+            }
+            if entry.key == PACKAGE_DIR_VARIABLE {
+                return Err(eyre!(
+                    "Manifest '{}' cannot declare reserved config key '{PACKAGE_DIR_VARIABLE}'",
+                    self.name
+                ));
+            }
+            if !config_keys.insert(entry.key.as_str()) {
+                return Err(eyre!(
+                    "Manifest '{}' declares config key '{}' more than once",
+                    self.name,
+                    entry.key
+                ));
+            }
+            if entry.kind == ConfigFieldKind::Enum && entry.values.is_empty() {
+                return Err(eyre!(
+                    "Config key '{}' in manifest '{}' is an enum without values",
+                    entry.key,
+                    self.name
+                ));
+            }
+            if let Some(default) = &entry.default
+                && let Err(reason) = entry.to_field().check_value(default)
+            {
+                return Err(eyre!(
+                    "Config key '{}' in manifest '{}' has an invalid default: {reason}",
+                    entry.key,
+                    self.name
+                ));
+            }
+        }
+
+        if self.containers.is_empty() {
+            return Err(eyre!(
+                "Manifest '{}' must declare at least one container",
+                self.name
+            ));
+        }
+
+        let mut container_names = HashSet::new();
+        for container in &self.containers {
+            if !container.name.starts_with(RESOURCE_PREFIX) {
+                return Err(eyre!(
+                    "Container '{}' in manifest '{}' must be named with the '{RESOURCE_PREFIX}' prefix",
+                    container.name,
+                    self.name
+                ));
+            }
+            if !container_names.insert(container.name.as_str()) {
+                return Err(eyre!(
+                    "Manifest '{}' declares container '{}' more than once",
+                    self.name,
+                    container.name
+                ));
+            }
+            if container.image.trim().is_empty() {
+                return Err(eyre!(
+                    "Container '{}' in manifest '{}' is missing an image",
+                    container.name,
+                    self.name
+                ));
+            }
+            if container.image.contains('@')
+                || container
+                    .image
+                    .rsplit('/')
+                    .next()
+                    .is_some_and(|last| last.contains(':'))
+            {
+                return Err(eyre!(
+                    "Container '{}' in manifest '{}' must set its version with `tag` and `digest` instead of in `image`",
+                    container.name,
+                    self.name
+                ));
+            }
+            for port in container.ports.keys() {
+                validate_port_key(port).wrap_err_with(|| {
+                    format!(
+                        "Container '{}' in manifest '{}' has an invalid port",
+                        container.name, self.name
+                    )
+                })?;
+            }
+            if container
+                .cpus
+                .is_some_and(|cpus| !cpus.is_finite() || cpus <= 0.0)
+            {
+                return Err(eyre!(
+                    "Container '{}' in manifest '{}' must set cpus to a positive number",
+                    container.name,
+                    self.name
+                ));
+            }
+            if let Some(health) = &container.health {
+                health.to_health_check().wrap_err_with(|| {
+                    format!(
+                        "Container '{}' in manifest '{}' has an invalid health check",
+                        container.name, self.name
+                    )
+                })?;
+            }
+            for template in container.templates() {
+                for variable in template_variables(template)? {
+                    if variable != PACKAGE_DIR_VARIABLE && !config_keys.contains(variable) {
+                        return Err(eyre!(
+                            "Container '{}' in manifest '{}' references undeclared config key '{variable}'",
+                            container.name,
+                            self.name
+                        ));
+                    }
+                }
+            }
+        }
+
+        let nodes: Vec<(&str, &[String])> = self
+            .containers
+            .iter()
+            .map(|container| (container.name.as_str(), container.depends_on.as_slice()))
+            .collect();
+        dependency_order(&nodes)
+            .wrap_err_with(|| format!("Manifest '{}' has invalid depends_on", self.name))?;
+
+        Ok(())
+    }
+
+    pub(crate) fn config_schema(&self) -> Vec<ConfigField> {
+        self.config
+            .iter()
+            .map(ManifestConfigKey::to_field)
+            .collect()
+    }
+
+    /// Renders the manifest into a concrete [`Package`] using the saved configuration.
+    ///
+    /// Saved values override manifest defaults, and every template must resolve to a value.
+    pub(crate) fn render(
+        &self,
+        package_dir: &Path,
+        saved_config: &PackageConfig,
+    ) -> Result<Package> {
+        let config_schema = self.config_schema();
+        let default_config = PackageConfig::from_schema_defaults(&config_schema);
+        let mut variables = default_config.values.clone();
+        for (key, value) in &saved_config.values {
+            variables.insert(key.clone(), value.clone());
+        }
+        variables.insert(
+            PACKAGE_DIR_VARIABLE.to_string(),
+            package_dir.display().to_string(),
+        );
+
+        let containers = self
+            .containers
+            .iter()
+            .map(|container| container.render(&variables))
+            .collect::<Result<Vec<_>>>()
+            .wrap_err_with(|| format!("Failed to render manifest for '{}'", self.name))?;
+
+        Ok(Package {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            network_name: self
+                .network_name
+                .clone()
+                .unwrap_or_else(|| format!("{RESOURCE_PREFIX}{}-network", self.name)),
+            containers,
+            default_config,
+            config_schema,
+            manifest_path: Some(package_dir.join(MANIFEST_FILE_NAME)),
+        })
+    }
+}
+
+impl ManifestContainer {
+    fn templates(&self) -> impl Iterator<Item = &str> {
+        let ports = self.ports.values().flatten().flat_map(|binding| {
+            binding
+                .host_ip
+                .iter()
+                .chain(binding.host_port.iter())
+                .map(String::as_str)
+        });
+        let bindings = self
+            .volumes
+            .iter()
+            .chain(&self.files)
+            .flat_map(|binding| [binding.source.as_str(), binding.destination.as_str()]);
+
+        [self.image.as_str(), self.tag.as_str()]
+            .into_iter()
+            .chain(self.digest.as_deref())
+            .chain(self.cmd.iter().map(String::as_str))
+            .chain(self.extra_args.as_deref())
+            .chain(self.env.values().map(String::as_str))
+            .chain(self.labels.values().map(String::as_str))
+            .chain(ports)
+            .chain(bindings)
+    }
+
+    fn render(&self, variables: &HashMap<String, String>) -> Result<Container> {
+        let port_bindings = self
+            .ports
+            .iter()
+            .map(|(port, bindings)| {
+                let bindings = bindings
+                    .iter()
+                    .map(|binding| {
+                        Ok(PortBinding {
+                            host_ip: render_optional(binding.host_ip.as_deref(), variables)?,
+                            host_port: render_optional(binding.host_port.as_deref(), variables)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((port.clone(), bindings))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let volume_bindings = self
+            .volumes
+            .iter()
+            .map(|binding| {
+                let binding = binding.render(variables)?;
+                if !binding.source.starts_with(RESOURCE_PREFIX) {
+                    return Err(eyre!(
+                        "Volume '{}' of container '{}' must be named with the '{RESOURCE_PREFIX}' prefix",
+                        binding.source,
+                        self.name
+                    ));
+                }
+                Ok(binding)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let file_bindings = self
+            .files
+            .iter()
+            .map(|binding| {
+                let binding = binding.render(variables)?;
+                if !Path::new(&binding.source).is_absolute() {
+                    return Err(eyre!(
+                        "File binding '{}' of container '{}' must resolve to an absolute path; use {{{{{PACKAGE_DIR_VARIABLE}}}}}",
+                        binding.source,
+                        self.name
+                    ));
+                }
+                Ok(binding)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let tag = render_template(&self.tag, variables)?;
+        if !is_valid_image_tag(&tag) {
+            return Err(eyre!(
+                "Container '{}' has invalid image tag '{tag}'",
+                self.name
+            ));
+        }
+        let digest =
+            render_optional(self.digest.as_deref(), variables)?.filter(|digest| !digest.is_empty());
+        if let Some(digest) = &digest
+            && !is_valid_image_digest(digest)
+        {
+            return Err(eyre!(
+                "Container '{}' has invalid image digest '{digest}'",
+                self.name
+            ));
+        }
+
+        let extra_args = match render_optional(self.extra_args.as_deref(), variables)? {
+            Some(extra_args) => split_arguments(&extra_args).map_err(|reason| {
+                eyre!(
+                    "Container '{}' has invalid extra_args '{extra_args}': {reason}",
+                    self.name
+                )
+            })?,
+            None => Vec::new(),
+        };
+        let render_map = |values: &HashMap<String, String>| {
+            values
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), render_template(value, variables)?)))
+                .collect::<Result<HashMap<_, _>>>()
+        };
+
+        Ok(Container {
+            name: self.name.clone(),
+            image: render_template(&self.image, variables)?,
+            tag,
+            digest,
+            entrypoint: Vec::new(),
+            cmd: self
+                .cmd
+                .iter()
+                .map(|arg| render_template(arg, variables))
+                .collect::<Result<Vec<_>>>()?,
+            extra_args,
+            env: render_map(&self.env)?,
+            labels: render_map(&self.labels)?,
+            port_bindings,
+            volume_bindings,
+            file_bindings,
+            health_check: self
+                .health
+                .as_ref()
+                .map(ManifestHealthCheck::to_health_check)
+                .transpose()?,
+            depends_on: self.depends_on.clone(),
+            restart_policy: self.restart,
+            memory_limit_bytes: self
+                .memory_limit_mib
+                .map(|mib| mib.saturating_mul(1024 * 1024)),
+            cpu_limit: self.cpus,
+            ulimits: Vec::new(),
+            stop_timeout_secs: self.stop_timeout,
+        })
+    }
+}
+
+impl ManifestBinding {
+    fn render(&self, variables: &HashMap<String, String>) -> Result<Binding> {
+        Ok(Binding {
+            source: render_template(&self.source, variables)?,
+            destination: render_template(&self.destination, variables)?,
+            options: self.options.clone(),
+        })
+    }
+}
+
+/// Loads every `packages/*/manifest.toml` below the given Kittynode config root.
+///
+/// Manifests that fail to parse, validate, or render are skipped with a warning so that one broken
+/// package cannot take down the whole catalog.
+pub(crate) fn load_manifest_packages(base_dir: &Path) -> Result<Vec<Package>> {
+    let packages_dir = base_dir.join("packages");
+    let entries = match fs::read_dir(&packages_dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).wrap_err_with(|| {
+                format!(
+                    "Failed to read packages directory {}",
+                    packages_dir.display()
+                )
+            });
+        }
+    };
+
+    let mut manifest_paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(MANIFEST_FILE_NAME))
+        .filter(|path| path.is_file())
+        .collect();
+    manifest_paths.sort();
+
+    let mut packages = Vec::new();
+    for manifest_path in manifest_paths {
+        match load_manifest_package(base_dir, &manifest_path) {
+            Ok(package) => packages.push(package),
+            Err(error) => warn!(
+                "Skipping package manifest '{}': {error:#}",
+                manifest_path.display()
+            ),
+        }
+    }
+
+    Ok(packages)
+}
+
+fn load_manifest_package(base_dir: &Path, manifest_path: &Path) -> Result<Package> {
+    let (manifest, package_dir) = read_manifest(base_dir, manifest_path)?;
+    let saved_config = PackageConfigStore::load_from(base_dir, manifest.name())?;
+    manifest.render(&package_dir, &saved_config)
+}
+
+/// Renders the manifest package `name` with `config` in place of its saved configuration.
+///
+/// Returns `None` when the package has no manifest.
+pub(crate) fn preview_manifest_package(
+    base_dir: &Path,
+    name: &str,
+    config: &PackageConfig,
+) -> Result<Option<Package>> {
+    let manifest_path = PackageConfigStore::package_dir(base_dir, name).join(MANIFEST_FILE_NAME);
+    if !manifest_path.is_file() {
+        return Ok(None);
+    }
+    let (manifest, package_dir) = read_manifest(base_dir, &manifest_path)?;
+    manifest.render(&package_dir, config).map(Some)
+}
+
+fn read_manifest(base_dir: &Path, manifest_path: &Path) -> Result<(PackageManifest, PathBuf)> {
+    let raw = fs::read_to_string(manifest_path)
+        .wrap_err_with(|| format!("Failed to read {}", manifest_path.display()))?;
+    let manifest = PackageManifest::from_toml_str(&raw)?;
+
+    let package_dir = PackageConfigStore::package_dir(base_dir, manifest.name());
+    if manifest_path.parent() != Some(package_dir.as_path()) {
+        return Err(eyre!(
+            "Manifest for '{}' must live in {}",
+            manifest.name(),
+            package_dir.display()
+        ));
+    }
+
+    Ok((manifest, package_dir))
+}
+
+fn validate_package_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let valid_start = chars
+        .next()
+        .is_some_and(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit());
+    let valid_rest = chars.all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-');
+    if valid_start && valid_rest {
+        Ok(())
+    } else {
+        Err(eyre!(
+            "Invalid package name '{name}': use lowercase letters, digits, and dashes"
+        ))
+    }
+}
+
+fn validate_port_key(port: &str) -> Result<()> {
+    let (number, protocol) = port
+        .split_once('/')
+        .ok_or_else(|| eyre!("Port '{port}' must look like '<port>/<tcp|udp>'"))?;
+    number
+        .parse::<u16>()
+        .ok()
+        .filter(|value| *value > 0)
+        .ok_or_else(|| eyre!("Port '{port}' has an invalid port number"))?;
+    match protocol {
+        "tcp" | "udp" => Ok(()),
+        other => Err(eyre!("Port '{port}' has unsupported protocol '{other}'")),
+    }
+}
+
+/// Returns the variable names referenced by `{{ name }}` placeholders in a template.
+fn template_variables(template: &str) -> Result<Vec<&str>> {
+    let mut variables = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| eyre!("Unterminated placeholder in '{template}'"))?;
+        let name = after[..end].trim();
+        if name.is_empty() {
+            return Err(eyre!("Empty placeholder in '{template}'"));
+        }
+        variables.push(name);
+        rest = &after[end + 2..];
+    }
+    Ok(variables)
+}
+
+fn render_template(template: &str, variables: &HashMap<String, String>) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| eyre!("Unterminated placeholder in '{template}'"))?;
+        let name = after[..end].trim();
+        let value = variables
+            .get(name)
+            .ok_or_else(|| eyre!("No value configured for '{name}' used in '{template}'"))?;
+        rendered.push_str(value);
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+fn render_optional(
+    template: Option<&str>,
+    variables: &HashMap<String, String>,
+) -> Result<Option<String>> {
+    template
+        .map(|value| render_template(value, variables))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        MANIFEST_FILE_NAME, PackageManifest, load_manifest_packages, render_template,
+        template_variables,
+    };
+    use crate::packages::{
+        ConfigFieldKind, HealthProbe, PackageConfig, PackageConfigStore, RestartPolicy,
+    };
+    use std::{collections::HashMap, fs, path::Path};
+    use tempfile::tempdir;
+
+    const MANIFEST: &str = r#"
+name = "grafana"
+description = "Grafana dashboards"
+
+[[config]]
+key = "http_port"
+type = "integer"
+default = "3001"
+description = "Host port for the web UI"
+
+[[config]]
+key = "grafana_version"
+type = "image_tag"
+default = "12.2.0"
+
+[[config]]
+key = "grafana_extra_args"
+type = "arguments"
+default = ""
+
+[[containers]]
+name = "kittynode-grafana"
+image = "grafana/grafana"
+tag = "{{ grafana_version }}"
+cmd = ["--config", "/etc/grafana/grafana.ini"]
+extra_args = "{{ grafana_extra_args }}"
+
+[containers.env]
+GF_SERVER_HTTP_PORT = "3000"
+
+[containers.labels]
+"org.example.dashboard-port" = "{{ http_port }}"
+
+[containers.ports]
+"3000/tcp" = [{ host_ip = "127.0.0.1", host_port = "{{ http_port }}" }]
+
+[[containers.volumes]]
+source = "kittynode-grafana-data"
+destination = "/var/lib/grafana"
+
+[[containers.files]]
+source = "{{package_dir}}/grafana.ini"
+destination = "/etc/grafana/grafana.ini"
+options = "ro"
+"#;
+
+    fn write_manifest(base_dir: &Path, name: &str, raw: &str) {
+        let package_dir = PackageConfigStore::package_dir(base_dir, name);
+        fs::create_dir_all(&package_dir).expect("create package dir");
+        fs::write(package_dir.join(MANIFEST_FILE_NAME), raw).expect("write manifest");
+    }
+
+    #[test]
+    fn render_applies_defaults_and_package_dir() {
+        let manifest = PackageManifest::from_toml_str(MANIFEST).expect("manifest should parse");
+        let package = manifest
+            .render(
+                Path::new("/tmp/kittynode/packages/grafana"),
+                &PackageConfig::new(),
+            )
+            .expect("manifest should render");
+
+        assert_eq!(package.name(), "grafana");
+        assert_eq!(package.network_name(), "kittynode-grafana-network");
+        assert_eq!(
+            package
+                .default_config
+                .values
+                .get("http_port")
+                .map(String::as_str),
+            Some("3001")
+        );
+
+        let container = &package.containers[0];
+        assert_eq!(container.image_reference(), "grafana/grafana:12.2.0");
+        let binding = &container.port_bindings["3000/tcp"][0];
+        assert_eq!(binding.host_port.as_deref(), Some("3001"));
+        assert_eq!(
+            container.file_bindings[0].source,
+            "/tmp/kittynode/packages/grafana/grafana.ini"
+        );
+        assert!(container.extra_args.is_empty());
+        assert_eq!(container.env["GF_SERVER_HTTP_PORT"], "3000");
+        assert_eq!(container.labels["org.example.dashboard-port"], "3001");
+    }
+
+    #[test]
+    fn render_prefers_saved_config_over_defaults() {
+        let manifest = PackageManifest::from_toml_str(MANIFEST).expect("manifest should parse");
+        let mut saved = PackageConfig::new();
+        saved.values.insert("http_port".into(), "4000".into());
+        saved.values.insert(
+            "grafana_extra_args".into(),
+            "--homepath '/usr/share/grafana'".into(),
+        );
+
+        let package = manifest
+            .render(Path::new("/tmp/grafana"), &saved)
+            .expect("manifest should render");
+
+        let container = &package.containers[0];
+        let binding = &container.port_bindings["3000/tcp"][0];
+        assert_eq!(binding.host_port.as_deref(), Some("4000"));
+        assert_eq!(
+            container.command(),
+            vec![
+                "--config",
+                "/etc/grafana/grafana.ini",
+                "--homepath",
+                "/usr/share/grafana"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_undeclared_template_variables() {
+        let raw = MANIFEST.replace("{{ http_port }}", "{{ web_port }}");
+        let error = PackageManifest::from_toml_str(&raw)
+            .err()
+            .expect("undeclared key should be rejected");
+        assert!(error.to_string().contains("web_port"), "got {error}");
+    }
+
+    #[test]
+    fn config_schema_carries_types_and_rejects_bad_defaults() {
+        let manifest = PackageManifest::from_toml_str(MANIFEST).expect("manifest should parse");
+        let schema = manifest.config_schema();
+        assert_eq!(schema.len(), 3);
+        assert_eq!(schema[0].kind(), ConfigFieldKind::Integer);
+        assert_eq!(schema[1].kind(), ConfigFieldKind::ImageTag);
+        assert_eq!(schema[2].kind(), ConfigFieldKind::Arguments);
+        assert_eq!(schema[0].description(), "Host port for the web UI");
+
+        let raw = MANIFEST.replace("default = \"3001\"", "default = \"web\"");
+        assert!(PackageManifest::from_toml_str(&raw).is_err());
+    }
+
+    #[test]
+    fn rejects_tags_embedded_in_image() {
+        let raw = MANIFEST.replace("\"grafana/grafana\"", "\"grafana/grafana:latest\"");
+        assert!(PackageManifest::from_toml_str(&raw).is_err());
+    }
+
+    #[test]
+    fn rejects_unprefixed_container_names() {
+        let raw = MANIFEST.replace("name = \"kittynode-grafana\"", "name = \"grafana\"");
+        assert!(PackageManifest::from_toml_str(&raw).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_port_keys() {
+        let raw = MANIFEST.replace("\"3000/tcp\"", "\"3000/sctp\"");
+        assert!(PackageManifest::from_toml_str(&raw).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let raw = format!("{MANIFEST}\n[extras]\nfoo = 1\n");
+        assert!(PackageManifest::from_toml_str(&raw).is_err());
+    }
+
+    #[test]
+    fn parses_health_checks_and_dependencies() {
+        let raw = format!(
+            r#"{MANIFEST}
+[containers.health]
+type = "http"
+port = 3000
+path = "/api/health"
+timeout = 30
+
+[[containers]]
+name = "kittynode-grafana-renderer"
+image = "grafana/grafana-image-renderer"
+tag = "latest"
+depends_on = ["kittynode-grafana"]
+restart = "unless-stopped"
+stop_timeout = 60
+
+[containers.health]
+type = "exec"
+command = ["true"]
+"#
+        );
+        let manifest = PackageManifest::from_toml_str(&raw).expect("manifest should parse");
+        let package = manifest
+            .render(Path::new("/tmp/grafana"), &PackageConfig::new())
+            .expect("manifest should render");
+
+        let grafana = package.containers[0]
+            .health_check
+            .as_ref()
+            .expect("health check");
+        assert_eq!(
+            grafana.probe(),
+            &HealthProbe::Http {
+                port: 3000,
+                path: "/api/health".to_string()
+            }
+        );
+        assert_eq!(grafana.timeout_secs(), 30);
+        assert_eq!(package.containers[1].depends_on, vec!["kittynode-grafana"]);
+        assert_eq!(
+            package.containers[1]
+                .health_check
+                .as_ref()
+                .map(|check| check.timeout_secs()),
+            Some(120)
+        );
+        assert_eq!(
+            package.containers[1].restart_policy,
+            RestartPolicy::UnlessStopped
+        );
+        assert_eq!(package.containers[1].stop_timeout_secs, Some(60));
+        assert_eq!(package.containers[0].restart_policy, RestartPolicy::No);
+    }
+
+    #[test]
+    fn rejects_invalid_health_checks_and_dependencies() {
+        let tcp_without_port = format!("{MANIFEST}\n[containers.health]\ntype = \"tcp\"\n");
+        assert!(PackageManifest::from_toml_str(&tcp_without_port).is_err());
+
+        let unknown_dependency = MANIFEST.replace(
+            "tag = \"{{ grafana_version }}\"",
+            "tag = \"{{ grafana_version }}\"\ndepends_on = [\"kittynode-missing\"]",
+        );
+        let error = PackageManifest::from_toml_str(&unknown_dependency)
+            .err()
+            .expect("unknown dependency should be rejected");
+        assert!(
+            format!("{error:#}").contains("kittynode-missing"),
+            "got {error:#}"
+        );
+    }
+
+    #[test]
+    fn load_manifest_packages_skips_invalid_manifests() {
+        let temp_dir = tempdir().expect("tempdir");
+        let base_dir = temp_dir.path().join(".config").join("kittynode");
+        write_manifest(&base_dir, "grafana", MANIFEST);
+        write_manifest(&base_dir, "broken", "name = \"broken\"\n");
+        fs::create_dir_all(PackageConfigStore::package_dir(&base_dir, "ethereum"))
+            .expect("create package dir without manifest");
+
+        let packages = load_manifest_packages(&base_dir).expect("manifests should load");
+        let names: Vec<&str> = packages.iter().map(|package| package.name()).collect();
+        assert_eq!(names, vec!["grafana"]);
+    }
+
+    #[test]
+    fn load_manifest_packages_requires_matching_directory() {
+        let temp_dir = tempdir().expect("tempdir");
+        let base_dir = temp_dir.path().join(".config").join("kittynode");
+        write_manifest(&base_dir, "not-grafana", MANIFEST);
+
+        let packages = load_manifest_packages(&base_dir).expect("manifests should load");
+        assert!(packages.is_empty());
+    }
+
+    #[test]
+    fn load_manifest_packages_handles_missing_directory() {
+        let temp_dir = tempdir().expect("tempdir");
+        let packages =
+            load_manifest_packages(temp_dir.path()).expect("missing dir should be empty");
+        assert!(packages.is_empty());
+    }
+
+    #[test]
+    fn template_helpers_handle_edge_cases() {
+        assert_eq!(
+            template_variables("{{a}}-{{ b }}").expect("variables"),
+            vec!["a", "b"]
+        );
+        assert!(template_variables("{{a").is_err());
+        assert!(template_variables("{{ }}").is_err());
+
+        let variables = HashMap::from([("a".to_string(), "1".to_string())]);
+        assert_eq!(
+            render_template("x{{a}}y", &variables).expect("render"),
+            "x1y"
+        );
+        assert!(render_template("{{missing}}", &variables).is_err());
+    }
+}
@@ -0,0 +1,510 @@
+use crate::docker::{find_container, get_docker_instance};
+use crate::ethereum;
+use crate::packages::catalog::preview_package;
+use crate::packages::lifecycle::{
+    collect_cleanup_plan, ignore_unsupported_network, merge_config_update, non_container_artifacts,
+    validate_network_selection,
+};
+use crate::packages::{Container, Package, PackageConfig, PackageConfigStore, split_instance_name};
+use crate::paths::kittynode_path;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fmt};
+
+/// The package operation a [`Plan`] describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlanOperation {
+    Install,
+    UpdateConfig,
+    Delete,
+}
+
+/// What an operation does to one container.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ContainerAction {
+    Create,
+    Recreate,
+    Keep,
+    Remove,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedContainer {
+    pub name: String,
+    pub image: String,
+    pub action: ContainerAction,
+}
+
+/// A host port that a created or recreated container binds.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedPort {
+    pub container: String,
+    pub container_port: String,
+    pub host_ip: Option<String>,
+    pub host_port: String,
+}
+
+/// The changes a package operation would make, computed without making any of them.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Plan {
+    pub package: String,
+    pub operation: PlanOperation,
+    pub containers: Vec<PlannedContainer>,
+    pub images_pulled: Vec<String>,
+    pub images_removed: Vec<String>,
+    pub volumes_kept: Vec<String>,
+    pub volumes_purged: Vec<String>,
+    pub host_ports: Vec<PlannedPort>,
+    pub files_deleted: Vec<String>,
+}
+
+impl Plan {
+    fn new(package: &str, operation: PlanOperation) -> Self {
+        Self {
+            package: package.to_string(),
+            operation,
+            containers: Vec::new(),
+            images_pulled: Vec::new(),
+            images_removed: Vec::new(),
+            volumes_kept: Vec::new(),
+            volumes_purged: Vec::new(),
+            host_ports: Vec::new(),
+            files_deleted: Vec::new(),
+        }
+    }
+
+    /// Returns true when carrying out the operation would leave everything as it is.
+    pub fn is_noop(&self) -> bool {
+        self.containers
+            .iter()
+            .all(|container| container.action == ContainerAction::Keep)
+            && self.images_pulled.is_empty()
+            && self.images_removed.is_empty()
+            && self.volumes_purged.is_empty()
+            && self.files_deleted.is_empty()
+    }
+
+    /// Adds a container that gets started, along with its image and host ports.
+    fn start(&mut self, container: &Container, action: ContainerAction) {
+        let image = container.image_reference();
+        if !self.images_pulled.contains(&image) {
+            self.images_pulled.push(image.clone());
+        }
+        self.containers.push(PlannedContainer {
+            name: container.name.clone(),
+            image,
+            action,
+        });
+
+        let mut ports: Vec<_> = container.port_bindings.iter().collect();
+        ports.sort_by(|(left, _), (right, _)| left.cmp(right));
+        for (container_port, bindings) in ports {
+            for binding in bindings {
+                if let Some(host_port) = &binding.host_port {
+                    self.host_ports.push(PlannedPort {
+                        container: container.name.clone(),
+                        container_port: container_port.clone(),
+                        host_ip: binding.host_ip.clone(),
+                        host_port: host_port.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn keep_volumes(&mut self, package: &Package) {
+        for volume in package_volumes(package) {
+            if !self.volumes_kept.contains(&volume) {
+                self.volumes_kept.push(volume);
+            }
+        }
+    }
+
+    fn delete_files<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) {
+        for path in paths {
+            if !self.files_deleted.iter().any(|deleted| deleted == path) {
+                self.files_deleted.push(path.to_string());
+            }
+        }
+        self.files_deleted.sort();
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = match self.operation {
+            PlanOperation::Install => "Installing",
+            PlanOperation::UpdateConfig => "Updating the config of",
+            PlanOperation::Delete => "Deleting",
+        };
+        writeln!(formatter, "{verb} package '{}' would:", self.package)?;
+        if self.is_noop() {
+            return writeln!(formatter, "  change nothing");
+        }
+
+        for container in &self.containers {
+            let action = match container.action {
+                ContainerAction::Create => "create",
+                ContainerAction::Recreate => "recreate",
+                ContainerAction::Keep => "keep",
+                ContainerAction::Remove => "remove",
+            };
+            writeln!(
+                formatter,
+                "  {action} container {} ({})",
+                container.name, container.image
+            )?;
+        }
+        for image in &self.images_pulled {
+            writeln!(formatter, "  pull image {image}")?;
+        }
+        for image in &self.images_removed {
+            writeln!(formatter, "  remove image {image}")?;
+        }
+        for port in &self.host_ports {
+            let host_ip = port.host_ip.as_deref().unwrap_or("0.0.0.0");
+            writeln!(
+                formatter,
+                "  bind host port {host_ip}:{} to {} {}",
+                port.host_port, port.container, port.container_port
+            )?;
+        }
+        for volume in &self.volumes_kept {
+            writeln!(formatter, "  keep volume {volume}")?;
+        }
+        for volume in &self.volumes_purged {
+            writeln!(formatter, "  purge volume {volume}")?;
+        }
+        for path in &self.files_deleted {
+            writeln!(formatter, "  delete {path}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Plans [`install_package_with_network`](crate::packages::install_package_with_network).
+pub(crate) async fn plan_install(name: &str, network: Option<&str>) -> Result<Plan> {
+    let mut config = PackageConfigStore::load(name)?;
+    if let Some(network) = network {
+        validate_network_selection(name, network)?;
+        config
+            .values
+            .insert("network".to_string(), network.to_string());
+    }
+    if let (ethereum::ETHEREUM_NAME, Some(instance)) = split_instance_name(name)
+        && let Some(ports) =
+            ethereum::instance_port_assignment(&kittynode_path()?, instance, &config)?
+    {
+        config.values.extend(ports);
+    }
+    let package = preview_package(name, &config)?;
+    let existing = existing_containers(&[&package]).await?;
+    // Selecting a network saves the config, so the package counts as configured either way.
+    let config_present = network.is_some()
+        || PackageConfigStore::config_file_path(&kittynode_path()?, name).exists();
+    Ok(install_plan(&package, &existing, config_present))
+}
+
+/// Plans [`update_package_config`](crate::packages::update_package_config).
+pub(crate) async fn plan_update_config(name: &str, config: &PackageConfig) -> Result<Plan> {
+    let merged = merge_config_update(name, config)?;
+    let saved = PackageConfigStore::load(name)?;
+    let current = ignore_unsupported_network(preview_package(name, &saved))?;
+    let target = preview_package(name, &merged)?;
+
+    let mut packages = vec![&target];
+    packages.extend(current.as_ref());
+    let existing = existing_containers(&packages).await?;
+    Ok(update_plan(current.as_ref(), &target, &existing))
+}
+
+/// Plans [`delete_package`](crate::packages::delete_package).
+pub(crate) async fn plan_delete(name: &str, include_images: bool) -> Result<Plan> {
+    let saved = PackageConfigStore::load(name)?;
+    let package = preview_package(name, &saved)?;
+    let existing = existing_containers(&[&package]).await?;
+    let artifacts = non_container_artifacts(&kittynode_path()?, &package);
+    let artifacts: Vec<String> = artifacts
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+
+    let mut plan = delete_plan(&package, &existing, include_images);
+    plan.delete_files(artifacts.iter().map(String::as_str));
+    Ok(plan)
+}
+
+fn install_plan(package: &Package, existing: &HashSet<String>, config_present: bool) -> Plan {
+    let mut plan = Plan::new(package.name(), PlanOperation::Install);
+    let all_exist = package
+        .containers
+        .iter()
+        .all(|container| existing.contains(&container.name));
+
+    if config_present && all_exist && !package.containers.is_empty() {
+        for container in &package.containers {
+            plan.containers.push(PlannedContainer {
+                name: container.name.clone(),
+                image: container.image_reference(),
+                action: ContainerAction::Keep,
+            });
+        }
+        plan.keep_volumes(package);
+        return plan;
+    }
+
+    let partially_installed = config_present
+        || package
+            .containers
+            .iter()
+            .any(|container| existing.contains(&container.name));
+    for container in &package.containers {
+        let action = if existing.contains(&container.name) {
+            ContainerAction::Recreate
+        } else {
+            ContainerAction::Create
+        };
+        plan.start(container, action);
+    }
+    plan.keep_volumes(package);
+    // A partial install is cleaned up first, which removes the package's generated bound files.
+    if partially_installed && !package.containers.is_empty() {
+        plan.delete_files(cleanup_files(package));
+    }
+    plan
+}
+
+fn update_plan(current: Option<&Package>, target: &Package, existing: &HashSet<String>) -> Plan {
+    let mut plan = Plan::new(target.name(), PlanOperation::UpdateConfig);
+
+    if let Some(current) = current {
+        for container in &current.containers {
+            let replaced = target
+                .containers
+                .iter()
+                .any(|candidate| candidate.name == container.name);
+            if existing.contains(&container.name) && !replaced {
+                plan.containers.push(PlannedContainer {
+                    name: container.name.clone(),
+                    image: container.image_reference(),
+                    action: ContainerAction::Remove,
+                });
+            }
+        }
+        plan.keep_volumes(current);
+        plan.delete_files(cleanup_files(current));
+    }
+
+    for container in &target.containers {
+        let action = if existing.contains(&container.name) {
+            ContainerAction::Recreate
+        } else {
+            ContainerAction::Create
+        };
+        plan.start(container, action);
+    }
+    plan.keep_volumes(target);
+    // The reinstall finds the saved config without containers and cleans up once more.
+    plan.delete_files(cleanup_files(target));
+    plan
+}
+
+fn delete_plan(package: &Package, existing: &HashSet<String>, include_images: bool) -> Plan {
+    let mut plan = Plan::new(package.name(), PlanOperation::Delete);
+    if package.containers.is_empty() {
+        return plan;
+    }
+
+    for container in &package.containers {
+        if existing.contains(&container.name) {
+            plan.containers.push(PlannedContainer {
+                name: container.name.clone(),
+                image: container.image_reference(),
+                action: ContainerAction::Remove,
+            });
+        }
+    }
+
+    let cleanup = collect_cleanup_plan(package, include_images, true);
+    for image in cleanup.image_names {
+        if !plan.images_removed.iter().any(|removed| removed == image) {
+            plan.images_removed.push(image.to_string());
+        }
+    }
+    plan.volumes_purged = package_volumes(package);
+    plan.delete_files(cleanup.file_paths);
+    plan.delete_files(cleanup.directory_paths);
+    plan
+}
+
+/// Lists the bound files and directories that removing the package's containers deletes while
+/// keeping its volumes and Ephemery cache.
+fn cleanup_files(package: &Package) -> Vec<&str> {
+    let cleanup = collect_cleanup_plan(package, false, false);
+    cleanup
+        .file_paths
+        .into_iter()
+        .chain(cleanup.directory_paths)
+        .collect()
+}
+
+fn package_volumes(package: &Package) -> Vec<String> {
+    let mut volumes: Vec<String> = Vec::new();
+    for binding in package
+        .containers
+        .iter()
+        .flat_map(|container| &container.volume_bindings)
+    {
+        if !volumes.contains(&binding.source) {
+            volumes.push(binding.source.clone());
+        }
+    }
+    volumes
+}
+
+/// Returns the names of the given packages' containers that currently exist in Docker.
+async fn existing_containers(packages: &[&Package]) -> Result<HashSet<String>> {
+    let mut existing = HashSet::new();
+    if packages.iter().all(|package| package.containers.is_empty()) {
+        return Ok(existing);
+    }
+
+    let docker = get_docker_instance().await?;
+    for container in packages.iter().flat_map(|package| &package.containers) {
+        if !existing.contains(&container.name)
+            && !find_container(&docker, &container.name).await?.is_empty()
+        {
+            existing.insert(container.name.clone());
+        }
+    }
+    Ok(existing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ContainerAction, delete_plan, install_plan, update_plan};
+    use crate::packages::{Binding, Container, Package, PackageConfig, PortBinding};
+    use std::collections::{HashMap, HashSet};
+
+    fn container(name: &str, tag: &str) -> Container {
+        Container {
+            name: name.to_string(),
+            image: "example/node".to_string(),
+            tag: tag.to_string(),
+            port_bindings: HashMap::from([(
+                "8545/tcp".to_string(),
+                vec![PortBinding {
+                    host_ip: Some("127.0.0.1".to_string()),
+                    host_port: Some("8545".to_string()),
+                }],
+            )]),
+            volume_bindings: vec![Binding {
+                source: format!("{name}-data"),
+                destination: "/data".to_string(),
+                options: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn package(containers: Vec<Container>) -> Package {
+        Package {
+            name: "example".to_string(),
+            description: "Example".to_string(),
+            network_name: "kittynode-example-network".to_string(),
+            containers,
+            default_config: PackageConfig::default(),
+            config_schema: Vec::new(),
+            manifest_path: None,
+        }
+    }
+
+    fn actions(plan: &super::Plan) -> Vec<(&str, ContainerAction)> {
+        plan.containers
+            .iter()
+            .map(|container| (container.name.as_str(), container.action))
+            .collect()
+    }
+
+    #[test]
+    fn install_plan_creates_missing_containers_and_binds_ports() {
+        let package = package(vec![container("kittynode-node", "v1")]);
+        let plan = install_plan(&package, &HashSet::new(), false);
+
+        assert_eq!(
+            actions(&plan),
+            vec![("kittynode-node", ContainerAction::Create)]
+        );
+        assert_eq!(plan.images_pulled, vec!["example/node:v1"]);
+        assert_eq!(plan.volumes_kept, vec!["kittynode-node-data"]);
+        assert_eq!(plan.host_ports.len(), 1);
+        assert_eq!(plan.host_ports[0].host_port, "8545");
+        assert!(plan.files_deleted.is_empty());
+    }
+
+    #[test]
+    fn install_plan_keeps_an_installed_package() {
+        let package = package(vec![container("kittynode-node", "v1")]);
+        let existing = HashSet::from(["kittynode-node".to_string()]);
+        let plan = install_plan(&package, &existing, true);
+
+        assert_eq!(
+            actions(&plan),
+            vec![("kittynode-node", ContainerAction::Keep)]
+        );
+        assert!(plan.images_pulled.is_empty());
+        assert!(plan.is_noop());
+        assert!(plan.to_string().contains("change nothing"));
+    }
+
+    #[test]
+    fn update_plan_recreates_kept_names_and_removes_dropped_containers() {
+        let current = package(vec![
+            container("kittynode-node", "v1"),
+            container("kittynode-sidecar", "v1"),
+        ]);
+        let target = package(vec![container("kittynode-node", "v2")]);
+        let existing = HashSet::from([
+            "kittynode-node".to_string(),
+            "kittynode-sidecar".to_string(),
+        ]);
+        let plan = update_plan(Some(&current), &target, &existing);
+
+        assert_eq!(
+            actions(&plan),
+            vec![
+                ("kittynode-sidecar", ContainerAction::Remove),
+                ("kittynode-node", ContainerAction::Recreate),
+            ]
+        );
+        assert_eq!(plan.images_pulled, vec!["example/node:v2"]);
+        assert_eq!(
+            plan.volumes_kept,
+            vec!["kittynode-node-data", "kittynode-sidecar-data"]
+        );
+        assert!(plan.volumes_purged.is_empty());
+    }
+
+    #[test]
+    fn delete_plan_purges_volumes_and_only_removes_images_on_request() {
+        let package = package(vec![container("kittynode-node", "v1")]);
+        let existing = HashSet::from(["kittynode-node".to_string()]);
+
+        let plan = delete_plan(&package, &existing, false);
+        assert_eq!(
+            actions(&plan),
+            vec![("kittynode-node", ContainerAction::Remove)]
+        );
+        assert_eq!(plan.volumes_purged, vec!["kittynode-node-data"]);
+        assert!(plan.images_removed.is_empty());
+        assert!(plan.images_pulled.is_empty());
+
+        let plan = delete_plan(&package, &existing, true);
+        assert_eq!(plan.images_removed, vec!["example/node"]);
+    }
+}
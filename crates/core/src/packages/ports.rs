@@ -0,0 +1,370 @@
+use crate::packages::Package;
+use bollard::{Docker, query_parameters::ListContainersOptionsBuilder};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    io::ErrorKind,
+    net::{TcpListener, UdpSocket},
+};
+
+/// Whoever already holds a host port that a package wants to bind.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum PortHolder {
+    /// A Docker container publishes the port.
+    Container { name: String },
+    /// A host process listens on the port.
+    Process { pid: u32, name: String },
+    /// The port is taken, but its owner could not be identified.
+    Unknown,
+}
+
+impl fmt::Display for PortHolder {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Container { name } => write!(formatter, "container '{name}'"),
+            Self::Process { pid, name } => write!(formatter, "process '{name}' (pid {pid})"),
+            Self::Unknown => write!(formatter, "an unidentified process"),
+        }
+    }
+}
+
+/// A host port binding of a package container that is already in use.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortConflict {
+    /// The package container that wants the port.
+    pub container: String,
+    pub host_ip: String,
+    pub host_port: u16,
+    pub protocol: String,
+    pub holder: PortHolder,
+}
+
+impl fmt::Display for PortConflict {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "{}:{}/{} for '{}' is held by {}",
+            self.host_ip, self.host_port, self.protocol, self.container, self.holder
+        )
+    }
+}
+
+/// Returned when host ports a package binds are already in use.
+///
+/// Callers can downcast an `eyre::Report` to this type to report conflicts separately from other
+/// install failures.
+#[derive(Debug)]
+pub struct PortConflictError {
+    package_name: String,
+    conflicts: Vec<PortConflict>,
+}
+
+impl PortConflictError {
+    pub fn conflicts(&self) -> &[PortConflict] {
+        &self.conflicts
+    }
+}
+
+impl fmt::Display for PortConflictError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conflicts: Vec<String> = self.conflicts.iter().map(ToString::to_string).collect();
+        write!(
+            formatter,
+            "Host ports needed by package '{}' are already in use: {}",
+            self.package_name,
+            conflicts.join("; ")
+        )
+    }
+}
+
+impl Error for PortConflictError {}
+
+/// A host port published by a running container, as reported by Docker.
+struct PublishedPort {
+    container: String,
+    host_ip: String,
+    host_port: u16,
+    protocol: String,
+}
+
+/// Fails with a [`PortConflictError`] if any host port the package binds is already taken.
+pub(crate) async fn ensure_ports_available(docker: &Docker, package: &Package) -> Result<()> {
+    let conflicts = find_port_conflicts(docker, package).await?;
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    Err(PortConflictError {
+        package_name: package.name.clone(),
+        conflicts,
+    }
+    .into())
+}
+
+/// Checks every host port binding of the package against other Docker containers and against
+/// sockets that are open on the host.
+///
+/// Ports published by the package's own containers are skipped because installs replace those
+/// containers.
+async fn find_port_conflicts(docker: &Docker, package: &Package) -> Result<Vec<PortConflict>> {
+    let own_names: Vec<&str> = package
+        .containers
+        .iter()
+        .map(|container| container.name.as_str())
+        .collect();
+    let published = published_ports(docker).await?;
+
+    let mut conflicts = Vec::new();
+    let mut claimed: HashMap<(String, u16, String), &str> = HashMap::new();
+    for container in &package.containers {
+        let mut bindings: Vec<_> = container.port_bindings.iter().collect();
+        bindings.sort_by(|(left, _), (right, _)| left.cmp(right));
+        for (container_port, port_bindings) in bindings {
+            let protocol = container_port
+                .split_once('/')
+                .map_or("tcp", |(_, protocol)| protocol);
+            for binding in port_bindings {
+                let Some(host_port) = binding
+                    .host_port
+                    .as_deref()
+                    .and_then(|port| port.parse::<u16>().ok())
+                else {
+                    continue;
+                };
+                let host_ip = binding
+                    .host_ip
+                    .clone()
+                    .filter(|ip| !ip.is_empty())
+                    .unwrap_or_else(|| "0.0.0.0".to_string());
+
+                let claimed_by = claimed.iter().find(|((ip, port, proto), _)| {
+                    *port == host_port && proto == protocol && addresses_overlap(ip, &host_ip)
+                });
+                let holder = match (
+                    claimed_by,
+                    find_publisher(&published, &host_ip, host_port, protocol),
+                ) {
+                    (Some((_, name)), _) => Some(PortHolder::Container {
+                        name: (*name).to_string(),
+                    }),
+                    // Docker holds the sockets of published ports itself, so a port published by
+                    // one of the package's own containers must not be probed on the host either.
+                    (None, Some(publisher))
+                        if own_names.contains(&publisher.container.as_str()) =>
+                    {
+                        None
+                    }
+                    (None, Some(publisher)) => Some(PortHolder::Container {
+                        name: publisher.container.clone(),
+                    }),
+                    (None, None) => host_socket_holder(&host_ip, host_port, protocol),
+                };
+                claimed.insert(
+                    (host_ip.clone(), host_port, protocol.to_string()),
+                    container.name.as_str(),
+                );
+
+                if let Some(holder) = holder {
+                    conflicts.push(PortConflict {
+                        container: container.name.clone(),
+                        host_ip,
+                        host_port,
+                        protocol: protocol.to_string(),
+                        holder,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(conflicts)
+}
+
+async fn published_ports(docker: &Docker) -> Result<Vec<PublishedPort>> {
+    let options = ListContainersOptionsBuilder::default().all(false).build();
+    let mut published = Vec::new();
+    for summary in docker.list_containers(Some(options)).await? {
+        let Some(name) = summary
+            .names
+            .as_ref()
+            .and_then(|names| names.first())
+            .map(|name| name.trim_start_matches('/').to_string())
+        else {
+            continue;
+        };
+        for port in summary.ports.unwrap_or_default() {
+            let Some(host_port) = port.public_port else {
+                continue;
+            };
+            published.push(PublishedPort {
+                container: name.clone(),
+                host_ip: port.ip.unwrap_or_else(|| "0.0.0.0".to_string()),
+                host_port,
+                protocol: port
+                    .typ
+                    .map(|typ| typ.to_string())
+                    .filter(|typ| !typ.is_empty())
+                    .unwrap_or_else(|| "tcp".to_string()),
+            });
+        }
+    }
+    Ok(published)
+}
+
+fn find_publisher<'a>(
+    published: &'a [PublishedPort],
+    host_ip: &str,
+    host_port: u16,
+    protocol: &str,
+) -> Option<&'a PublishedPort> {
+    published.iter().find(|port| {
+        port.host_port == host_port
+            && port.protocol == protocol
+            && addresses_overlap(&port.host_ip, host_ip)
+    })
+}
+
+/// Tries to bind the port on the host and identifies the owner if the address is in use.
+fn host_socket_holder(host_ip: &str, host_port: u16, protocol: &str) -> Option<PortHolder> {
+    let address = (host_ip, host_port);
+    let result = match protocol {
+        "udp" => UdpSocket::bind(address).map(drop),
+        _ => TcpListener::bind(address).map(drop),
+    };
+    match result {
+        Err(error) if error.kind() == ErrorKind::AddrInUse => {
+            Some(socket_owner(host_port, protocol).unwrap_or(PortHolder::Unknown))
+        }
+        _ => None,
+    }
+}
+
+/// Returns true when binding both addresses would collide: they are equal or either is a
+/// wildcard address.
+fn addresses_overlap(left: &str, right: &str) -> bool {
+    let is_wildcard = |ip: &str| matches!(ip, "" | "0.0.0.0" | "::");
+    is_wildcard(left) || is_wildcard(right) || left == right
+}
+
+/// Finds the process that owns a listening socket by matching `/proc/net` entries against the
+/// socket descriptors in `/proc/<pid>/fd`.
+#[cfg(target_os = "linux")]
+fn socket_owner(port: u16, protocol: &str) -> Option<PortHolder> {
+    use std::fs;
+
+    let inodes: Vec<u64> = ["", "6"]
+        .iter()
+        .filter_map(|suffix| fs::read_to_string(format!("/proc/net/{protocol}{suffix}")).ok())
+        .flat_map(|table| listening_inodes(&table, port, protocol))
+        .collect();
+    if inodes.is_empty() {
+        return None;
+    }
+    let targets: Vec<String> = inodes
+        .iter()
+        .map(|inode| format!("socket:[{inode}]"))
+        .collect();
+
+    for entry in fs::read_dir("/proc").ok()?.filter_map(|entry| entry.ok()) {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let Ok(descriptors) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let owns_socket = descriptors.filter_map(|fd| fd.ok()).any(|fd| {
+            fs::read_link(fd.path()).is_ok_and(|link| {
+                targets
+                    .iter()
+                    .any(|target| link.as_os_str() == target.as_str())
+            })
+        });
+        if owns_socket {
+            let name = fs::read_to_string(entry.path().join("comm"))
+                .map(|name| name.trim().to_string())
+                .unwrap_or_else(|_| "unknown".to_string());
+            return Some(PortHolder::Process { pid, name });
+        }
+    }
+    None
+}
+
+#[cfg(not(target_os = "linux"))]
+fn socket_owner(_port: u16, _protocol: &str) -> Option<PortHolder> {
+    None
+}
+
+/// Returns the socket inodes in a `/proc/net/{tcp,udp}` table that are bound to `port`.
+///
+/// TCP sockets only count while listening; UDP sockets have no listening state.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn listening_inodes(table: &str, port: u16, protocol: &str) -> Vec<u64> {
+    const TCP_LISTEN: &str = "0A";
+
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let local_port = fields.get(1)?.rsplit_once(':')?.1;
+            let state = fields.get(3)?;
+            let inode = fields.get(9)?.parse::<u64>().ok()?;
+            let bound = u16::from_str_radix(local_port, 16).ok()? == port;
+            let listening = protocol != "tcp" || *state == TCP_LISTEN;
+            (bound && listening && inode != 0).then_some(inode)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PublishedPort, addresses_overlap, find_publisher, listening_inodes};
+
+    const TCP_TABLE: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:765F 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:765F 0100007F:9C40 01 00000000:00000000 00:00000000 00000000  1000        0 41299 1 0000000000000000 20 4 30 10 -1
+   2: 00000000:13BC 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 40001 1 0000000000000000 100 0 0 10 0
+";
+
+    #[test]
+    fn listening_inodes_only_matches_listening_tcp_sockets_on_the_port() {
+        assert_eq!(listening_inodes(TCP_TABLE, 30303, "tcp"), vec![41234]);
+        assert_eq!(listening_inodes(TCP_TABLE, 5052, "tcp"), vec![40001]);
+        assert!(listening_inodes(TCP_TABLE, 9000, "tcp").is_empty());
+        assert_eq!(
+            listening_inodes(TCP_TABLE, 30303, "udp"),
+            vec![41234, 41299]
+        );
+    }
+
+    #[test]
+    fn addresses_overlap_treats_wildcards_as_every_address() {
+        assert!(addresses_overlap("0.0.0.0", "127.0.0.1"));
+        assert!(addresses_overlap("192.168.1.5", "::"));
+        assert!(addresses_overlap("127.0.0.1", "127.0.0.1"));
+        assert!(!addresses_overlap("127.0.0.1", "192.168.1.5"));
+    }
+
+    #[test]
+    fn find_publisher_matches_port_protocol_and_address() {
+        let published = vec![PublishedPort {
+            container: "other-node".to_string(),
+            host_ip: "0.0.0.0".to_string(),
+            host_port: 9000,
+            protocol: "udp".to_string(),
+        }];
+
+        let publisher = find_publisher(&published, "127.0.0.1", 9000, "udp").expect("publisher");
+        assert_eq!(publisher.container, "other-node");
+        assert!(find_publisher(&published, "0.0.0.0", 9000, "tcp").is_none());
+        assert!(find_publisher(&published, "0.0.0.0", 9001, "udp").is_none());
+    }
+}
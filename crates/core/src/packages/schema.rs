@@ -0,0 +1,451 @@
+use crate::packages::PackageConfig;
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fmt, net::IpAddr};
+use url::Url;
+
+/// The type a package configuration value must parse as.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigFieldKind {
+    Bool,
+    Url,
+    Address,
+    Enum,
+    Integer,
+    /// A positive decimal number, such as a CPU count.
+    Number,
+    String,
+    #[serde(alias = "image_tag")]
+    ImageTag,
+    #[serde(alias = "image_digest")]
+    ImageDigest,
+    Port,
+    #[serde(alias = "ip_address")]
+    IpAddress,
+    /// Command-line arguments separated by whitespace, with single or double quotes grouping words.
+    Arguments,
+}
+
+/// Describes a single key a package accepts in its configuration.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigField {
+    pub(crate) key: String,
+    pub(crate) kind: ConfigFieldKind,
+    pub(crate) default: Option<String>,
+    pub(crate) required: bool,
+    pub(crate) description: String,
+    /// Allowed values when `kind` is [`ConfigFieldKind::Enum`].
+    pub(crate) values: Vec<String>,
+}
+
+impl ConfigField {
+    pub(crate) fn new(key: &str, kind: ConfigFieldKind, description: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            kind,
+            default: None,
+            required: false,
+            description: description.to_string(),
+            values: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_default(mut self, default: &str) -> Self {
+        self.default = Some(default.to_string());
+        self
+    }
+
+    pub(crate) fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    pub(crate) fn with_values<'a>(mut self, values: impl IntoIterator<Item = &'a str>) -> Self {
+        self.values = values.into_iter().map(str::to_string).collect();
+        self
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn kind(&self) -> ConfigFieldKind {
+        self.kind
+    }
+
+    pub fn default(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    pub(crate) fn check_value(&self, value: &str) -> Result<(), String> {
+        match self.kind {
+            ConfigFieldKind::Bool => value
+                .parse::<bool>()
+                .map(|_| ())
+                .map_err(|_| "expected 'true' or 'false'".to_string()),
+            ConfigFieldKind::Integer => value
+                .parse::<i64>()
+                .map(|_| ())
+                .map_err(|_| "expected a whole number".to_string()),
+            ConfigFieldKind::Number => match value.parse::<f64>() {
+                Ok(number) if number.is_finite() && number > 0.0 => Ok(()),
+                _ => Err("expected a positive number such as '1.5'".to_string()),
+            },
+            ConfigFieldKind::Address => value
+                .parse::<Address>()
+                .map(|_| ())
+                .map_err(|_| "expected a 0x-prefixed 20-byte hex address".to_string()),
+            ConfigFieldKind::Url => {
+                let url = Url::parse(value).map_err(|error| format!("invalid URL: {error}"))?;
+                if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+                    return Err("expected an http or https URL with a host".to_string());
+                }
+                Ok(())
+            }
+            ConfigFieldKind::Enum => {
+                if self.values.iter().any(|allowed| allowed == value) {
+                    Ok(())
+                } else {
+                    Err(format!("expected one of: {}", self.values.join(", ")))
+                }
+            }
+            ConfigFieldKind::String => Ok(()),
+            ConfigFieldKind::ImageTag => {
+                if is_valid_image_tag(value) {
+                    Ok(())
+                } else {
+                    Err("expected a Docker image tag such as 'v1.2.3'".to_string())
+                }
+            }
+            ConfigFieldKind::ImageDigest => {
+                if is_valid_image_digest(value) {
+                    Ok(())
+                } else {
+                    Err("expected 'sha256:' followed by 64 hex characters".to_string())
+                }
+            }
+            ConfigFieldKind::Port => match value.parse::<u16>() {
+                Ok(port) if port != 0 => Ok(()),
+                _ => Err("expected a port between 1 and 65535".to_string()),
+            },
+            ConfigFieldKind::IpAddress => value
+                .parse::<IpAddr>()
+                .map(|_| ())
+                .map_err(|_| "expected an IPv4 or IPv6 address".to_string()),
+            ConfigFieldKind::Arguments => split_arguments(value).map(|_| ()),
+        }
+    }
+}
+
+/// Splits a command-line string into arguments the way a POSIX shell would, without expansion.
+///
+/// Whitespace separates arguments, single quotes keep their contents verbatim, double quotes
+/// group words while allowing `\"` and `\\` escapes, and a backslash outside quotes escapes the
+/// next character.
+pub(crate) fn split_arguments(value: &str) -> Result<Vec<String>, String> {
+    let mut arguments = Vec::new();
+    let mut current = String::new();
+    let mut in_argument = false;
+    let mut chars = value.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            ch if ch.is_whitespace() => {
+                if in_argument {
+                    arguments.push(std::mem::take(&mut current));
+                    in_argument = false;
+                }
+            }
+            '\'' => {
+                in_argument = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(quoted) => current.push(quoted),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_argument = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '\\')) => current.push(escaped),
+                            Some(other) => {
+                                current.push('\\');
+                                current.push(other);
+                            }
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(quoted) => current.push(quoted),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_argument = true;
+                match chars.next() {
+                    Some(escaped) => current.push(escaped),
+                    None => return Err("trailing backslash".to_string()),
+                }
+            }
+            other => {
+                in_argument = true;
+                current.push(other);
+            }
+        }
+    }
+    if in_argument {
+        arguments.push(current);
+    }
+    Ok(arguments)
+}
+
+/// Matches Docker's tag grammar: `[A-Za-z0-9_][A-Za-z0-9_.-]{0,127}`.
+pub(crate) fn is_valid_image_tag(tag: &str) -> bool {
+    let mut chars = tag.chars();
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        && tag.len() <= 128
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '.' | '-'))
+}
+
+pub(crate) fn is_valid_image_digest(digest: &str) -> bool {
+    digest.strip_prefix("sha256:").is_some_and(|hex| {
+        hex.len() == 64
+            && hex
+                .chars()
+                .all(|ch| ch.is_ascii_digit() || ('a'..='f').contains(&ch))
+    })
+}
+
+/// Returned when configuration values do not match a package's schema.
+///
+/// Callers can downcast an `eyre::Report` to this type to tell bad input apart from runtime
+/// failures.
+#[derive(Debug)]
+pub struct ConfigValidationError {
+    package_name: String,
+    problems: Vec<String>,
+}
+
+impl ConfigValidationError {
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+}
+
+impl fmt::Display for ConfigValidationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "Invalid configuration for package '{}': {}",
+            self.package_name,
+            self.problems.join("; ")
+        )
+    }
+}
+
+impl Error for ConfigValidationError {}
+
+impl PackageConfig {
+    /// Returns the defaults declared by a schema.
+    pub(crate) fn from_schema_defaults(schema: &[ConfigField]) -> Self {
+        let mut config = Self::new();
+        for field in schema {
+            if let Some(default) = &field.default {
+                config.values.insert(field.key.clone(), default.clone());
+            }
+        }
+        config
+    }
+}
+
+/// Validates an update against a package schema.
+///
+/// Every key in `update` must be declared and parse as its declared type; empty values clear
+/// optional keys. Required keys are checked against `merged`, the configuration that would be
+/// saved after applying the update.
+pub(crate) fn validate_config_update(
+    package_name: &str,
+    schema: &[ConfigField],
+    update: &PackageConfig,
+    merged: &PackageConfig,
+) -> Result<(), ConfigValidationError> {
+    let mut problems = Vec::new();
+
+    let mut keys: Vec<&String> = update.values.keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = &update.values[key];
+        let Some(field) = schema.iter().find(|field| &field.key == key) else {
+            problems.push(format!("unknown key '{key}'"));
+            continue;
+        };
+        if value.is_empty() && !field.required {
+            continue;
+        }
+        if let Err(reason) = field.check_value(value) {
+            problems.push(format!("invalid value '{value}' for '{key}': {reason}"));
+        }
+    }
+
+    for field in schema.iter().filter(|field| field.required) {
+        let has_value = merged
+            .values
+            .get(&field.key)
+            .or(field.default.as_ref())
+            .is_some_and(|value| !value.is_empty());
+        if !has_value {
+            problems.push(format!("missing required key '{}'", field.key));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigValidationError {
+            package_name: package_name.to_string(),
+            problems,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigField, ConfigFieldKind, split_arguments, validate_config_update};
+    use crate::packages::PackageConfig;
+
+    fn schema() -> Vec<ConfigField> {
+        vec![
+            ConfigField::new("network", ConfigFieldKind::Enum, "Network")
+                .with_values(["hoodi", "mainnet"])
+                .required(),
+            ConfigField::new("enabled", ConfigFieldKind::Bool, "Toggle").with_default("false"),
+            ConfigField::new("endpoint", ConfigFieldKind::Url, "Endpoint"),
+            ConfigField::new("recipient", ConfigFieldKind::Address, "Recipient"),
+            ConfigField::new("peers", ConfigFieldKind::Integer, "Peers"),
+            ConfigField::new("version", ConfigFieldKind::ImageTag, "Version"),
+            ConfigField::new("digest", ConfigFieldKind::ImageDigest, "Digest"),
+            ConfigField::new("port", ConfigFieldKind::Port, "Port"),
+            ConfigField::new("address", ConfigFieldKind::IpAddress, "Address"),
+            ConfigField::new("cpus", ConfigFieldKind::Number, "CPUs"),
+            ConfigField::new("args", ConfigFieldKind::Arguments, "Arguments"),
+        ]
+    }
+
+    fn config(values: &[(&str, &str)]) -> PackageConfig {
+        PackageConfig {
+            values: values
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_well_typed_values() {
+        let update = config(&[
+            ("network", "hoodi"),
+            ("enabled", "true"),
+            ("endpoint", "http://127.0.0.1:8545"),
+            ("recipient", "0x00000000219ab540356cBB839Cbe05303d7705Fa"),
+            ("peers", "50"),
+            ("version", "v1.9.3"),
+            ("digest", &format!("sha256:{}", "0f".repeat(32))),
+            ("port", "30303"),
+            ("address", "::1"),
+            ("cpus", "1.5"),
+            ("args", "--log.stdout.format json"),
+        ]);
+        validate_config_update("test", &schema(), &update, &update).expect("should validate");
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_bad_values() {
+        let update = config(&[
+            ("network", "goerli"),
+            ("enabled", "yes"),
+            ("endpoint", "ftp://example.com"),
+            ("recipient", "0x1234"),
+            ("peers", "many"),
+            ("version", "v1:latest"),
+            ("digest", "sha256:1234"),
+            ("port", "0"),
+            ("address", "localhost"),
+            ("cpus", "-2"),
+            ("args", "--name 'unclosed"),
+            ("typo", "1"),
+        ]);
+        let error =
+            validate_config_update("test", &schema(), &update, &update).expect_err("should reject");
+        assert_eq!(error.problems().len(), 12, "{error}");
+        assert!(error.to_string().contains("unknown key 'typo'"));
+    }
+
+    #[test]
+    fn empty_values_clear_optional_keys() {
+        let update = config(&[("endpoint", "")]);
+        let merged = config(&[("network", "mainnet"), ("endpoint", "")]);
+        validate_config_update("test", &schema(), &update, &merged).expect("should validate");
+    }
+
+    #[test]
+    fn required_keys_are_checked_against_merged_config() {
+        let update = config(&[("enabled", "true")]);
+        let error = validate_config_update("test", &schema(), &update, &update)
+            .expect_err("network is required");
+        assert!(error.to_string().contains("missing required key 'network'"));
+
+        let merged = config(&[("network", "hoodi"), ("enabled", "true")]);
+        validate_config_update("test", &schema(), &update, &merged).expect("should validate");
+    }
+
+    #[test]
+    fn default_config_contains_declared_defaults() {
+        let defaults = PackageConfig::from_schema_defaults(&schema());
+        assert_eq!(defaults.values.len(), 1);
+        assert_eq!(
+            defaults.values.get("enabled").map(String::as_str),
+            Some("false")
+        );
+    }
+
+    #[test]
+    fn split_arguments_handles_quotes_and_escapes() {
+        assert_eq!(
+            split_arguments(r#"  --graffiti 'hello world' --name "a \"b\"" c\ d  "#),
+            Ok(vec![
+                "--graffiti".to_string(),
+                "hello world".to_string(),
+                "--name".to_string(),
+                r#"a "b""#.to_string(),
+                "c d".to_string(),
+            ])
+        );
+        assert_eq!(
+            split_arguments("--empty ''"),
+            Ok(vec!["--empty".to_string(), String::new()])
+        );
+        assert_eq!(split_arguments("   "), Ok(Vec::new()));
+        assert!(split_arguments(r#"--name "open"#).is_err());
+        assert!(split_arguments("trailing\\").is_err());
+    }
+}
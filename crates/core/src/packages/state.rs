@@ -1,8 +1,12 @@
-use crate::docker::{container_is_running, find_container, get_docker_instance};
+use crate::docker::{container_is_running, find_container, get_docker_instance, image_repo_digest};
 use crate::packages::catalog::get_package_catalog;
-use crate::packages::{InstallStatus, Package, PackageConfigStore, PackageState, RuntimeStatus};
+use crate::packages::health::check_container_health;
+use crate::packages::{
+    Container, ContainerState, InstallStatus, Package, PackageConfigStore, PackageState,
+    RuntimeStatus,
+};
 use crate::paths::kittynode_path;
-use bollard::Docker;
+use bollard::{Docker, secret::ContainerSummary};
 use eyre::{Context, Result};
 use std::path::Path;
 use tracing::warn;
@@ -63,6 +67,7 @@ async fn get_package_with(docker: &Docker, package: &Package) -> Result<PackageS
     let total = package.containers.len();
     let mut missing = Vec::new();
     let mut running_count = 0usize;
+    let mut containers = Vec::new();
 
     for container in &package.containers {
         let summaries = find_container(docker, &container.name).await?;
@@ -73,6 +78,7 @@ async fn get_package_with(docker: &Docker, package: &Package) -> Result<PackageS
         if summaries.iter().any(container_is_running) {
             running_count += 1;
         }
+        containers.push(describe_container(docker, container, &summaries).await);
     }
 
     let install = if total == 0 {
@@ -102,9 +108,47 @@ async fn get_package_with(docker: &Docker, package: &Package) -> Result<PackageS
         runtime,
         config_present,
         missing_containers: missing,
+        containers,
     })
 }
 
+async fn describe_container(
+    docker: &Docker,
+    container: &Container,
+    summaries: &[ContainerSummary],
+) -> ContainerState {
+    let summary = summaries
+        .iter()
+        .find(|summary| container_is_running(summary))
+        .or_else(|| summaries.first());
+    let image_id = summary.and_then(|summary| summary.image_id.clone());
+    let image_digest = match &image_id {
+        Some(image_id) => image_repo_digest(docker, image_id, &container.image)
+            .await
+            .unwrap_or_else(|error| {
+                warn!(
+                    "Failed to inspect image for container '{}': {error}",
+                    container.name
+                );
+                None
+            }),
+        None => None,
+    };
+
+    let running = summary.is_some_and(container_is_running);
+    let health = check_container_health(docker, container, running).await;
+
+    ContainerState {
+        name: container.name.clone(),
+        running,
+        expected_image: container.image_reference(),
+        image: summary.and_then(|summary| summary.image.clone()),
+        image_id,
+        image_digest,
+        health,
+    }
+}
+
 fn get_package_without_docker(package: &Package) -> Result<PackageState> {
     let base = kittynode_path()?;
     get_package_without_docker_at(&base, package)
@@ -125,6 +169,7 @@ fn get_package_without_docker_at(base: &Path, package: &Package) -> Result<Packa
         runtime: RuntimeStatus::NotRunning,
         config_present,
         missing_containers: Vec::new(),
+        containers: Vec::new(),
     })
 }
 
@@ -148,6 +193,8 @@ mod tests {
             network_name: "test".to_string(),
             containers: Vec::new(),
             default_config: PackageConfig::default(),
+            config_schema: Vec::new(),
+            manifest_path: None,
         };
 
         let state =
@@ -172,6 +219,8 @@ mod tests {
             network_name: "test".to_string(),
             containers: Vec::new(),
             default_config: PackageConfig::default(),
+            config_schema: Vec::new(),
+            manifest_path: None,
         };
 
         let state =
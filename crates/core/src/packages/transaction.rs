@@ -0,0 +1,242 @@
+use crate::docker::{get_docker_instance, remove_container};
+use crate::ethereum::{Ethereum, ephemery_dir};
+use crate::packages::lifecycle::{remove_bound_directories, remove_bound_files, remove_network};
+use crate::packages::{PackageConfigStore, PackageDefinition, split_instance_name};
+use crate::paths::kittynode_path;
+use eyre::{Report, Result};
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+};
+use tracing::{error, info, warn};
+
+/// A resource created by an install that has to be undone if a later step fails.
+enum Resource {
+    JwtSecret(PathBuf),
+    EphemeryCache(PathBuf),
+    Network(String),
+    Container(String),
+}
+
+impl Resource {
+    fn path(&self) -> Option<&Path> {
+        match self {
+            Self::JwtSecret(path) | Self::EphemeryCache(path) => Some(path),
+            Self::Network(_) | Self::Container(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::JwtSecret(path) => write!(formatter, "JWT secret '{}'", path.display()),
+            Self::EphemeryCache(path) => {
+                write!(formatter, "Ephemery cache '{}'", path.display())
+            }
+            Self::Network(name) => write!(formatter, "network '{name}'"),
+            Self::Container(name) => write!(formatter, "container '{name}'"),
+        }
+    }
+}
+
+/// Records what an install creates so that a failure can undo it in reverse order.
+///
+/// Generated files only count as created when they did not exist when the transaction began, so a
+/// failed reinstall never deletes a JWT secret or Ephemery cache that an earlier install made.
+pub(crate) struct InstallTransaction {
+    package_name: String,
+    config_root: PathBuf,
+    step: String,
+    created: Vec<Resource>,
+    preexisting_files: HashSet<PathBuf>,
+}
+
+impl InstallTransaction {
+    pub(crate) fn begin(package_name: &str) -> Result<Self> {
+        Ok(Self::begin_at(kittynode_path()?, package_name))
+    }
+
+    fn begin_at(config_root: PathBuf, package_name: &str) -> Self {
+        let mut transaction = Self {
+            package_name: package_name.to_string(),
+            config_root,
+            step: "starting the install".to_string(),
+            created: Vec::new(),
+            preexisting_files: HashSet::new(),
+        };
+        for resource in transaction.generated_files() {
+            if let Some(path) = resource.path()
+                && path.exists()
+            {
+                transaction.preexisting_files.insert(path.to_path_buf());
+            }
+        }
+        transaction
+    }
+
+    /// Names the step that is about to run; a failure is reported against the latest step.
+    pub(crate) fn step(&mut self, description: impl Into<String>) {
+        self.step = description.into();
+    }
+
+    pub(crate) fn record_network(&mut self, name: &str) {
+        self.created.push(Resource::Network(name.to_string()));
+    }
+
+    pub(crate) fn record_container(&mut self, name: &str) {
+        self.created.push(Resource::Container(name.to_string()));
+    }
+
+    /// Records the JWT secret and Ephemery cache if they appeared since the transaction began.
+    pub(crate) fn record_generated_files(&mut self) {
+        for resource in self.generated_files() {
+            let Some(path) = resource.path() else {
+                continue;
+            };
+            let already_recorded = self
+                .created
+                .iter()
+                .any(|created| created.path() == Some(path));
+            if path.exists() && !self.preexisting_files.contains(path) && !already_recorded {
+                self.created.push(resource);
+            }
+        }
+    }
+
+    /// Undoes every recorded resource in reverse order and describes the failure.
+    ///
+    /// The returned report keeps `error` as its source and names the failed step together with
+    /// what was rolled back and anything that could not be. When nothing was created yet, `error`
+    /// is returned unchanged.
+    pub(crate) async fn roll_back(self, error: Report) -> Report {
+        if self.created.is_empty() {
+            return error;
+        }
+
+        warn!(
+            "Install of '{}' failed while {}; rolling back",
+            self.package_name, self.step
+        );
+
+        let mut undone = Vec::new();
+        let mut failures = Vec::new();
+        let needs_docker = self
+            .created
+            .iter()
+            .any(|resource| matches!(resource, Resource::Network(_) | Resource::Container(_)));
+        let docker = if needs_docker {
+            match get_docker_instance().await {
+                Ok(docker) => Some(docker),
+                Err(docker_error) => {
+                    failures.push(format!("could not connect to Docker: {docker_error}"));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        for resource in self.created.iter().rev() {
+            info!("Rolling back {resource}...");
+            let result = match (resource, &docker) {
+                (Resource::Container(name), Some(docker)) => remove_container(docker, name).await,
+                (Resource::Network(name), Some(docker)) => remove_network(docker, name).await,
+                (Resource::Container(_) | Resource::Network(_), None) => continue,
+                (Resource::JwtSecret(path), _) => {
+                    remove_bound_files(&HashSet::from([path.to_string_lossy().as_ref()]))
+                }
+                (Resource::EphemeryCache(path), _) => {
+                    remove_bound_directories(&HashSet::from([path.to_string_lossy().as_ref()]))
+                }
+            };
+            match result {
+                Ok(()) => undone.push(format!("removed {resource}")),
+                Err(undo_error) => {
+                    error!("Failed to roll back {resource}: {undo_error}");
+                    failures.push(format!("{resource}: {undo_error}"));
+                }
+            }
+        }
+
+        let mut message = format!("Install failed while {}", self.step);
+        if !undone.is_empty() {
+            message.push_str(&format!("; rolled back: {}", undone.join(", ")));
+        }
+        if !failures.is_empty() {
+            message.push_str(&format!(
+                "; could not roll back: {}. Delete the package to finish cleaning up",
+                failures.join(", ")
+            ));
+        }
+        error.wrap_err(message)
+    }
+
+    fn generated_files(&self) -> Vec<Resource> {
+        let mut files = vec![Resource::JwtSecret(
+            PackageConfigStore::package_dir(&self.config_root, &self.package_name).join("jwt.hex"),
+        )];
+        // Only the Ethereum package downloads Ephemery metadata.
+        if split_instance_name(&self.package_name).0 == Ethereum::NAME {
+            files.push(Resource::EphemeryCache(ephemery_dir(&self.config_root)));
+        }
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InstallTransaction;
+    use crate::ethereum::ephemery_dir;
+    use crate::packages::PackageConfigStore;
+    use eyre::eyre;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn roll_back_removes_generated_files_and_names_the_step() {
+        let temp_dir = tempdir().expect("tempdir");
+        let base_dir = temp_dir.path().join(".config").join("kittynode");
+        let mut transaction = InstallTransaction::begin_at(base_dir.clone(), "ethereum");
+
+        let jwt_path = PackageConfigStore::package_dir(&base_dir, "ethereum").join("jwt.hex");
+        fs::create_dir_all(jwt_path.parent().expect("parent")).expect("create package dir");
+        fs::write(&jwt_path, "secret").expect("write jwt");
+        fs::create_dir_all(ephemery_dir(&base_dir)).expect("create ephemery cache");
+        transaction.record_generated_files();
+        transaction.step("starting container 'kittynode-reth-node'");
+
+        let report = transaction.roll_back(eyre!("pull failed")).await;
+        let message = report.to_string();
+        assert!(
+            message.contains("while starting container 'kittynode-reth-node'"),
+            "{message}"
+        );
+        assert!(message.contains("removed JWT secret"), "{message}");
+        assert!(message.contains("removed Ephemery cache"), "{message}");
+        assert_eq!(
+            report.root_cause().to_string(),
+            "pull failed",
+            "original error should stay in the chain"
+        );
+        assert!(!jwt_path.exists());
+        assert!(!ephemery_dir(&base_dir).exists());
+    }
+
+    #[tokio::test]
+    async fn roll_back_keeps_files_that_existed_before_the_install() {
+        let temp_dir = tempdir().expect("tempdir");
+        let base_dir = temp_dir.path().join(".config").join("kittynode");
+        let jwt_path = PackageConfigStore::package_dir(&base_dir, "ethereum").join("jwt.hex");
+        fs::create_dir_all(jwt_path.parent().expect("parent")).expect("create package dir");
+        fs::write(&jwt_path, "secret").expect("write jwt");
+
+        let mut transaction = InstallTransaction::begin_at(base_dir, "ethereum");
+        transaction.record_generated_files();
+
+        let report = transaction.roll_back(eyre!("network failed")).await;
+        assert_eq!(report.to_string(), "network failed");
+        assert!(jwt_path.exists());
+    }
+}
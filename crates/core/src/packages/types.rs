@@ -1,21 +1,139 @@
+use crate::packages::ConfigField;
 use eyre::Result;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 pub(crate) trait PackageDefinition {
     const NAME: &'static str;
     fn get_package() -> Result<Package>;
+    fn config_schema() -> Vec<ConfigField>;
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Container {
     pub(crate) name: String,
+    /// Image repository without a tag, e.g. `sigp/lighthouse`.
     pub(crate) image: String,
+    pub(crate) tag: String,
+    /// Optional `sha256:<hex>` digest that pins the image regardless of what the tag points to.
+    pub(crate) digest: Option<String>,
+    /// Replaces the image entrypoint when not empty.
+    pub(crate) entrypoint: Vec<String>,
     pub(crate) cmd: Vec<String>,
+    /// Arguments appended to `cmd`, usually taken from package config.
+    pub(crate) extra_args: Vec<String>,
+    /// Environment variables set inside the container.
+    pub(crate) env: HashMap<String, String>,
+    /// Docker labels attached to the container.
+    pub(crate) labels: HashMap<String, String>,
     pub(crate) port_bindings: HashMap<String, Vec<PortBinding>>,
     pub(crate) volume_bindings: Vec<Binding>,
     pub(crate) file_bindings: Vec<Binding>,
+    /// Probe that must pass before containers that depend on this one are started.
+    pub(crate) health_check: Option<HealthCheck>,
+    /// Names of containers in the same package that must be healthy before this one starts.
+    pub(crate) depends_on: Vec<String>,
+    /// What Docker does when the container exits or the host reboots.
+    pub(crate) restart_policy: RestartPolicy,
+    /// Hard memory limit in bytes.
+    pub(crate) memory_limit_bytes: Option<u64>,
+    /// CPU limit in CPUs, e.g. `1.5`.
+    pub(crate) cpu_limit: Option<f64>,
+    pub(crate) ulimits: Vec<Ulimit>,
+    /// Seconds Docker waits after `SIGTERM` before it kills the container.
+    pub(crate) stop_timeout_secs: Option<u64>,
+}
+
+/// Docker restart policy of a container.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    #[default]
+    No,
+    OnFailure,
+    UnlessStopped,
+    Always,
+}
+
+impl RestartPolicy {
+    pub const ALL: [Self; 4] = [Self::No, Self::OnFailure, Self::UnlessStopped, Self::Always];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::No => "no",
+            Self::OnFailure => "on-failure",
+            Self::UnlessStopped => "unless-stopped",
+            Self::Always => "always",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|policy| policy.as_str() == value)
+    }
+}
+
+/// A resource limit set with `setrlimit` inside the container, such as `nofile`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ulimit {
+    pub(crate) name: String,
+    pub(crate) soft: i64,
+    pub(crate) hard: i64,
+}
+
+impl Ulimit {
+    pub(crate) fn new(name: &str, soft: i64, hard: i64) -> Self {
+        Self {
+            name: name.to_string(),
+            soft,
+            hard,
+        }
+    }
+}
+
+/// How Kittynode checks that a container is ready.
+///
+/// HTTP and TCP probes connect from the host, through the published port when there is one and
+/// through the container's address on the package network otherwise. Exec probes run inside the
+/// container and pass when the command exits with status 0.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum HealthProbe {
+    Http { port: u16, path: String },
+    Tcp { port: u16 },
+    Exec { command: Vec<String> },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    pub(crate) probe: HealthProbe,
+    /// Seconds to wait for the probe to pass after the container starts.
+    pub(crate) timeout_secs: u64,
+}
+
+impl HealthCheck {
+    pub(crate) fn new(probe: HealthProbe, timeout_secs: u64) -> Self {
+        Self {
+            probe,
+            timeout_secs,
+        }
+    }
+
+    pub fn probe(&self) -> &HealthProbe {
+        &self.probe
+    }
+
+    pub fn timeout_secs(&self) -> u64 {
+        self.timeout_secs
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -33,10 +151,31 @@ pub struct Binding {
     pub(crate) options: Option<String>,
 }
 
+impl Container {
+    /// Returns the reference used to pull and create the container.
+    ///
+    /// A digest takes precedence over the tag so that a moved tag cannot change the image.
+    pub fn image_reference(&self) -> String {
+        match &self.digest {
+            Some(digest) => format!("{}@{digest}", self.image),
+            None => format!("{}:{}", self.image, self.tag),
+        }
+    }
+
+    /// Returns the full command the container runs: `cmd` followed by `extra_args`.
+    pub fn command(&self) -> Vec<String> {
+        self.cmd.iter().chain(&self.extra_args).cloned().collect()
+    }
+}
+
 impl fmt::Display for Container {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(formatter, "- Name: {}", self.name)?;
-        writeln!(formatter, "  Image: {}", self.image)
+        writeln!(formatter, "  Image: {}:{}", self.image, self.tag)?;
+        if let Some(digest) = &self.digest {
+            writeln!(formatter, "  Digest: {digest}")?;
+        }
+        Ok(())
     }
 }
 
@@ -63,6 +202,10 @@ pub struct Package {
     pub(crate) network_name: String,
     pub(crate) containers: Vec<Container>,
     pub(crate) default_config: PackageConfig,
+    pub(crate) config_schema: Vec<ConfigField>,
+    /// Set when the package was loaded from an on-disk `manifest.toml`.
+    #[serde(skip)]
+    pub(crate) manifest_path: Option<PathBuf>,
 }
 
 impl fmt::Display for Package {
@@ -89,6 +232,14 @@ impl Package {
     pub fn network_name(&self) -> &str {
         &self.network_name
     }
+
+    pub fn config_schema(&self) -> &[ConfigField] {
+        &self.config_schema
+    }
+
+    pub fn manifest_path(&self) -> Option<&Path> {
+        self.manifest_path.as_deref()
+    }
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
@@ -114,22 +265,46 @@ pub struct PackageState {
     pub runtime: RuntimeStatus,
     pub config_present: bool,
     pub missing_containers: Vec<String>,
+    pub containers: Vec<ContainerState>,
+}
+
+/// What Docker reports for one of a package's containers.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerState {
+    pub name: String,
+    pub running: bool,
+    /// The image reference the package expects, including tag or digest.
+    pub expected_image: String,
+    /// The image reference the container was created from.
+    pub image: Option<String>,
+    pub image_id: Option<String>,
+    /// Repository digest of the running image, when Docker knows it.
+    pub image_digest: Option<String>,
+    pub health: ContainerHealth,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ContainerHealth {
+    Healthy,
+    Unhealthy,
+    /// The container does not declare a health probe.
+    NotChecked,
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Binding, Container};
-    use std::collections::HashMap;
+    use super::{Binding, Container, RestartPolicy};
 
     #[test]
     fn display_includes_name_and_image() {
         let container = Container {
             name: "kittynode-test".to_string(),
-            image: "example/image:latest".to_string(),
+            image: "example/image".to_string(),
+            tag: "latest".to_string(),
             cmd: vec!["echo".to_string(), "hi".to_string()],
-            port_bindings: HashMap::new(),
-            volume_bindings: Vec::new(),
-            file_bindings: Vec::new(),
+            ..Default::default()
         };
 
         assert_eq!(
@@ -138,6 +313,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn image_reference_prefers_digest_over_tag() {
+        let mut container = Container {
+            name: "kittynode-test".to_string(),
+            image: "example/image".to_string(),
+            tag: "v1.2.3".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(container.image_reference(), "example/image:v1.2.3");
+
+        container.digest = Some(format!("sha256:{}", "a".repeat(64)));
+        assert_eq!(
+            container.image_reference(),
+            format!("example/image@sha256:{}", "a".repeat(64))
+        );
+    }
+
     #[test]
     fn binding_keeps_fields() {
         let binding = Binding {
@@ -149,4 +341,25 @@ mod tests {
         assert_eq!(binding.destination, "/root/a");
         assert_eq!(binding.options.as_deref(), Some("ro"));
     }
+
+    #[test]
+    fn restart_policy_round_trips_docker_names() {
+        for policy in RestartPolicy::ALL {
+            assert_eq!(RestartPolicy::parse(policy.as_str()), Some(policy));
+        }
+        assert_eq!(RestartPolicy::parse("sometimes"), None);
+    }
+
+    #[test]
+    fn command_appends_extra_args() {
+        let container = Container {
+            cmd: vec!["node".to_string()],
+            extra_args: vec!["--max-outbound-peers".to_string(), "50".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            container.command(),
+            vec!["node", "--max-outbound-peers", "50"]
+        );
+    }
 }
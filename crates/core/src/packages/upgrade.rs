@@ -0,0 +1,336 @@
+use crate::docker::{
+    create_and_start_container, get_docker_instance, image_id, log_pull_progress, pull_image,
+    remove_container, wait_for_container_healthy,
+};
+use crate::packages::catalog::{get_package_by_name, get_package_config_schema};
+use crate::packages::health::{startup_order, wait_until_healthy};
+use crate::packages::schema::validate_config_update;
+use crate::packages::state::get_concrete_package;
+use crate::packages::{
+    ConfigField, ConfigFieldKind, Container, ContainerState, InstallStatus, Package, PackageConfig,
+    PackageConfigStore,
+};
+use bollard::Docker;
+use eyre::{Result, eyre};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// How long an upgraded container without a Docker healthcheck must stay up.
+const UPGRADE_SETTLE_TIME: Duration = Duration::from_secs(15);
+const UPGRADE_HEALTH_TIMEOUT: Duration = Duration::from_secs(120);
+const VERSION_KEY_SUFFIX: &str = "_version";
+const DIGEST_KEY_SUFFIX: &str = "_digest";
+
+/// An image change applied to one container during an upgrade.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerUpgrade {
+    pub name: String,
+    pub from_image: Option<String>,
+    pub to_image: String,
+}
+
+/// A container that was recreated and can be put back on its previous image.
+struct RollbackTarget {
+    container: Container,
+    image: String,
+}
+
+/// Upgrades a package's container images, optionally to the version given by `to`.
+///
+/// `to` is either a bare version, which applies when the package has a single versioned client,
+/// or `client=version`. New images are pulled before anything is touched, then containers are
+/// recreated one at a time. If a recreated container does not become healthy, every container
+/// recreated so far goes back to its previous image and the previous config is restored. Named
+/// volumes are never removed.
+pub(crate) async fn upgrade_package(name: &str, to: Option<&str>) -> Result<Vec<ContainerUpgrade>> {
+    let previous_package = get_package_by_name(name)?;
+    let previous_state = get_concrete_package(&previous_package).await?;
+    if previous_state.install != InstallStatus::Installed {
+        return Err(eyre!(
+            "Package '{name}' is not installed; install it before upgrading"
+        ));
+    }
+
+    let previous_config = PackageConfigStore::load(name)?;
+    if let Some(to) = to {
+        let schema = get_package_config_schema(name)?;
+        let update = version_update(name, &schema, to)?;
+        let mut merged = previous_config.clone();
+        for (key, value) in &update.values {
+            merged.values.insert(key.clone(), value.clone());
+        }
+        validate_config_update(name, &schema, &update, &merged)?;
+        PackageConfigStore::save(name, &merged)?;
+    }
+
+    let result = upgrade_containers(name, &previous_package, &previous_state.containers).await;
+    if result.is_err()
+        && to.is_some()
+        && let Err(restore_error) = PackageConfigStore::save(name, &previous_config)
+    {
+        error!(
+            "Failed to restore configuration for '{name}' after a failed upgrade: {restore_error}"
+        );
+    }
+    result
+}
+
+async fn upgrade_containers(
+    name: &str,
+    previous_package: &Package,
+    previous_states: &[ContainerState],
+) -> Result<Vec<ContainerUpgrade>> {
+    let package = get_package_by_name(name)?;
+    let docker = get_docker_instance().await?;
+
+    // Pull everything up front so a missing tag fails before any container is touched.
+    for container in &package.containers {
+        info!("Pulling image '{}'...", container.image_reference());
+        pull_image(&docker, container, &mut log_pull_progress).await?;
+    }
+
+    let mut upgrades = Vec::new();
+    let mut rollbacks = Vec::new();
+
+    for container in startup_order(&package.containers)? {
+        let target = container.image_reference();
+        let previous = previous_states
+            .iter()
+            .find(|state| state.name == container.name);
+        let target_id = image_id(&docker, &target).await?;
+        if target_id.is_some() && previous.and_then(|state| state.image_id.clone()) == target_id {
+            info!(
+                "Container '{}' already runs '{target}'; skipping",
+                container.name
+            );
+            continue;
+        }
+
+        let rollback = previous.and_then(|state| {
+            let image = rollback_image(container, state)?;
+            let container = previous_package
+                .containers
+                .iter()
+                .find(|previous| previous.name == container.name)
+                .unwrap_or(container)
+                .clone();
+            Some(RollbackTarget { container, image })
+        });
+
+        info!("Recreating container '{}' on '{target}'...", container.name);
+        remove_container(&docker, &container.name).await?;
+        if let Some(rollback) = rollback {
+            rollbacks.push(rollback);
+        }
+
+        let started =
+            create_and_start_container(&docker, container, &target, &package.network_name).await;
+        let healthy = match started {
+            Ok(()) => {
+                match wait_for_container_healthy(
+                    &docker,
+                    &container.name,
+                    UPGRADE_SETTLE_TIME,
+                    UPGRADE_HEALTH_TIMEOUT,
+                )
+                .await
+                {
+                    Ok(()) => wait_until_healthy(&docker, container).await,
+                    Err(error) => Err(error),
+                }
+            }
+            Err(error) => Err(error),
+        };
+
+        if let Err(error) = healthy {
+            warn!(
+                "Container '{}' failed after upgrading to '{target}': {error}",
+                container.name
+            );
+            roll_back(&docker, &rollbacks, &package.network_name).await?;
+            return Err(error.wrap_err(format!(
+                "Upgrade of '{}' to '{target}' failed; containers were rolled back to their previous images",
+                container.name
+            )));
+        }
+
+        info!("Container '{}' upgraded to '{target}'", container.name);
+        upgrades.push(ContainerUpgrade {
+            name: container.name.clone(),
+            from_image: previous.and_then(|state| state.image.clone()),
+            to_image: target,
+        });
+    }
+
+    Ok(upgrades)
+}
+
+/// Prefers the registry digest so that the exact previous image is restored even if its tag moved.
+fn rollback_image(container: &Container, state: &ContainerState) -> Option<String> {
+    state
+        .image_digest
+        .as_ref()
+        .map(|digest| format!("{}@{digest}", container.image))
+        .or_else(|| state.image_id.clone())
+}
+
+async fn roll_back(
+    docker: &Docker,
+    rollbacks: &[RollbackTarget],
+    network_name: &str,
+) -> Result<()> {
+    let mut failures = Vec::new();
+
+    for rollback in rollbacks.iter().rev() {
+        let name = &rollback.container.name;
+        info!("Rolling back container '{name}' to '{}'...", rollback.image);
+        let result = async {
+            remove_container(docker, name).await?;
+            create_and_start_container(docker, &rollback.container, &rollback.image, network_name)
+                .await
+        }
+        .await;
+        if let Err(error) = result {
+            error!("Failed to roll back container '{name}': {error}");
+            failures.push(format!("{name}: {error}"));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(eyre!(
+            "Rollback failed for {}; reinstall the package to recover",
+            failures.join(", ")
+        ))
+    }
+}
+
+/// Translates `version` or `client=version` into config updates for the package's version keys.
+fn version_update(name: &str, schema: &[ConfigField], to: &str) -> Result<PackageConfig> {
+    let clients: Vec<&str> = schema
+        .iter()
+        .filter(|field| field.kind == ConfigFieldKind::ImageTag)
+        .filter_map(|field| field.key.strip_suffix(VERSION_KEY_SUFFIX))
+        .collect();
+
+    let (client, version) = match to.split_once('=') {
+        Some((client, version)) => (client.trim(), version.trim()),
+        None => match clients.as_slice() {
+            [client] => (*client, to.trim()),
+            [] => {
+                return Err(eyre!("Package '{name}' does not have versioned images"));
+            }
+            _ => {
+                return Err(eyre!(
+                    "Package '{name}' runs several clients; use --to <client>=<version> with one of: {}",
+                    clients.join(", ")
+                ));
+            }
+        },
+    };
+
+    if !clients.contains(&client) {
+        return Err(eyre!(
+            "Package '{name}' has no client named '{client}'. Available clients: {}",
+            clients.join(", ")
+        ));
+    }
+
+    let mut update = PackageConfig::new();
+    update
+        .values
+        .insert(format!("{client}{VERSION_KEY_SUFFIX}"), version.to_string());
+    let digest_key = format!("{client}{DIGEST_KEY_SUFFIX}");
+    if schema.iter().any(|field| field.key == digest_key) {
+        // A pinned digest would override the requested tag, so clear it.
+        update.values.insert(digest_key, String::new());
+    }
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rollback_image, version_update};
+    use crate::packages::{
+        ConfigField, ConfigFieldKind, Container, ContainerHealth, ContainerState,
+    };
+
+    fn schema() -> Vec<ConfigField> {
+        vec![
+            ConfigField::new("reth_version", ConfigFieldKind::ImageTag, "Reth"),
+            ConfigField::new("reth_digest", ConfigFieldKind::ImageDigest, "Reth digest"),
+            ConfigField::new(
+                "lighthouse_version",
+                ConfigFieldKind::ImageTag,
+                "Lighthouse",
+            ),
+        ]
+    }
+
+    #[test]
+    fn version_update_targets_named_client_and_clears_digest() {
+        let update = version_update("ethereum", &schema(), "reth=v2.0.0").expect("update");
+        assert_eq!(
+            update.values.get("reth_version").map(String::as_str),
+            Some("v2.0.0")
+        );
+        assert_eq!(
+            update.values.get("reth_digest").map(String::as_str),
+            Some("")
+        );
+        assert_eq!(update.values.len(), 2);
+    }
+
+    #[test]
+    fn version_update_requires_client_when_ambiguous() {
+        let error = version_update("ethereum", &schema(), "v2.0.0").expect_err("ambiguous");
+        assert!(error.to_string().contains("reth, lighthouse"), "{error}");
+        assert!(version_update("ethereum", &schema(), "geth=v1").is_err());
+    }
+
+    #[test]
+    fn version_update_accepts_bare_version_for_single_client() {
+        let schema = vec![ConfigField::new(
+            "grafana_version",
+            ConfigFieldKind::ImageTag,
+            "Grafana",
+        )];
+        let update = version_update("grafana", &schema, "12.3.0").expect("update");
+        assert_eq!(
+            update.values.get("grafana_version").map(String::as_str),
+            Some("12.3.0")
+        );
+    }
+
+    #[test]
+    fn rollback_image_prefers_repo_digest() {
+        let container = Container {
+            name: "kittynode-reth-node".to_string(),
+            image: "ghcr.io/paradigmxyz/reth".to_string(),
+            tag: "v1.9.3".to_string(),
+            ..Default::default()
+        };
+        let mut state = ContainerState {
+            name: container.name.clone(),
+            running: true,
+            expected_image: container.image_reference(),
+            image: Some(container.image_reference()),
+            image_id: Some("sha256:abc".to_string()),
+            image_digest: Some("sha256:def".to_string()),
+            health: ContainerHealth::NotChecked,
+        };
+        assert_eq!(
+            rollback_image(&container, &state).as_deref(),
+            Some("ghcr.io/paradigmxyz/reth@sha256:def")
+        );
+
+        state.image_digest = None;
+        assert_eq!(
+            rollback_image(&container, &state).as_deref(),
+            Some("sha256:abc")
+        );
+    }
+}
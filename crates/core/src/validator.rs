@@ -1,13 +1,33 @@
+#[path = "validator/bls_change.rs"]
+mod bls_change;
 #[path = "validator/deposit.rs"]
 mod deposit;
+#[path = "validator/deposit_verification.rs"]
+mod deposit_verification;
+#[path = "validator/exit.rs"]
+mod exit;
 #[path = "validator/input_validation.rs"]
 mod input_validation;
 #[path = "validator/keygen.rs"]
 mod keygen;
+#[path = "validator/top_up.rs"]
+mod top_up;
 
 pub use crate::ethereum::{
     EPHEMERY_CHECKPOINT_URLS, EPHEMERY_NETWORK_NAME, EphemeryConfig, ensure_ephemery_config,
 };
+pub(crate) use bls_change::bls_withdrawal_credentials;
+pub use bls_change::{
+    BlsToExecutionChange, BlsToExecutionChangeRequest, BlsToExecutionChanges,
+    SignedBlsToExecutionChange, sign_bls_to_execution_changes,
+};
+pub use deposit_verification::{
+    DepositCheck, DepositVerification, DepositVerificationRequest, verify_deposit_data,
+};
+pub use exit::{
+    SignedVoluntaryExit, VoluntaryExit, VoluntaryExitRequest, sign_voluntary_exit,
+    write_signed_voluntary_exit,
+};
 pub use input_validation::{
     normalize_withdrawal_address, parse_deposit_amount_gwei, parse_validator_count,
     validate_endpoint_url, validate_password,
@@ -17,6 +37,7 @@ pub use keygen::swap_active;
 pub use keygen::{
     ValidatorKeygenOutcome, ValidatorKeygenRequest, ValidatorProgress, available_networks,
     check_internet_connectivity, default_withdrawal_address, derive_execution_address,
-    format_eth_from_gwei, generate_validator_files, generate_validator_files_with_progress,
-    resolve_withdrawal_address,
+    derive_validator_pubkeys, format_eth_from_gwei, generate_validator_files,
+    generate_validator_files_with_progress, resolve_withdrawal_address,
 };
+pub use top_up::{TopUpKeys, TopUpOutcome, TopUpRequest, generate_top_up_deposits};
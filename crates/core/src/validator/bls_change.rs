@@ -0,0 +1,295 @@
+use super::deposit::{
+    SignatureBytes, compute_domain, compute_signing_root, hash_concat, hash_tree_root_48,
+    hash_tree_root_u64, quoted_u64,
+};
+use super::keygen::{
+    DEPOSIT_CLI_VERSION, derive_eip2334_secret, load_chain_spec, secs_since_unix_epoch, write_json,
+};
+use alloy_primitives::{Address, B256};
+use bip39::{Language, Mnemonic, Seed as Bip39Seed};
+use eth2_keystore::keypair_from_secret;
+use eyre::{Result, WrapErr, eyre};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use zeroize::Zeroizing;
+
+/// Domain type constant for BLS-to-execution changes, per consensus spec.
+const DOMAIN_BLS_TO_EXECUTION_CHANGE: [u8; 4] = [0x0a, 0x00, 0x00, 0x00];
+
+/// BLS withdrawal prefix byte (0x00), per consensus spec.
+const BLS_WITHDRAWAL_PREFIX: u8 = 0x00;
+
+/// Mirrors the consensus-spec `BLSToExecutionChange` container.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BlsToExecutionChange {
+    #[serde(with = "quoted_u64")]
+    pub validator_index: u64,
+    /// The withdrawal pubkey the validator's 0x00 credentials commit to.
+    pub from_bls_pubkey: String,
+    pub to_execution_address: String,
+}
+
+/// A signed BLS-to-execution change, in the JSON shape the beacon API accepts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedBlsToExecutionChange {
+    pub message: BlsToExecutionChange,
+    pub signature: String,
+}
+
+/// Signed changes together with the chain they were signed for.
+#[derive(Clone, Debug)]
+pub struct BlsToExecutionChanges {
+    pub network: String,
+    pub genesis_validators_root: B256,
+    pub changes: Vec<SignedBlsToExecutionChange>,
+}
+
+pub struct BlsToExecutionChangeRequest {
+    pub mnemonic_phrase: Zeroizing<String>,
+    pub network: String,
+    /// Key index in the mnemonic of the first validator in `validator_indices`; the rest follow
+    /// in order.
+    pub start_index: u32,
+    /// Beacon chain indices of the validators to change.
+    pub validator_indices: Vec<u64>,
+    pub execution_address: Address,
+    /// Genesis validators root reported by a beacon node; see
+    /// `ChainSpec::resolve_genesis_validators_root`.
+    pub genesis_validators_root: Option<B256>,
+}
+
+/// The standard file layout, which adds the chain the change was signed for to each entry.
+#[derive(Serialize)]
+struct BlsToExecutionChangeEntry<'a> {
+    #[serde(flatten)]
+    change: &'a SignedBlsToExecutionChange,
+    metadata: BlsToExecutionChangeMetadata<'a>,
+}
+
+#[derive(Serialize)]
+struct BlsToExecutionChangeMetadata<'a> {
+    network_name: &'a str,
+    genesis_validators_root: String,
+    deposit_cli_version: &'a str,
+}
+
+/// Derives the withdrawal key of each validator from the mnemonic at `m/12381/3600/i/0` and
+/// signs a change of its withdrawal credentials to `execution_address`.
+///
+/// A validator's credentials can only be changed once, so callers should check that the derived
+/// withdrawal pubkeys match the validators' current credentials before submitting the changes.
+///
+/// # Errors
+/// Returns an [`eyre::Report`] when the mnemonic is invalid, when no validator indices are
+/// given, or when the network's genesis validators root is unknown or doesn't match.
+pub fn sign_bls_to_execution_changes(
+    request: BlsToExecutionChangeRequest,
+) -> Result<BlsToExecutionChanges> {
+    let BlsToExecutionChangeRequest {
+        mnemonic_phrase,
+        network,
+        start_index,
+        validator_indices,
+        execution_address,
+        genesis_validators_root,
+    } = request;
+
+    if validator_indices.is_empty() {
+        return Err(eyre!("At least one validator index is required"));
+    }
+    let spec = load_chain_spec(&network)?;
+    let genesis_validators_root =
+        spec.resolve_genesis_validators_root(&network, genesis_validators_root)?;
+    // Unlike exits, credential changes keep signing with the genesis fork version.
+    let domain = compute_domain(
+        DOMAIN_BLS_TO_EXECUTION_CHANGE,
+        spec.genesis_fork_version,
+        genesis_validators_root.into(),
+    );
+
+    let mnemonic = Mnemonic::from_phrase(mnemonic_phrase.as_str(), Language::English)
+        .wrap_err("Mnemonic phrase is invalid")?;
+    let seed = Bip39Seed::new(&mnemonic, "");
+
+    let mut changes = Vec::with_capacity(validator_indices.len());
+    for (offset, validator_index) in validator_indices.into_iter().enumerate() {
+        let key_index = u32::try_from(offset)
+            .ok()
+            .and_then(|offset| start_index.checked_add(offset))
+            .ok_or_else(|| eyre!("Key index overflows past {start_index}"))?;
+        let secret = derive_eip2334_secret(seed.as_bytes(), &[12381, 3600, key_index, 0])?;
+        let keypair = keypair_from_secret(&secret)
+            .map_err(|error| eyre!("Failed to derive withdrawal key {key_index}: {error:?}"))?;
+        let pubkey = keypair.pk.serialize();
+
+        let message_root = change_tree_hash_root(validator_index, &pubkey, execution_address);
+        let signing_root = compute_signing_root(&message_root, &domain);
+        let signature = keypair.sk.sign(B256::from(signing_root));
+        changes.push(SignedBlsToExecutionChange {
+            message: BlsToExecutionChange {
+                validator_index,
+                from_bls_pubkey: format!("0x{}", hex::encode(pubkey)),
+                to_execution_address: format!("{execution_address:#x}"),
+            },
+            signature: format!(
+                "0x{}",
+                hex::encode(SignatureBytes::from(signature).serialize())
+            ),
+        });
+    }
+
+    Ok(BlsToExecutionChanges {
+        network,
+        genesis_validators_root,
+        changes,
+    })
+}
+
+impl BlsToExecutionChanges {
+    /// Writes the changes to `bls_to_execution_change-<timestamp>.json` in `output_dir`.
+    pub fn write(&self, output_dir: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(output_dir)
+            .wrap_err_with(|| format!("Failed to create {}", output_dir.display()))?;
+        let timestamp = secs_since_unix_epoch(SystemTime::now())?;
+        let path = output_dir.join(format!("bls_to_execution_change-{timestamp}.json"));
+        let entries: Vec<_> = self
+            .changes
+            .iter()
+            .map(|change| BlsToExecutionChangeEntry {
+                change,
+                metadata: BlsToExecutionChangeMetadata {
+                    network_name: &self.network,
+                    genesis_validators_root: format!("{:#x}", self.genesis_validators_root),
+                    deposit_cli_version: DEPOSIT_CLI_VERSION,
+                },
+            })
+            .collect();
+        write_json(&path, &entries).wrap_err("Failed to write the BLS-to-execution changes")?;
+        Ok(path)
+    }
+}
+
+/// Returns the 0x00 withdrawal credentials `0x00 || sha256(pubkey)[1..]` a hex-encoded
+/// withdrawal pubkey commits to.
+pub(crate) fn bls_withdrawal_credentials(pubkey: &str) -> Result<String> {
+    let bytes = hex::decode(pubkey.trim_start_matches("0x"))
+        .map_err(|error| eyre!("Invalid withdrawal pubkey {pubkey}: {error}"))?;
+    let mut credentials: [u8; 32] = Sha256::digest(&bytes).into();
+    credentials[0] = BLS_WITHDRAWAL_PREFIX;
+    Ok(format!("0x{}", hex::encode(credentials)))
+}
+
+/// SSZ hash tree root of `BLSToExecutionChange`: 3 fields padded to 4 leaves.
+fn change_tree_hash_root(validator_index: u64, pubkey: &[u8; 48], address: Address) -> [u8; 32] {
+    let leaf0 = hash_tree_root_u64(validator_index);
+    let leaf1 = hash_tree_root_48(pubkey);
+    let mut leaf2 = [0u8; 32]; // Bytes20, zero-padded
+    leaf2[..20].copy_from_slice(address.as_slice());
+    let leaf3 = [0u8; 32]; // padding to next power of 2
+    let h01 = hash_concat(&leaf0, &leaf1);
+    let h23 = hash_concat(&leaf2, &leaf3);
+    hash_concat(&h01, &h23)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls::{PublicKey, Signature};
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn request(validator_indices: Vec<u64>) -> BlsToExecutionChangeRequest {
+        BlsToExecutionChangeRequest {
+            mnemonic_phrase: Zeroizing::new(MNEMONIC.to_string()),
+            network: "hoodi".to_string(),
+            start_index: 2,
+            validator_indices,
+            execution_address: "0x48fe05daea0f8cc6958a72522db42b2edb3fda1a"
+                .parse()
+                .unwrap(),
+            genesis_validators_root: None,
+        }
+    }
+
+    #[test]
+    fn changes_are_signed_by_consecutive_withdrawal_keys() {
+        let signed = sign_bls_to_execution_changes(request(vec![700, 701])).unwrap();
+        assert_eq!(signed.changes.len(), 2);
+
+        let seed = Bip39Seed::new(
+            &Mnemonic::from_phrase(MNEMONIC, Language::English).unwrap(),
+            "",
+        );
+        let domain = compute_domain(
+            DOMAIN_BLS_TO_EXECUTION_CHANGE,
+            [0x10, 0x00, 0x09, 0x10],
+            signed.genesis_validators_root.into(),
+        );
+        for (key_index, change) in (2u32..).zip(&signed.changes) {
+            let secret =
+                derive_eip2334_secret(seed.as_bytes(), &[12381, 3600, key_index, 0]).unwrap();
+            let keypair = keypair_from_secret(&secret).unwrap();
+            let pubkey = keypair.pk.serialize();
+            assert_eq!(
+                change.message.from_bls_pubkey,
+                format!("0x{}", hex::encode(pubkey))
+            );
+
+            let root = change_tree_hash_root(
+                change.message.validator_index,
+                &pubkey,
+                change.message.to_execution_address.parse().unwrap(),
+            );
+            let signature =
+                Signature::deserialize(&hex::decode(&change.signature[2..]).unwrap()).unwrap();
+            let public_key = PublicKey::deserialize(&pubkey).unwrap();
+            assert!(signature.verify(
+                &public_key,
+                B256::from(compute_signing_root(&root, &domain))
+            ));
+        }
+
+        let json = serde_json::to_value(&signed.changes[0]).unwrap();
+        assert_eq!(json["message"]["validator_index"], "700");
+        assert_eq!(
+            json["message"]["to_execution_address"],
+            "0x48fe05daea0f8cc6958a72522db42b2edb3fda1a"
+        );
+    }
+
+    #[test]
+    fn written_file_carries_the_network_metadata() {
+        let signed = sign_bls_to_execution_changes(request(vec![700])).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = signed.write(dir.path()).unwrap();
+        assert!(
+            path.file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("bls_to_execution_change-")
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json[0]["message"]["validator_index"], "700");
+        assert_eq!(json[0]["metadata"]["network_name"], "hoodi");
+        assert_eq!(
+            json[0]["metadata"]["genesis_validators_root"],
+            "0x212f13fc4df078b6cb7db228f1c8307566dcecf900867401a92023d7ba99cb5f"
+        );
+    }
+
+    #[test]
+    fn bls_credentials_replace_the_first_hash_byte() {
+        let pubkey = format!("0x{}", "11".repeat(48));
+        let credentials = bls_withdrawal_credentials(&pubkey).unwrap();
+        let hash = Sha256::digest([0x11u8; 48]);
+        assert_eq!(&credentials[..4], "0x00");
+        assert_eq!(credentials[4..], hex::encode(&hash[1..]));
+        assert!(sign_bls_to_execution_changes(request(Vec::new())).is_err());
+    }
+}
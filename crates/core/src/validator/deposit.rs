@@ -2,15 +2,15 @@
 //!
 //! Replaces the heavy `types` and `eth2_network_config` lighthouse crates with a thin,
 //! self-contained module that implements only what kittynode needs: deposit data construction,
-//! SSZ tree hashing, and BLS signing — all per the Ethereum consensus spec.
+//! SSZ tree hashing, signing domains and BLS signing — all per the Ethereum consensus spec.
 
-use alloy_primitives::{Address, B256};
+use alloy_primitives::{Address, B256, b256};
 use eyre::{Result, eyre};
 use sha2::{Digest, Sha256};
 use std::path::Path;
 
 /// Re-export BLS types from the `bls` crate (already a transitive dep of `eth2_keystore`).
-pub use bls::{PublicKeyBytes, SecretKey, SignatureBytes};
+pub use bls::{PublicKey, PublicKeyBytes, SecretKey, Signature, SignatureBytes};
 
 // ---------------------------------------------------------------------------
 // Network configuration
@@ -19,33 +19,97 @@ pub use bls::{PublicKeyBytes, SecretKey, SignatureBytes};
 /// Minimal chain spec — only the fields kittynode actually uses.
 pub struct ChainSpec {
     pub genesis_fork_version: [u8; 4],
+    /// Fork version voluntary exits are signed with from Deneb on (EIP-7044).
+    pub capella_fork_version: Option<[u8; 4]>,
+    /// Unknown for networks loaded from a config directory that doesn't record it.
+    pub genesis_validators_root: Option<B256>,
     pub config_name: Option<String>,
 }
 
-/// Hardcoded genesis fork versions per network. These are consensus-spec constants.
-const NETWORKS: &[(&str, [u8; 4])] = &[
-    ("mainnet", [0x00, 0x00, 0x00, 0x00]),
-    ("gnosis", [0x00, 0x00, 0x00, 0x64]),
-    ("chiado", [0x00, 0x00, 0x00, 0x6f]),
-    ("sepolia", [0x90, 0x00, 0x00, 0x69]),
-    ("holesky", [0x01, 0x01, 0x70, 0x00]),
-    ("hoodi", [0x10, 0x00, 0x09, 0x10]),
+impl ChainSpec {
+    /// Returns the genesis validators root to sign with.
+    ///
+    /// Exits and BLS-to-execution changes are signed over this root. The root a beacon node
+    /// reports is required for networks whose config doesn't record one, and is checked against
+    /// the known root otherwise so a node on another network can't be used by mistake.
+    ///
+    /// Errors when the root is unknown or the reported root belongs to another network.
+    pub(super) fn resolve_genesis_validators_root(
+        &self,
+        network: &str,
+        reported: Option<B256>,
+    ) -> Result<B256> {
+        match (self.genesis_validators_root, reported) {
+            (Some(known), Some(reported)) if known != reported => Err(eyre!(
+                "The beacon node reports genesis validators root {reported}, but {network} has {known}"
+            )),
+            (Some(root), _) | (None, Some(root)) => Ok(root),
+            (None, None) => Err(eyre!(
+                "The genesis validators root of {network} is unknown; submit to a beacon node instead"
+            )),
+        }
+    }
+}
+
+/// A built-in network: its name, genesis and Capella fork versions, and genesis validators root.
+type NetworkConstants = (&'static str, [u8; 4], [u8; 4], B256);
+
+/// Hardcoded fork versions and genesis validators roots per network. These are consensus-spec
+/// constants.
+const NETWORKS: &[NetworkConstants] = &[
+    (
+        "mainnet",
+        [0x00, 0x00, 0x00, 0x00],
+        [0x03, 0x00, 0x00, 0x00],
+        b256!("4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"),
+    ),
+    (
+        "gnosis",
+        [0x00, 0x00, 0x00, 0x64],
+        [0x03, 0x00, 0x00, 0x64],
+        b256!("f5dcb5564e829aab27264b9becd5dfaa017085611224cb3036f573368dbb9d47"),
+    ),
+    (
+        "chiado",
+        [0x00, 0x00, 0x00, 0x6f],
+        [0x03, 0x00, 0x00, 0x6f],
+        b256!("9d642dac73058fbf39c0ae41ab1e34e4d889043cb199851ded7095bc99eb4c1e"),
+    ),
+    (
+        "sepolia",
+        [0x90, 0x00, 0x00, 0x69],
+        [0x90, 0x00, 0x00, 0x72],
+        b256!("d8ea171f3c94aea21ebc42a1ed61052acf3f9209c00e4efbaaddac09ed9b8078"),
+    ),
+    (
+        "holesky",
+        [0x01, 0x01, 0x70, 0x00],
+        [0x04, 0x01, 0x70, 0x00],
+        b256!("9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1"),
+    ),
+    (
+        "hoodi",
+        [0x10, 0x00, 0x09, 0x10],
+        [0x40, 0x00, 0x09, 0x10],
+        b256!("212f13fc4df078b6cb7db228f1c8307566dcecf900867401a92023d7ba99cb5f"),
+    ),
 ];
 
 /// Returns the list of built-in network names.
 pub fn hardcoded_net_names() -> Vec<&'static str> {
-    NETWORKS.iter().map(|(name, _)| *name).collect()
+    NETWORKS.iter().map(|(name, ..)| *name).collect()
 }
 
 /// Looks up a built-in network by name and returns its [`ChainSpec`].
 pub fn chain_spec_for_network(name: &str) -> Option<ChainSpec> {
-    NETWORKS
-        .iter()
-        .find(|(n, _)| *n == name)
-        .map(|(n, fork_version)| ChainSpec {
-            genesis_fork_version: *fork_version,
+    NETWORKS.iter().find(|(n, ..)| *n == name).map(
+        |(n, genesis_fork_version, capella_fork_version, genesis_validators_root)| ChainSpec {
+            genesis_fork_version: *genesis_fork_version,
+            capella_fork_version: Some(*capella_fork_version),
+            genesis_validators_root: Some(*genesis_validators_root),
             config_name: Some(n.to_string()),
-        })
+        },
+    )
 }
 
 /// Extracts the scalar value from a YAML `KEY: value # comment` line,
@@ -59,9 +123,20 @@ fn yaml_value(raw: &str) -> &str {
     }
 }
 
+/// Parses a `0x`-prefixed 4-byte fork version.
+fn parse_fork_version(key: &str, raw: &str) -> Result<[u8; 4]> {
+    let hex = yaml_value(raw).trim_start_matches("0x");
+    let bytes = hex::decode(hex).map_err(|e| eyre!("Invalid {key} hex: {e}"))?;
+    bytes
+        .try_into()
+        .map_err(|_| eyre!("{key} must be exactly 4 bytes"))
+}
+
 /// Loads a [`ChainSpec`] from a directory containing a `config.yaml` file.
 ///
-/// Only reads `GENESIS_FORK_VERSION` and `CONFIG_NAME` — everything else is ignored.
+/// Only reads `GENESIS_FORK_VERSION`, `CAPELLA_FORK_VERSION` and `CONFIG_NAME` — everything else
+/// is ignored. The genesis validators root comes from `genesis_validators_root.txt` when the
+/// directory has one.
 pub fn chain_spec_from_dir(path: &Path) -> Result<ChainSpec> {
     let config_path = path.join("config.yaml");
     let contents = std::fs::read_to_string(&config_path).map_err(|e| {
@@ -72,27 +147,36 @@ pub fn chain_spec_from_dir(path: &Path) -> Result<ChainSpec> {
     })?;
 
     let mut genesis_fork_version: Option<[u8; 4]> = None;
+    let mut capella_fork_version: Option<[u8; 4]> = None;
     let mut config_name: Option<String> = None;
 
     for line in contents.lines() {
         let line = line.trim();
         if let Some(value) = line.strip_prefix("GENESIS_FORK_VERSION:") {
-            let hex = yaml_value(value).trim_start_matches("0x");
-            let bytes =
-                hex::decode(hex).map_err(|e| eyre!("Invalid GENESIS_FORK_VERSION hex: {e}"))?;
-            genesis_fork_version = Some(
-                bytes
-                    .try_into()
-                    .map_err(|_| eyre!("GENESIS_FORK_VERSION must be exactly 4 bytes"))?,
-            );
+            genesis_fork_version = Some(parse_fork_version("GENESIS_FORK_VERSION", value)?);
+        } else if let Some(value) = line.strip_prefix("CAPELLA_FORK_VERSION:") {
+            capella_fork_version = Some(parse_fork_version("CAPELLA_FORK_VERSION", value)?);
         } else if let Some(value) = line.strip_prefix("CONFIG_NAME:") {
             config_name = Some(yaml_value(value).to_string());
         }
     }
 
+    let root_path = path.join("genesis_validators_root.txt");
+    let genesis_validators_root = match std::fs::read_to_string(&root_path) {
+        Ok(contents) => Some(contents.trim().parse::<B256>().map_err(|e| {
+            eyre!(
+                "Invalid genesis validators root in {}: {e}",
+                root_path.display()
+            )
+        })?),
+        Err(_) => None,
+    };
+
     Ok(ChainSpec {
         genesis_fork_version: genesis_fork_version
             .ok_or_else(|| eyre!("GENESIS_FORK_VERSION not found in config.yaml"))?,
+        capella_fork_version,
+        genesis_validators_root,
         config_name,
     })
 }
@@ -107,6 +191,15 @@ const ETH1_ADDRESS_WITHDRAWAL_PREFIX: u8 = 0x01;
 /// Compounding withdrawal prefix byte (0x02), per Electra spec.
 const COMPOUNDING_WITHDRAWAL_PREFIX: u8 = 0x02;
 
+/// The deposit contract rejects deposits below 1 ETH.
+pub(super) const MIN_DEPOSIT_GWEI: u64 = 1_000_000_000;
+
+/// Balance above which a 0x01 validator's excess is swept, per consensus spec.
+pub(super) const MAX_EFFECTIVE_BALANCE_GWEI: u64 = 32_000_000_000;
+
+/// Balance above which a 0x02 validator's excess is swept, per Electra spec.
+pub(super) const MAX_EFFECTIVE_BALANCE_ELECTRA_GWEI: u64 = 2_048_000_000_000;
+
 /// Builds ETH1 withdrawal credentials: `0x01 || 11_zero_bytes || address`.
 pub fn eth1_withdrawal_credentials(address: Address) -> B256 {
     let mut buf = [0u8; 32];
@@ -128,7 +221,7 @@ pub fn compounding_withdrawal_credentials(address: Address) -> B256 {
 // ---------------------------------------------------------------------------
 
 /// SHA-256 hash of two 32-byte chunks concatenated.
-fn hash_concat(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+pub(super) fn hash_concat(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(a);
     hasher.update(b);
@@ -136,7 +229,7 @@ fn hash_concat(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
 }
 
 /// SSZ hash tree root of a `uint64`: little-endian, zero-padded to 32 bytes.
-fn hash_tree_root_u64(val: u64) -> [u8; 32] {
+pub(super) fn hash_tree_root_u64(val: u64) -> [u8; 32] {
     let mut chunk = [0u8; 32];
     chunk[..8].copy_from_slice(&val.to_le_bytes());
     chunk
@@ -150,7 +243,7 @@ fn hash_tree_root_bytes4(val: &[u8; 4]) -> [u8; 32] {
 }
 
 /// SSZ hash tree root of a 48-byte value (BLS pubkey): split into two 32-byte chunks.
-fn hash_tree_root_48(bytes: &[u8; 48]) -> [u8; 32] {
+pub(super) fn hash_tree_root_48(bytes: &[u8; 48]) -> [u8; 32] {
     let mut chunk0 = [0u8; 32];
     let mut chunk1 = [0u8; 32];
     chunk0.copy_from_slice(&bytes[..32]);
@@ -254,6 +347,21 @@ impl DepositData {
         self.signature = SignatureBytes::from(sig).serialize();
     }
 
+    /// Checks the signature over the `DepositMessage` under the deposit domain of `spec`.
+    ///
+    /// Returns `false` when the pubkey or signature bytes are not valid BLS points.
+    pub fn verify_signature(&self, spec: &ChainSpec) -> bool {
+        let (Ok(pubkey), Ok(signature)) = (
+            PublicKey::deserialize(&self.pubkey),
+            Signature::deserialize(&self.signature),
+        ) else {
+            return false;
+        };
+        let domain = compute_deposit_domain(spec.genesis_fork_version);
+        let signing_root = compute_signing_root(&self.deposit_message_root(), &domain);
+        signature.verify(&pubkey, B256::from(signing_root))
+    }
+
     /// Returns the `DepositMessage` tree hash root (used in deposit data JSON output).
     pub fn deposit_message_root(&self) -> [u8; 32] {
         self.as_deposit_message().tree_hash_root()
@@ -262,33 +370,52 @@ impl DepositData {
 
 /// Computes the deposit domain for a given fork version.
 ///
-/// `domain = DOMAIN_DEPOSIT || fork_data_root[0..28]`
-///
 /// For deposits, `genesis_validators_root` is always zero.
 fn compute_deposit_domain(fork_version: [u8; 4]) -> [u8; 32] {
-    let fork_data_root = compute_fork_data_root(fork_version);
+    compute_domain(DOMAIN_DEPOSIT, fork_version, [0u8; 32])
+}
+
+/// `domain = domain_type || fork_data_root[0..28]`
+pub(super) fn compute_domain(
+    domain_type: [u8; 4],
+    fork_version: [u8; 4],
+    genesis_validators_root: [u8; 32],
+) -> [u8; 32] {
+    let fork_data_root = compute_fork_data_root(fork_version, genesis_validators_root);
     let mut domain = [0u8; 32];
-    domain[..4].copy_from_slice(&DOMAIN_DEPOSIT);
+    domain[..4].copy_from_slice(&domain_type);
     domain[4..].copy_from_slice(&fork_data_root[..28]);
     domain
 }
 
 /// Tree hash root of `ForkData { current_version, genesis_validators_root }`.
-///
-/// For deposits, `genesis_validators_root` is always zero.
-fn compute_fork_data_root(fork_version: [u8; 4]) -> [u8; 32] {
+fn compute_fork_data_root(fork_version: [u8; 4], genesis_validators_root: [u8; 32]) -> [u8; 32] {
     let leaf0 = hash_tree_root_bytes4(&fork_version);
-    let leaf1 = [0u8; 32]; // genesis_validators_root = zero for deposits
-    hash_concat(&leaf0, &leaf1)
+    hash_concat(&leaf0, &genesis_validators_root)
 }
 
 /// `signing_root = hash_tree_root(SigningData { object_root, domain })`
 ///
 /// Both fields are 32 bytes, so this is just `sha256(object_root || domain)`.
-fn compute_signing_root(object_root: &[u8; 32], domain: &[u8; 32]) -> [u8; 32] {
+pub(super) fn compute_signing_root(object_root: &[u8; 32], domain: &[u8; 32]) -> [u8; 32] {
     hash_concat(object_root, domain)
 }
 
+/// The beacon API encodes `uint64` values as decimal strings.
+pub(super) mod quoted_u64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -307,6 +434,15 @@ mod tests {
     fn mainnet_fork_version_is_correct() {
         let spec = chain_spec_for_network("mainnet").unwrap();
         assert_eq!(spec.genesis_fork_version, [0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(spec.capella_fork_version, Some([0x03, 0x00, 0x00, 0x00]));
+        assert_eq!(
+            spec.genesis_validators_root,
+            Some(
+                "0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+                    .parse()
+                    .unwrap()
+            )
+        );
     }
 
     #[test]
@@ -350,6 +486,47 @@ mod tests {
         let spec = chain_spec_from_dir(tmp.path()).unwrap();
         assert_eq!(spec.genesis_fork_version, [0x10, 0x00, 0x09, 0x10]);
         assert_eq!(spec.config_name.as_deref(), Some("testnet"));
+        assert_eq!(spec.capella_fork_version, None);
+        assert_eq!(spec.genesis_validators_root, None);
+    }
+
+    #[test]
+    fn chain_spec_from_dir_reads_capella_fork_and_genesis_validators_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("config.yaml"),
+            "GENESIS_FORK_VERSION: 0x1000101b\nCAPELLA_FORK_VERSION: 0x4000101b\n",
+        )
+        .unwrap();
+        let root = format!("0x{}", "ab".repeat(32));
+        std::fs::write(
+            tmp.path().join("genesis_validators_root.txt"),
+            format!("{root}\n"),
+        )
+        .unwrap();
+        let spec = chain_spec_from_dir(tmp.path()).unwrap();
+        assert_eq!(spec.capella_fork_version, Some([0x40, 0x00, 0x10, 0x1b]));
+        assert_eq!(spec.genesis_validators_root, Some(root.parse().unwrap()));
+    }
+
+    #[test]
+    fn signed_deposit_verifies_only_unchanged_and_on_its_network() {
+        let keypair = bls::Keypair::random();
+        let addr: Address = "0x48fe05daea0f8cc6958a72522db42b2edb3fda1a"
+            .parse()
+            .unwrap();
+        let hoodi = chain_spec_for_network("hoodi").unwrap();
+        let mut deposit = DepositData::new(
+            keypair.pk.serialize(),
+            eth1_withdrawal_credentials(addr),
+            32_000_000_000,
+        );
+        deposit.sign(&keypair.sk, &hoodi);
+        assert!(deposit.verify_signature(&hoodi));
+        assert!(!deposit.verify_signature(&chain_spec_for_network("mainnet").unwrap()));
+
+        deposit.amount = 1_000_000_000;
+        assert!(!deposit.verify_signature(&hoodi));
     }
 
     #[test]
@@ -0,0 +1,306 @@
+use super::deposit::{
+    ChainSpec, DepositData, MIN_DEPOSIT_GWEI, compounding_withdrawal_credentials,
+    eth1_withdrawal_credentials,
+};
+use super::keygen::load_chain_spec;
+use alloy_primitives::{Address, B256};
+use eyre::{Result, WrapErr, eyre};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+pub struct DepositVerificationRequest {
+    pub deposit_data_path: PathBuf,
+    /// Network to verify against; defaults to the `network_name` the deposit file records.
+    pub network: Option<String>,
+    /// Execution address every deposit must withdraw to.
+    pub withdrawal_address: Option<Address>,
+}
+
+/// What verifying each deposit of a deposit data file found.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepositVerification {
+    pub network: String,
+    pub deposits: Vec<DepositCheck>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepositCheck {
+    pub pubkey: String,
+    pub amount_gwei: u64,
+    pub withdrawal_credentials: String,
+    /// Everything wrong with the deposit; empty when it is safe to send.
+    pub problems: Vec<String>,
+}
+
+impl DepositVerification {
+    pub fn is_valid(&self) -> bool {
+        self.deposits
+            .iter()
+            .all(|deposit| deposit.problems.is_empty())
+    }
+}
+
+/// One entry of a `deposit_data-*.json` file, as written by Kittynode and the staking deposit CLI.
+#[derive(Deserialize)]
+struct DepositFileEntry {
+    pubkey: String,
+    withdrawal_credentials: String,
+    amount: u64,
+    signature: String,
+    deposit_message_root: Option<String>,
+    deposit_data_root: Option<String>,
+    fork_version: Option<String>,
+    network_name: Option<String>,
+}
+
+/// Checks every deposit in a deposit data file before it is sent to the deposit contract.
+///
+/// Recomputes the deposit message and deposit data roots, verifies each signature under the
+/// network's genesis fork version, checks the withdrawal credentials against the expected
+/// address and reports pubkeys that appear more than once.
+///
+/// # Errors
+/// Returns an [`eyre::Report`] when the file cannot be read or parsed, or when the network is
+/// neither given nor recorded in the file.
+pub fn verify_deposit_data(request: DepositVerificationRequest) -> Result<DepositVerification> {
+    let DepositVerificationRequest {
+        deposit_data_path,
+        network,
+        withdrawal_address,
+    } = request;
+
+    let contents = fs::read_to_string(&deposit_data_path)
+        .wrap_err_with(|| format!("Failed to read {}", deposit_data_path.display()))?;
+    let entries: Vec<DepositFileEntry> = serde_json::from_str(&contents)
+        .wrap_err_with(|| format!("{} is not a deposit data file", deposit_data_path.display()))?;
+    if entries.is_empty() {
+        return Err(eyre!(
+            "{} contains no deposits",
+            deposit_data_path.display()
+        ));
+    }
+
+    let network = match network {
+        Some(network) => network,
+        None => entries[0].network_name.clone().ok_or_else(|| {
+            eyre!("The deposit file doesn't record its network; pass the network to verify against")
+        })?,
+    };
+    let spec = load_chain_spec(&network)?;
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let deposits = entries
+        .iter()
+        .enumerate()
+        .map(|(position, entry)| {
+            let mut check = check_deposit(entry, &spec, withdrawal_address);
+            match seen.get(&check.pubkey) {
+                Some(first) => check.problems.push(format!(
+                    "Duplicate pubkey, also deposited by entry {}",
+                    first + 1
+                )),
+                None => {
+                    seen.insert(check.pubkey.clone(), position);
+                }
+            }
+            check
+        })
+        .collect();
+
+    Ok(DepositVerification { network, deposits })
+}
+
+fn check_deposit(
+    entry: &DepositFileEntry,
+    spec: &ChainSpec,
+    withdrawal_address: Option<Address>,
+) -> DepositCheck {
+    let mut check = DepositCheck {
+        pubkey: normalize_hex(&entry.pubkey),
+        amount_gwei: entry.amount,
+        withdrawal_credentials: normalize_hex(&entry.withdrawal_credentials),
+        problems: Vec::new(),
+    };
+    let problems = &mut check.problems;
+
+    let fields = (
+        decode::<48>("pubkey", &entry.pubkey),
+        decode::<32>("withdrawal_credentials", &entry.withdrawal_credentials),
+        decode::<96>("signature", &entry.signature),
+    );
+    let (pubkey, credentials, signature) = match fields {
+        (Ok(pubkey), Ok(credentials), Ok(signature)) => (pubkey, credentials, signature),
+        (pubkey, credentials, signature) => {
+            problems.extend(pubkey.err());
+            problems.extend(credentials.err());
+            problems.extend(signature.err());
+            return check;
+        }
+    };
+
+    let deposit = DepositData {
+        pubkey,
+        withdrawal_credentials: B256::from(credentials),
+        amount: entry.amount,
+        signature,
+    };
+    if let Some(recorded) = &entry.deposit_message_root
+        && normalize_hex(recorded) != hex::encode(deposit.deposit_message_root())
+    {
+        problems.push("deposit_message_root doesn't match the deposit".to_string());
+    }
+    if let Some(recorded) = &entry.deposit_data_root
+        && normalize_hex(recorded) != hex::encode(deposit.tree_hash_root())
+    {
+        problems.push("deposit_data_root doesn't match the deposit".to_string());
+    }
+    if let Some(recorded) = &entry.fork_version
+        && normalize_hex(recorded) != hex::encode(spec.genesis_fork_version)
+    {
+        problems.push(format!(
+            "Fork version {recorded} is not the genesis fork version of the network"
+        ));
+    }
+    if !deposit.verify_signature(spec) {
+        problems.push("Signature is invalid for this network".to_string());
+    }
+    if entry.amount < MIN_DEPOSIT_GWEI {
+        problems.push("Amount is below the 1 ETH minimum deposit".to_string());
+    }
+    if let Some(address) = withdrawal_address
+        && deposit.withdrawal_credentials != eth1_withdrawal_credentials(address)
+        && deposit.withdrawal_credentials != compounding_withdrawal_credentials(address)
+    {
+        problems.push(format!(
+            "Withdrawal credentials don't withdraw to {address}"
+        ));
+    }
+    check
+}
+
+/// Lowercases hex and drops its `0x` prefix, the form deposit files use.
+fn normalize_hex(value: &str) -> String {
+    value.trim_start_matches("0x").to_ascii_lowercase()
+}
+
+fn decode<const N: usize>(field: &str, value: &str) -> Result<[u8; N], String> {
+    hex::decode(value.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| format!("{field} is not {N} bytes of hex"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::deposit::chain_spec_for_network;
+    use bls::Keypair;
+    use serde_json::json;
+
+    const ADDRESS: &str = "0x48fe05daea0f8cc6958a72522db42b2edb3fda1a";
+
+    fn deposit_entry(keypair: &Keypair, network: &str) -> serde_json::Value {
+        let spec = chain_spec_for_network(network).unwrap();
+        let mut deposit = DepositData::new(
+            keypair.pk.serialize(),
+            compounding_withdrawal_credentials(ADDRESS.parse().unwrap()),
+            32_000_000_000,
+        );
+        deposit.sign(&keypair.sk, &spec);
+        json!({
+            "pubkey": hex::encode(deposit.pubkey),
+            "withdrawal_credentials": hex::encode(deposit.withdrawal_credentials),
+            "amount": deposit.amount,
+            "signature": hex::encode(deposit.signature),
+            "deposit_message_root": hex::encode(deposit.deposit_message_root()),
+            "deposit_data_root": hex::encode(deposit.tree_hash_root()),
+            "fork_version": hex::encode(spec.genesis_fork_version),
+            "network_name": network,
+        })
+    }
+
+    fn verify(
+        entries: &serde_json::Value,
+        withdrawal_address: Option<&str>,
+    ) -> DepositVerification {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deposit_data-1700000000.json");
+        fs::write(&path, entries.to_string()).unwrap();
+        verify_deposit_data(DepositVerificationRequest {
+            deposit_data_path: path,
+            network: None,
+            withdrawal_address: withdrawal_address.map(|address| address.parse().unwrap()),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn valid_deposits_pass() {
+        let entries = json!([
+            deposit_entry(&Keypair::random(), "hoodi"),
+            deposit_entry(&Keypair::random(), "hoodi"),
+        ]);
+        let verification = verify(&entries, Some(ADDRESS));
+        assert_eq!(verification.network, "hoodi");
+        assert!(verification.is_valid(), "{verification:?}");
+    }
+
+    #[test]
+    fn tampered_duplicate_and_misdirected_deposits_are_reported() {
+        let keypair = Keypair::random();
+        let mut tampered = deposit_entry(&keypair, "hoodi");
+        tampered["amount"] = json!(31_000_000_000u64);
+        let entries = json!([deposit_entry(&keypair, "hoodi"), tampered]);
+
+        let verification = verify(&entries, Some("0x0000000000000000000000000000000000000001"));
+        assert!(!verification.is_valid());
+        let first = &verification.deposits[0].problems;
+        assert_eq!(first.len(), 1, "{first:?}");
+        assert!(first[0].starts_with("Withdrawal credentials"));
+
+        let second = &verification.deposits[1].problems;
+        assert!(
+            second
+                .iter()
+                .any(|problem| problem.starts_with("deposit_message_root"))
+        );
+        assert!(
+            second
+                .iter()
+                .any(|problem| problem.starts_with("deposit_data_root"))
+        );
+        assert!(
+            second
+                .iter()
+                .any(|problem| problem.starts_with("Signature"))
+        );
+        assert!(
+            second
+                .iter()
+                .any(|problem| problem.starts_with("Duplicate pubkey"))
+        );
+    }
+
+    #[test]
+    fn signatures_are_checked_against_the_network() {
+        let mut entry = deposit_entry(&Keypair::random(), "hoodi");
+        entry["network_name"] = json!("sepolia");
+        let verification = verify(&json!([entry]), None);
+        assert_eq!(verification.network, "sepolia");
+        let problems = &verification.deposits[0].problems;
+        assert!(
+            problems
+                .iter()
+                .any(|problem| problem.starts_with("Fork version"))
+        );
+        assert!(
+            problems
+                .iter()
+                .any(|problem| problem.starts_with("Signature"))
+        );
+    }
+}
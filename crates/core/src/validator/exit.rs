@@ -0,0 +1,186 @@
+use super::deposit::{
+    ChainSpec, SecretKey, SignatureBytes, compute_domain, compute_signing_root, hash_concat,
+    hash_tree_root_u64, quoted_u64,
+};
+use super::keygen::{decrypt_keystore, load_chain_spec, secs_since_unix_epoch, write_json};
+use alloy_primitives::B256;
+use eyre::{Result, WrapErr, eyre};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use zeroize::Zeroizing;
+
+/// Domain type constant for voluntary exits, per consensus spec.
+const DOMAIN_VOLUNTARY_EXIT: [u8; 4] = [0x04, 0x00, 0x00, 0x00];
+
+/// Mirrors the consensus-spec `VoluntaryExit` container.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VoluntaryExit {
+    /// Earliest epoch the exit can be included in.
+    #[serde(with = "quoted_u64")]
+    pub epoch: u64,
+    #[serde(with = "quoted_u64")]
+    pub validator_index: u64,
+}
+
+impl VoluntaryExit {
+    /// SSZ hash tree root: 2 fields, already a power of 2.
+    fn tree_hash_root(&self) -> [u8; 32] {
+        hash_concat(
+            &hash_tree_root_u64(self.epoch),
+            &hash_tree_root_u64(self.validator_index),
+        )
+    }
+}
+
+/// A signed voluntary exit, in the JSON shape the beacon API accepts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedVoluntaryExit {
+    pub message: VoluntaryExit,
+    pub signature: String,
+}
+
+pub struct VoluntaryExitRequest {
+    pub keystore_path: PathBuf,
+    pub password: Zeroizing<String>,
+    pub network: String,
+    pub validator_index: u64,
+    pub epoch: u64,
+    /// Genesis validators root reported by a beacon node; see
+    /// `ChainSpec::resolve_genesis_validators_root`.
+    pub genesis_validators_root: Option<B256>,
+}
+
+/// Decrypts the keystore and signs a voluntary exit for the validator it holds.
+///
+/// Exits are signed with the Capella fork version (EIP-7044), so the signature stays valid
+/// across later forks and can be stored until it is needed.
+///
+/// # Errors
+/// Returns an [`eyre::Report`] when the network's signing domain is unknown, when a supplied
+/// genesis validators root doesn't match the network, or when the keystore cannot be decrypted.
+pub fn sign_voluntary_exit(request: VoluntaryExitRequest) -> Result<SignedVoluntaryExit> {
+    let VoluntaryExitRequest {
+        keystore_path,
+        password,
+        network,
+        validator_index,
+        epoch,
+        genesis_validators_root,
+    } = request;
+
+    let spec = load_chain_spec(&network)?;
+    let domain = voluntary_exit_domain(&spec, &network, genesis_validators_root)?;
+    let keypair = decrypt_keystore(&keystore_path, &password)?;
+
+    Ok(sign(
+        &keypair.sk,
+        VoluntaryExit {
+            epoch,
+            validator_index,
+        },
+        &domain,
+    ))
+}
+
+/// Writes `exit` to `signed_exit_transaction-<index>-<timestamp>.json` in `output_dir`.
+pub fn write_signed_voluntary_exit(
+    exit: &SignedVoluntaryExit,
+    output_dir: &Path,
+) -> Result<PathBuf> {
+    fs::create_dir_all(output_dir)
+        .wrap_err_with(|| format!("Failed to create {}", output_dir.display()))?;
+    let timestamp = secs_since_unix_epoch(SystemTime::now())?;
+    let path = output_dir.join(format!(
+        "signed_exit_transaction-{}-{timestamp}.json",
+        exit.message.validator_index
+    ));
+    write_json(&path, exit).wrap_err("Failed to write the signed voluntary exit")?;
+    Ok(path)
+}
+
+/// Computes `DOMAIN_VOLUNTARY_EXIT` under the Capella fork version and genesis validators root.
+fn voluntary_exit_domain(
+    spec: &ChainSpec,
+    network: &str,
+    reported_root: Option<B256>,
+) -> Result<[u8; 32]> {
+    let fork_version = spec
+        .capella_fork_version
+        .ok_or_else(|| eyre!("The Capella fork version of {network} is unknown"))?;
+    let root = spec.resolve_genesis_validators_root(network, reported_root)?;
+    Ok(compute_domain(
+        DOMAIN_VOLUNTARY_EXIT,
+        fork_version,
+        root.into(),
+    ))
+}
+
+fn sign(secret_key: &SecretKey, message: VoluntaryExit, domain: &[u8; 32]) -> SignedVoluntaryExit {
+    let signing_root = compute_signing_root(&message.tree_hash_root(), domain);
+    let signature = secret_key.sign(B256::from(signing_root));
+    SignedVoluntaryExit {
+        message,
+        signature: format!(
+            "0x{}",
+            hex::encode(SignatureBytes::from(signature).serialize())
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::deposit::chain_spec_for_network;
+    use crate::validator::keygen::write_test_keystore;
+    use bls::{Keypair, Signature};
+
+    const PASSWORD: &str = "correct horse battery";
+
+    #[test]
+    fn signed_exit_verifies_under_the_capella_domain() {
+        let keypair = Keypair::random();
+        let dir = tempfile::tempdir().unwrap();
+        let keystore_path = write_test_keystore(dir.path(), &keypair, PASSWORD);
+
+        let exit = sign_voluntary_exit(VoluntaryExitRequest {
+            keystore_path,
+            password: Zeroizing::new(PASSWORD.to_string()),
+            network: "hoodi".to_string(),
+            validator_index: 1234,
+            epoch: 0,
+            genesis_validators_root: None,
+        })
+        .unwrap();
+
+        let spec = chain_spec_for_network("hoodi").unwrap();
+        let domain = voluntary_exit_domain(&spec, "hoodi", None).unwrap();
+        let signing_root = compute_signing_root(&exit.message.tree_hash_root(), &domain);
+        let signature =
+            Signature::deserialize(&hex::decode(&exit.signature[2..]).unwrap()).unwrap();
+        assert!(signature.verify(&keypair.pk, B256::from(signing_root)));
+
+        let json = serde_json::to_value(&exit).unwrap();
+        assert_eq!(json["message"]["epoch"], "0");
+        assert_eq!(json["message"]["validator_index"], "1234");
+    }
+
+    #[test]
+    fn genesis_validators_root_must_be_known_and_match() {
+        let spec = chain_spec_for_network("hoodi").unwrap();
+        let known = spec.genesis_validators_root.unwrap();
+        assert_eq!(
+            voluntary_exit_domain(&spec, "hoodi", Some(known)).unwrap(),
+            voluntary_exit_domain(&spec, "hoodi", None).unwrap()
+        );
+        assert!(voluntary_exit_domain(&spec, "hoodi", Some(B256::ZERO)).is_err());
+
+        let unrecorded = ChainSpec {
+            genesis_validators_root: None,
+            ..spec
+        };
+        assert!(voluntary_exit_domain(&unrecorded, "ephemery", None).is_err());
+        assert!(voluntary_exit_domain(&unrecorded, "ephemery", Some(known)).is_ok());
+    }
+}
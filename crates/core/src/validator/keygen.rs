@@ -10,11 +10,12 @@ use super::deposit::{
 };
 use crate::ethereum::{EPHEMERY_NETWORK_NAME, ensure_ephemery_config};
 use alloy_primitives::{
-    Address, U256,
+    Address, B256, U256,
     utils::{Unit, format_units, keccak256},
 };
 use bip32::{DerivationPath, Seed as Bip32Seed, XPrv};
 use bip39::{Language, Mnemonic, Seed as Bip39Seed};
+use bls::Keypair;
 use eth2_key_derivation::DerivedKey;
 use eth2_keystore::{Keystore, KeystoreBuilder, keypair_from_secret};
 use eyre::{ContextCompat, Result, WrapErr, eyre};
@@ -29,7 +30,7 @@ const CONNECTIVITY_PROBES: &[(&str, u16)] = &[
     ("www.google.com", 80),
 ];
 const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(2);
-const DEPOSIT_CLI_VERSION: &str = "1.2.0";
+pub(super) const DEPOSIT_CLI_VERSION: &str = "1.2.0";
 
 /// Returns the list of networks supported by this build.
 pub fn available_networks() -> Vec<&'static str> {
@@ -83,6 +84,8 @@ pub fn format_eth_from_gwei(gwei: u64) -> String {
 
 pub struct ValidatorKeygenRequest {
     pub mnemonic_phrase: Zeroizing<String>,
+    /// Key index of the first validator; earlier indices belong to keys generated before.
+    pub start_index: u32,
     pub validator_count: u16,
     pub withdrawal_address: Address,
     pub network: String,
@@ -126,6 +129,7 @@ pub fn generate_validator_files_with_progress(
 ) -> Result<ValidatorKeygenOutcome> {
     let ValidatorKeygenRequest {
         mnemonic_phrase,
+        start_index,
         validator_count,
         withdrawal_address,
         network,
@@ -138,6 +142,9 @@ pub fn generate_validator_files_with_progress(
     let mnemonic = Mnemonic::from_phrase(mnemonic_phrase.as_str(), Language::English)
         .wrap_err("Mnemonic phrase is invalid")?;
     let spec = load_chain_spec(&network)?;
+    start_index
+        .checked_add(u32::from(validator_count))
+        .ok_or_else(|| eyre!("Validator key indices are out of range"))?;
 
     prepare_output_dir(&output_dir)?;
 
@@ -162,12 +169,13 @@ pub fn generate_validator_files_with_progress(
         network: &network,
     };
 
-    for index in 0..validator_count {
-        let (keystore_path, deposit_entry) = produce_materials(index, &params)?;
+    for offset in 0..validator_count {
+        let (keystore_path, deposit_entry) =
+            produce_materials(start_index + u32::from(offset), &params)?;
         keystore_paths.push(keystore_path);
         deposits.push(deposit_entry);
         on_progress(ValidatorProgress {
-            current: index + 1,
+            current: offset + 1,
             total: validator_count,
         });
     }
@@ -180,6 +188,32 @@ pub fn generate_validator_files_with_progress(
     })
 }
 
+/// Derives the pubkeys of validators `start_index..start_index + count` of a mnemonic, as
+/// 0x-prefixed hex, without writing any keystores.
+///
+/// Lets callers check whether keys were deposited before generating them again.
+pub fn derive_validator_pubkeys(
+    mnemonic_phrase: &str,
+    start_index: u32,
+    count: u16,
+) -> Result<Vec<String>> {
+    let mnemonic = Mnemonic::from_phrase(mnemonic_phrase, Language::English)
+        .wrap_err("Mnemonic phrase is invalid")?;
+    let seed = Bip39Seed::new(&mnemonic, "");
+    (0..u32::from(count))
+        .map(|offset| {
+            let index = start_index
+                .checked_add(offset)
+                .ok_or_else(|| eyre!("Validator key indices are out of range"))?;
+            let (keypair, _) = derive_validator_keypair(seed.as_bytes(), index)?;
+            Ok(format!(
+                "0x{}",
+                to_hex(PublicKeyBytes::from(keypair.pk).as_serialized())
+            ))
+        })
+        .collect()
+}
+
 /// Resolves the withdrawal address, falling back to the derived address when none is provided.
 pub fn resolve_withdrawal_address(user: Option<&str>, mnemonic: &str) -> Result<Address> {
     match user {
@@ -231,13 +265,8 @@ struct GenerationParams<'a> {
     network: &'a str,
 }
 
-fn produce_materials(index: u16, params: &GenerationParams<'_>) -> Result<(PathBuf, DepositEntry)> {
-    let (secret_bytes, derivation_path) = derive_validator_secret(params.seed, index as u32)
-        .map_err(|error| eyre!("Failed to derive validator secret {index}: {error}"))?;
-    let secret_bytes = Zeroizing::new(secret_bytes);
-    let keypair = keypair_from_secret(&secret_bytes)
-        .map_err(|error| eyre!("Failed to instantiate keypair {index}: {error:?}"))?;
-    drop(secret_bytes);
+fn produce_materials(index: u32, params: &GenerationParams<'_>) -> Result<(PathBuf, DepositEntry)> {
+    let (keypair, derivation_path) = derive_validator_keypair(params.seed, index)?;
 
     let builder = KeystoreBuilder::new(&keypair, params.password.as_bytes(), derivation_path)
         .map_err(|error| eyre!("Failed to prepare keystore {index}: {error:?}"))?;
@@ -269,54 +298,77 @@ fn produce_materials(index: u16, params: &GenerationParams<'_>) -> Result<(PathB
     } else {
         eth1_withdrawal_credentials(params.withdrawal_address)
     };
+    let deposit_entry = signed_deposit_entry(
+        &keypair,
+        withdrawal_credentials,
+        params.deposit_gwei,
+        params.spec,
+        params.network,
+    )?;
 
+    Ok((keystore_path, deposit_entry))
+}
+
+/// Signs a deposit of `amount_gwei` for `keypair` and renders it as a deposit data file entry.
+pub(super) fn signed_deposit_entry(
+    keypair: &Keypair,
+    withdrawal_credentials: B256,
+    amount_gwei: u64,
+    spec: &ChainSpec,
+    network: &str,
+) -> Result<DepositEntry> {
     let pubkey_bytes: [u8; 48] = PublicKeyBytes::from(keypair.pk.clone())
         .as_serialized()
         .try_into()
         .expect("BLS public key is always 48 bytes");
 
-    let mut deposit_data =
-        DepositData::new(pubkey_bytes, withdrawal_credentials, params.deposit_gwei);
-    deposit_data.sign(&keypair.sk, params.spec);
+    let mut deposit_data = DepositData::new(pubkey_bytes, withdrawal_credentials, amount_gwei);
+    deposit_data.sign(&keypair.sk, spec);
 
     let deposit_message_root = deposit_data.deposit_message_root();
     let deposit_data_root = deposit_data.tree_hash_root();
 
-    let network_name = if params.network == EPHEMERY_NETWORK_NAME {
+    let network_name = if network == EPHEMERY_NETWORK_NAME {
         EPHEMERY_NETWORK_NAME.to_string()
     } else {
-        params
-            .spec
-            .config_name
+        spec.config_name
             .clone()
             .context("Network config name missing")?
     };
 
-    let deposit_entry = DepositEntry {
+    Ok(DepositEntry {
         pubkey: to_hex(deposit_data.pubkey),
         withdrawal_credentials: to_hex(deposit_data.withdrawal_credentials),
         amount: deposit_data.amount,
         signature: to_hex(deposit_data.signature),
         deposit_message_root: to_hex(deposit_message_root),
         deposit_data_root: to_hex(deposit_data_root),
-        fork_version: to_hex(params.spec.genesis_fork_version),
+        fork_version: to_hex(spec.genesis_fork_version),
         network_name,
         deposit_cli_version: DEPOSIT_CLI_VERSION.to_string(),
-    };
+    })
+}
 
-    Ok((keystore_path, deposit_entry))
+/// Derives the signing keypair of validator `index` at `m/12381/3600/<index>/0/0`, returning it
+/// with its derivation path.
+pub(super) fn derive_validator_keypair(seed: &[u8], index: u32) -> Result<(Keypair, String)> {
+    let secret_bytes = derive_eip2334_secret(seed, &[12381, 3600, index, 0, 0])
+        .map_err(|error| eyre!("Failed to derive validator secret {index}: {error}"))?;
+    let keypair = keypair_from_secret(&secret_bytes)
+        .map_err(|error| eyre!("Failed to instantiate keypair {index}: {error:?}"))?;
+    Ok((keypair, format!("m/12381/3600/{index}/0/0")))
 }
 
-fn derive_validator_secret(seed: &[u8], index: u32) -> Result<(Vec<u8>, String)> {
+/// Derives the EIP-2333 secret key at the EIP-2334 path `m/<nodes>`, such as
+/// `[12381, 3600, index, 0, 0]` for a signing key or `[12381, 3600, index, 0]` for a withdrawal
+/// key.
+pub(super) fn derive_eip2334_secret(seed: &[u8], nodes: &[u32]) -> Result<Zeroizing<Vec<u8>>> {
     let master = DerivedKey::from_seed(seed).map_err(|_| eyre!("empty seed provided"))?;
-    let nodes = [12381u32, 3600, index, 0, 0];
-    let dest = nodes.into_iter().fold(master, |dk, i| dk.child(i));
-    let secret = dest.secret().to_vec();
-    let path_str = format!("m/12381/3600/{index}/0/0");
-    Ok((secret, path_str))
+    let dest = nodes.iter().fold(master, |dk, i| dk.child(*i));
+    Ok(Zeroizing::new(dest.secret().to_vec()))
 }
 
-fn secs_since_unix_epoch(now: SystemTime) -> Result<u64> {
+pub(super) fn secs_since_unix_epoch(now: SystemTime) -> Result<u64> {
     Ok(now
         .duration_since(UNIX_EPOCH)
         .wrap_err("System time is invalid")?
@@ -324,7 +376,7 @@ fn secs_since_unix_epoch(now: SystemTime) -> Result<u64> {
 }
 
 #[cfg(test)]
-fn fast_test_kdf() -> eth2_keystore::json_keystore::Kdf {
+pub(super) fn fast_test_kdf() -> eth2_keystore::json_keystore::Kdf {
     use eth2_keystore::json_keystore::{HexBytes, Kdf, Pbkdf2, Prf};
     use eth2_keystore::{DKLEN, SALT_SIZE};
     Kdf::Pbkdf2(Pbkdf2 {
@@ -335,8 +387,21 @@ fn fast_test_kdf() -> eth2_keystore::json_keystore::Kdf {
     })
 }
 
+/// Writes an EIP-2335 keystore of `keypair` into `dir` and returns its path.
+#[cfg(test)]
+pub(super) fn write_test_keystore(dir: &Path, keypair: &Keypair, password: &str) -> PathBuf {
+    let keystore = KeystoreBuilder::new(keypair, password.as_bytes(), "m/12381/3600/0/0/0".into())
+        .unwrap()
+        .kdf(fast_test_kdf())
+        .build()
+        .unwrap();
+    let path = dir.join("keystore-m_12381_3600_0_0_0-1700000000.json");
+    fs::write(&path, serde_json::to_string(&keystore).unwrap()).unwrap();
+    path
+}
+
 #[derive(Serialize)]
-struct DepositEntry {
+pub(super) struct DepositEntry {
     pub pubkey: String,
     pub withdrawal_credentials: String,
     pub amount: u64,
@@ -348,7 +413,7 @@ struct DepositEntry {
     pub deposit_cli_version: String,
 }
 
-fn load_chain_spec(network: &str) -> Result<ChainSpec> {
+pub(super) fn load_chain_spec(network: &str) -> Result<ChainSpec> {
     if network == EPHEMERY_NETWORK_NAME {
         let config =
             ensure_ephemery_config().wrap_err("Failed to prepare Ephemery configuration")?;
@@ -367,7 +432,7 @@ Please upgrade Kittynode if your desired network is missing"
     ))
 }
 
-fn prepare_output_dir(path: &Path) -> Result<()> {
+pub(super) fn prepare_output_dir(path: &Path) -> Result<()> {
     if path.exists() {
         if !path.is_dir() {
             return Err(eyre!("Path must be a directory: {path:?}"));
@@ -405,7 +470,7 @@ fn ensure_new_file(path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+pub(super) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
     let mut file = OpenOptions::new()
         .write(true)
         .create_new(true)
@@ -415,6 +480,15 @@ fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
         .wrap_err_with(|| format!("Failed to serialize JSON to {path:?}"))
 }
 
+/// Reads and decrypts the EIP-2335 keystore at `path`.
+pub(super) fn decrypt_keystore(path: &Path, password: &str) -> Result<Keypair> {
+    let keystore = Keystore::from_json_file(path)
+        .map_err(|error| eyre!("Failed to read keystore {}: {error:?}", path.display()))?;
+    keystore
+        .decrypt_keypair(password.as_bytes())
+        .map_err(|error| eyre!("Failed to decrypt keystore {}: {error:?}", path.display()))
+}
+
 fn write_keystore(path: &Path, keystore: &Keystore) -> Result<()> {
     let mut open_opts = OpenOptions::new();
     open_opts.write(true).create_new(true);
@@ -441,7 +515,7 @@ fn to_hex(bytes: impl AsRef<[u8]>) -> String {
     hex_encode(bytes.as_ref())
 }
 
-fn next_available_deposit_path(
+pub(super) fn next_available_deposit_path(
     output_dir: &Path,
     timestamp: u64,
 ) -> Result<(PathBuf, Option<u32>)> {
@@ -488,6 +562,7 @@ mod tests {
             .wrap_err("failed to parse withdrawal address")?;
         let outcome = generate_validator_files(ValidatorKeygenRequest {
             mnemonic_phrase: Zeroizing::new(MNEMONIC.to_string()),
+            start_index: 0,
             validator_count: 1,
             withdrawal_address,
             network: "hoodi".to_string(),
@@ -541,6 +616,7 @@ mod tests {
             .wrap_err("failed to parse withdrawal address")?;
         let outcome = generate_validator_files(ValidatorKeygenRequest {
             mnemonic_phrase: Zeroizing::new(MNEMONIC.to_string()),
+            start_index: 0,
             validator_count: 1,
             withdrawal_address,
             network: "hoodi".to_string(),
@@ -628,6 +704,7 @@ mod tests {
         let per_validator_gwei = 33_000_000_000u64;
         let outcome = generate_validator_files(ValidatorKeygenRequest {
             mnemonic_phrase: Zeroizing::new(MNEMONIC.to_string()),
+            start_index: 0,
             validator_count: 2,
             withdrawal_address,
             network: "hoodi".to_string(),
@@ -653,6 +730,50 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn later_batches_continue_from_the_start_index() -> Result<()> {
+        let tmp = tempdir().wrap_err("failed to create temp dir")?;
+        let withdrawal_address: Address = WITHDRAWAL_ADDRESS
+            .parse()
+            .wrap_err("failed to parse withdrawal address")?;
+        let request = |start_index, validator_count, dir: &str| ValidatorKeygenRequest {
+            mnemonic_phrase: Zeroizing::new(MNEMONIC.to_string()),
+            start_index,
+            validator_count,
+            withdrawal_address,
+            network: "hoodi".to_string(),
+            deposit_gwei: 32_000_000_000,
+            compounding: true,
+            password: Zeroizing::new(KEYSTORE_PASSWORD.to_string()),
+            output_dir: tmp.path().join(dir),
+        };
+
+        let first = generate_validator_files(request(0, 3, "first"))?;
+        let second = generate_validator_files(request(2, 2, "second"))?;
+        let pubkeys = |path: &Path| -> Result<Vec<String>> {
+            Ok(read_json_array(path)?
+                .iter()
+                .map(|entry| format!("0x{}", entry["pubkey"].as_str().unwrap_or_default()))
+                .collect())
+        };
+        let first_pubkeys = pubkeys(&first.deposit_data_path)?;
+        let second_pubkeys = pubkeys(&second.deposit_data_path)?;
+
+        assert_eq!(second_pubkeys[0], first_pubkeys[2]);
+        assert!(!first_pubkeys.contains(&second_pubkeys[1]));
+        assert_eq!(
+            derive_validator_pubkeys(MNEMONIC, 2, 2)?,
+            second_pubkeys,
+            "derived pubkeys should match the generated deposits"
+        );
+        let keystore_name = second.keystore_paths[1]
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        assert!(keystore_name.starts_with("keystore-m_12381_3600_3_0_0-"));
+        Ok(())
+    }
+
     #[test]
     fn resolve_withdrawal_address_prefers_user_value() -> Result<()> {
         let user = "0x48fe05daea0f8cc6958a72522db42b2edb3fda1a";
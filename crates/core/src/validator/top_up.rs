@@ -0,0 +1,253 @@
+use super::deposit::{
+    MAX_EFFECTIVE_BALANCE_ELECTRA_GWEI, MAX_EFFECTIVE_BALANCE_GWEI, MIN_DEPOSIT_GWEI,
+    compounding_withdrawal_credentials, eth1_withdrawal_credentials,
+};
+use super::keygen::{
+    decrypt_keystore, derive_validator_keypair, format_eth_from_gwei, load_chain_spec,
+    next_available_deposit_path, prepare_output_dir, secs_since_unix_epoch, signed_deposit_entry,
+    write_json,
+};
+use alloy_primitives::Address;
+use bip39::{Language, Mnemonic, Seed as Bip39Seed};
+use eyre::{Result, WrapErr, eyre};
+use std::path::PathBuf;
+use std::time::SystemTime;
+use zeroize::Zeroizing;
+
+/// Where the keys of the validators to top up come from.
+pub enum TopUpKeys {
+    /// Validators `start_index..start_index + count` of the mnemonic they were created from.
+    Mnemonic {
+        mnemonic_phrase: Zeroizing<String>,
+        start_index: u32,
+        count: u16,
+    },
+    /// The validator held by an EIP-2335 keystore.
+    Keystore {
+        keystore_path: PathBuf,
+        password: Zeroizing<String>,
+    },
+}
+
+pub struct TopUpRequest {
+    pub keys: TopUpKeys,
+    pub network: String,
+    /// Must match the validators' existing withdrawal credentials.
+    pub withdrawal_address: Address,
+    pub compounding: bool,
+    /// Deposited to each validator.
+    pub amount_gwei: u64,
+    pub output_dir: PathBuf,
+}
+
+pub struct TopUpOutcome {
+    pub deposit_data_path: PathBuf,
+    pub pubkeys: Vec<String>,
+}
+
+/// Signs deposits that add `amount_gwei` to existing validators and writes them to a
+/// launchpad-compatible deposit data file.
+///
+/// Top-ups carry the validator's current withdrawal credentials, so the address and credential
+/// type must match what the validator was created with.
+///
+/// # Errors
+/// Returns an [`eyre::Report`] when the amount is outside what the validators can use, when the
+/// mnemonic or keystore cannot be read, or when the deposit data file cannot be written.
+pub fn generate_top_up_deposits(request: TopUpRequest) -> Result<TopUpOutcome> {
+    let TopUpRequest {
+        keys,
+        network,
+        withdrawal_address,
+        compounding,
+        amount_gwei,
+        output_dir,
+    } = request;
+
+    check_top_up_amount(amount_gwei, compounding)?;
+    let spec = load_chain_spec(&network)?;
+    let keypairs = match keys {
+        TopUpKeys::Mnemonic {
+            mnemonic_phrase,
+            start_index,
+            count,
+        } => {
+            let mnemonic = Mnemonic::from_phrase(mnemonic_phrase.as_str(), Language::English)
+                .wrap_err("Mnemonic phrase is invalid")?;
+            let seed = Bip39Seed::new(&mnemonic, "");
+            (0..u32::from(count))
+                .map(|offset| {
+                    let index = start_index
+                        .checked_add(offset)
+                        .ok_or_else(|| eyre!("Validator key index is out of range"))?;
+                    derive_validator_keypair(seed.as_bytes(), index).map(|(keypair, _)| keypair)
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+        TopUpKeys::Keystore {
+            keystore_path,
+            password,
+        } => vec![decrypt_keystore(&keystore_path, &password)?],
+    };
+
+    let withdrawal_credentials = if compounding {
+        compounding_withdrawal_credentials(withdrawal_address)
+    } else {
+        eth1_withdrawal_credentials(withdrawal_address)
+    };
+    let deposits = keypairs
+        .iter()
+        .map(|keypair| {
+            signed_deposit_entry(
+                keypair,
+                withdrawal_credentials,
+                amount_gwei,
+                &spec,
+                &network,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    prepare_output_dir(&output_dir)?;
+    let timestamp = secs_since_unix_epoch(SystemTime::now())?;
+    let (deposit_data_path, _) = next_available_deposit_path(&output_dir, timestamp)?;
+    write_json(&deposit_data_path, &deposits).wrap_err("Failed to write deposit data")?;
+
+    Ok(TopUpOutcome {
+        deposit_data_path,
+        pubkeys: deposits
+            .into_iter()
+            .map(|deposit| format!("0x{}", deposit.pubkey))
+            .collect(),
+    })
+}
+
+/// Rejects amounts the deposit contract refuses or that would be swept straight back out.
+fn check_top_up_amount(amount_gwei: u64, compounding: bool) -> Result<()> {
+    if amount_gwei < MIN_DEPOSIT_GWEI {
+        return Err(eyre!("Top-ups must be at least 1 ETH"));
+    }
+    let max_gwei = if compounding {
+        MAX_EFFECTIVE_BALANCE_ELECTRA_GWEI
+    } else {
+        MAX_EFFECTIVE_BALANCE_GWEI
+    };
+    if amount_gwei > max_gwei {
+        return Err(eyre!(
+            "Top-ups above {} ETH would be withdrawn again by the next sweep",
+            format_eth_from_gwei(max_gwei)
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::deposit_verification::{DepositVerificationRequest, verify_deposit_data};
+    use crate::validator::keygen::{
+        ValidatorKeygenRequest, generate_validator_files, write_test_keystore,
+    };
+    use bls::Keypair;
+    use std::fs;
+
+    const MNEMONIC: &str = "upon pelican potato light kick symptom pioneer bridge wonder chief head citizen flip festival claw switch wear proud length zoo mercy foot repair ceiling";
+    const PASSWORD: &str = "blackcatsarenotevil";
+    const WITHDRAWAL_ADDRESS: &str = "0x48fe05daea0f8cc6958a72522db42b2edb3fda1a";
+
+    fn request(keys: TopUpKeys, output_dir: PathBuf) -> TopUpRequest {
+        TopUpRequest {
+            keys,
+            network: "hoodi".to_string(),
+            withdrawal_address: WITHDRAWAL_ADDRESS.parse().unwrap(),
+            compounding: true,
+            amount_gwei: 100_000_000_000,
+            output_dir,
+        }
+    }
+
+    fn verify(outcome: &TopUpOutcome) {
+        let verification = verify_deposit_data(DepositVerificationRequest {
+            deposit_data_path: outcome.deposit_data_path.clone(),
+            network: None,
+            withdrawal_address: Some(WITHDRAWAL_ADDRESS.parse().unwrap()),
+        })
+        .unwrap();
+        assert!(verification.is_valid(), "{verification:?}");
+        assert!(
+            verification
+                .deposits
+                .iter()
+                .all(|deposit| deposit.amount_gwei == 100_000_000_000)
+        );
+    }
+
+    #[test]
+    fn mnemonic_top_ups_target_the_validators_at_the_index_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let generated = generate_validator_files(ValidatorKeygenRequest {
+            mnemonic_phrase: Zeroizing::new(MNEMONIC.to_string()),
+            start_index: 0,
+            validator_count: 3,
+            withdrawal_address: WITHDRAWAL_ADDRESS.parse().unwrap(),
+            network: "hoodi".to_string(),
+            deposit_gwei: 32_000_000_000,
+            compounding: true,
+            password: Zeroizing::new(PASSWORD.to_string()),
+            output_dir: dir.path().join("keys"),
+        })
+        .unwrap();
+        let generated: Vec<serde_json::Value> =
+            serde_json::from_str(&fs::read_to_string(generated.deposit_data_path).unwrap())
+                .unwrap();
+
+        let outcome = generate_top_up_deposits(request(
+            TopUpKeys::Mnemonic {
+                mnemonic_phrase: Zeroizing::new(MNEMONIC.to_string()),
+                start_index: 1,
+                count: 2,
+            },
+            dir.path().join("top-up"),
+        ))
+        .unwrap();
+
+        let expected: Vec<String> = generated[1..]
+            .iter()
+            .map(|deposit| format!("0x{}", deposit["pubkey"].as_str().unwrap()))
+            .collect();
+        assert_eq!(outcome.pubkeys, expected);
+        verify(&outcome);
+    }
+
+    #[test]
+    fn keystore_top_up_targets_its_validator() {
+        let dir = tempfile::tempdir().unwrap();
+        let keypair = Keypair::random();
+        let keystore_path = write_test_keystore(dir.path(), &keypair, PASSWORD);
+
+        let outcome = generate_top_up_deposits(request(
+            TopUpKeys::Keystore {
+                keystore_path,
+                password: Zeroizing::new(PASSWORD.to_string()),
+            },
+            dir.path().join("top-up"),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            outcome.pubkeys,
+            vec![format!("0x{}", hex::encode(keypair.pk.serialize()))]
+        );
+        verify(&outcome);
+    }
+
+    #[test]
+    fn amounts_are_limited_by_the_credential_type() {
+        assert!(check_top_up_amount(999_999_999, true).is_err());
+        assert!(check_top_up_amount(1_000_000_000, false).is_ok());
+        assert!(check_top_up_amount(32_000_000_000, false).is_ok());
+        assert!(check_top_up_amount(33_000_000_000, false).is_err());
+        assert!(check_top_up_amount(2_048_000_000_000, true).is_ok());
+        assert!(check_top_up_amount(2_049_000_000_000, true).is_err());
+    }
+}
@@ -147,7 +147,7 @@ async fn install_ethereum_requires_network_before_docker_access() {
 async fn install_package_with_unsupported_network_errors_early() {
     let _home = TempHomeGuard::new();
 
-    let err = packages::install_package_with_network("ethereum", Some("does-not-exist"))
+    let err = packages::install_package_with_network("ethereum", None, Some("does-not-exist"))
         .await
         .expect_err("expected validation error");
     assert!(
@@ -160,7 +160,7 @@ async fn install_package_with_unsupported_network_errors_early() {
 async fn install_package_with_network_rejects_non_ethereum() {
     let _home = TempHomeGuard::new();
 
-    let err = packages::install_package_with_network("not-a-package", Some("mainnet"))
+    let err = packages::install_package_with_network("not-a-package", None, Some("mainnet"))
         .await
         .expect_err("expected validation error");
     assert!(
@@ -170,11 +170,32 @@ async fn install_package_with_network_rejects_non_ethereum() {
     );
 }
 
+#[tokio::test]
+async fn install_package_rejects_invalid_instances() {
+    let _home = TempHomeGuard::new();
+
+    let err = packages::install_package_with_network("ethereum", Some("Hoodi!"), Some("hoodi"))
+        .await
+        .expect_err("expected invalid instance id");
+    assert!(
+        err.to_string().contains("Invalid instance id"),
+        "unexpected error: {err}"
+    );
+
+    let err = packages::install_package_with_network("not-a-package", Some("second"), None)
+        .await
+        .expect_err("expected instances to be unsupported");
+    assert!(
+        err.to_string().contains("does not support named instances"),
+        "unexpected error: {err}"
+    );
+}
+
 #[tokio::test]
 async fn delete_unknown_package_is_not_found() {
     let _home = TempHomeGuard::new();
 
-    let err = packages::delete_package("does-not-exist", false)
+    let err = packages::delete_package("does-not-exist", None, false)
         .await
         .expect_err("expected error");
     assert!(
@@ -195,7 +216,54 @@ async fn delete_unconfigured_ethereum_does_not_require_docker() {
     std::fs::create_dir_all(&base_dir).expect("create package dir");
     std::fs::write(base_dir.join("config.toml"), "values = {}\n").expect("write config");
 
-    packages::delete_package("ethereum", false)
+    packages::delete_package("ethereum", None, false)
         .await
         .expect("delete should succeed without docker");
 }
+
+#[tokio::test]
+async fn update_package_config_rejects_unknown_keys_before_saving() {
+    let _home = TempHomeGuard::new();
+
+    let mut config = packages::PackageConfig::new();
+    config
+        .values
+        .insert("network".to_string(), "hoodi".to_string());
+    config
+        .values
+        .insert("validator_fee_recepient".to_string(), "0x0".to_string());
+
+    let err = packages::update_package_config("ethereum", None, config)
+        .await
+        .expect_err("expected validation error");
+    assert!(
+        err.downcast_ref::<packages::ConfigValidationError>()
+            .is_some(),
+        "unexpected error: {err}"
+    );
+    assert!(
+        err.to_string().contains("validator_fee_recepient"),
+        "unexpected error: {err}"
+    );
+
+    let saved = packages::get_package_config("ethereum", None)
+        .await
+        .expect("load config");
+    assert!(saved.values.is_empty(), "config should not be saved");
+}
+
+#[test]
+fn ethereum_package_exposes_config_schema() {
+    let _home = TempHomeGuard::new();
+
+    let schema = packages::get_package_config_schema("ethereum").expect("schema");
+    assert!(
+        schema
+            .iter()
+            .any(|field| field.key() == "network" && field.is_required())
+    );
+
+    let catalog = packages::get_package_catalog().expect("catalog");
+    let eth = catalog.get("ethereum").expect("ethereum entry");
+    assert_eq!(eth.config_schema().len(), schema.len());
+}
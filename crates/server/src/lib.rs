@@ -2,20 +2,31 @@ use axum::{
     Router,
     extract::{Path, Query},
     http::StatusCode,
-    response::Json,
+    response::{
+        Json,
+        sse::{Event, KeepAlive, Sse},
+    },
     routing::{get, post},
 };
 use eyre::Result;
 use kittynode_core::config::Config;
 use kittynode_core::daemon::{DEFAULT_SERVER_PORT, validate_server_port};
 use kittynode_core::docker::LogsQuery;
+use kittynode_core::ethereum::{NodeHealth, ValidatorStatus};
 use kittynode_core::node::{DockerStartStatus, OperationalState};
-use kittynode_core::packages::{Package, PackageConfig, PackageState};
+use kittynode_core::packages::{
+    ConfigValidationError, ContainerUpgrade, OrphanedResource, Package, PackageConfig,
+    PackageState, Plan, PortConflictError,
+};
 use kittynode_core::system::SystemInfo;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::{Stream, StreamExt, wrappers::UnboundedReceiverStream};
 
 pub async fn hello_world() -> &'static str {
     "Hello World!"
@@ -30,6 +41,17 @@ fn to_http_error<E: std::fmt::Display>(err: E) -> (StatusCode, String) {
     }
 }
 
+fn to_config_http_error(err: eyre::Report) -> (StatusCode, String) {
+    if err.downcast_ref::<ConfigValidationError>().is_some() {
+        (StatusCode::BAD_REQUEST, err.to_string())
+    } else if let Some(conflict) = err.downcast_ref::<PortConflictError>() {
+        // Installs wrap failures in a rollback summary; the conflicts are what the caller needs.
+        (StatusCode::CONFLICT, conflict.to_string())
+    } else {
+        to_http_error(err)
+    }
+}
+
 pub async fn add_capability(Path(name): Path<String>) -> Result<StatusCode, (StatusCode, String)> {
     kittynode_core::config::add_capability(&name).map_err(to_http_error)?;
     Ok(StatusCode::OK)
@@ -62,21 +84,79 @@ pub async fn get_config() -> Result<Json<Config>, (StatusCode, String)> {
 
 #[derive(Default, Deserialize)]
 pub struct InstallPackageQuery {
+    instance: Option<String>,
     network: Option<String>,
 }
 
+/// Selects a named package instance; omitted for the default instance.
+#[derive(Default, Deserialize)]
+pub struct InstanceQuery {
+    instance: Option<String>,
+}
+
 pub async fn install_package(
     Path(name): Path<String>,
     Query(params): Query<InstallPackageQuery>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    kittynode_core::packages::install_package_with_network(&name, params.network.as_deref())
-        .await
-        .map_err(to_http_error)?;
+    kittynode_core::packages::install_package_with_network(
+        &name,
+        params.instance.as_deref(),
+        params.network.as_deref(),
+    )
+    .await
+    .map_err(to_config_http_error)?;
     Ok(StatusCode::OK)
 }
 
+pub async fn plan_install_package(
+    Path(name): Path<String>,
+    Query(params): Query<InstallPackageQuery>,
+) -> Result<Json<Plan>, (StatusCode, String)> {
+    kittynode_core::packages::plan_install_package(
+        &name,
+        params.instance.as_deref(),
+        params.network.as_deref(),
+    )
+    .await
+    .map(Json)
+    .map_err(to_http_error)
+}
+
+/// Installs a package and streams image pull progress as server-sent events.
+///
+/// Emits `progress` events with [`kittynode_core::docker::PullProgress`] JSON, then a single
+/// `done` event on success or an `error` event carrying the failure message.
+pub async fn install_package_stream(
+    Path(name): Path<String>,
+    Query(params): Query<InstallPackageQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let progress_sender = sender.clone();
+        let result = kittynode_core::packages::install_package_with_progress(
+            &name,
+            params.instance.as_deref(),
+            params.network.as_deref(),
+            move |progress| {
+                if let Ok(event) = Event::default().event("progress").json_data(&progress) {
+                    let _ = progress_sender.send(event);
+                }
+            },
+        )
+        .await;
+        let event = match result {
+            Ok(()) => Event::default().event("done").data(name),
+            Err(error) => Event::default().event("error").data(error.to_string()),
+        };
+        let _ = sender.send(event);
+    });
+
+    Sse::new(UnboundedReceiverStream::new(receiver).map(Ok)).keep_alive(KeepAlive::default())
+}
+
 #[derive(Default, Deserialize)]
 pub struct DeletePackageQuery {
+    instance: Option<String>,
     include_images: Option<bool>,
 }
 
@@ -85,21 +165,58 @@ pub async fn delete_package(
     Query(params): Query<DeletePackageQuery>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     let include_images = params.include_images.unwrap_or(false);
-    kittynode_core::packages::delete_package(&name, include_images)
+    kittynode_core::packages::delete_package(&name, params.instance.as_deref(), include_images)
         .await
         .map_err(to_http_error)?;
     Ok(StatusCode::OK)
 }
 
-pub async fn stop_package(Path(name): Path<String>) -> Result<StatusCode, (StatusCode, String)> {
-    kittynode_core::packages::stop_package(&name)
+pub async fn plan_delete_package(
+    Path(name): Path<String>,
+    Query(params): Query<DeletePackageQuery>,
+) -> Result<Json<Plan>, (StatusCode, String)> {
+    let include_images = params.include_images.unwrap_or(false);
+    kittynode_core::packages::plan_delete_package(&name, params.instance.as_deref(), include_images)
+        .await
+        .map(Json)
+        .map_err(to_http_error)
+}
+
+#[derive(Default, Deserialize)]
+pub struct UpgradePackageQuery {
+    instance: Option<String>,
+    to: Option<String>,
+}
+
+pub async fn upgrade_package(
+    Path(name): Path<String>,
+    Query(params): Query<UpgradePackageQuery>,
+) -> Result<Json<Vec<ContainerUpgrade>>, (StatusCode, String)> {
+    kittynode_core::packages::upgrade_package(
+        &name,
+        params.instance.as_deref(),
+        params.to.as_deref(),
+    )
+    .await
+    .map(Json)
+    .map_err(to_config_http_error)
+}
+
+pub async fn stop_package(
+    Path(name): Path<String>,
+    Query(params): Query<InstanceQuery>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    kittynode_core::packages::stop_package(&name, params.instance.as_deref())
         .await
         .map_err(to_http_error)?;
     Ok(StatusCode::OK)
 }
 
-pub async fn start_package(Path(name): Path<String>) -> Result<StatusCode, (StatusCode, String)> {
-    kittynode_core::packages::start_package(&name)
+pub async fn start_package(
+    Path(name): Path<String>,
+    Query(params): Query<InstanceQuery>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    kittynode_core::packages::start_package(&name, params.instance.as_deref())
         .await
         .map_err(to_http_error)?;
     Ok(StatusCode::OK)
@@ -112,8 +229,9 @@ pub struct RuntimeStatesRequest {
 
 pub async fn get_package(
     Path(name): Path<String>,
+    Query(params): Query<InstanceQuery>,
 ) -> Result<Json<PackageState>, (StatusCode, String)> {
-    kittynode_core::packages::get_package(&name)
+    kittynode_core::packages::get_package(&name, params.instance.as_deref())
         .await
         .map(Json)
         .map_err(to_http_error)
@@ -136,6 +254,30 @@ pub async fn get_installed_packages() -> Result<Json<Vec<Package>>, (StatusCode,
         .map_err(to_http_error)
 }
 
+pub async fn find_orphaned_resources() -> Result<Json<Vec<OrphanedResource>>, (StatusCode, String)>
+{
+    kittynode_core::packages::find_orphaned_resources()
+        .await
+        .map(Json)
+        .map_err(to_http_error)
+}
+
+#[derive(Deserialize)]
+pub struct RemoveOrphanedResourcesRequest {
+    /// Orphaned volumes the user confirmed; other volumes are kept.
+    #[serde(default)]
+    volumes: Vec<String>,
+}
+
+pub async fn remove_orphaned_resources(
+    Json(payload): Json<RemoveOrphanedResourcesRequest>,
+) -> Result<Json<Vec<OrphanedResource>>, (StatusCode, String)> {
+    kittynode_core::packages::remove_orphaned_resources(&payload.volumes)
+        .await
+        .map(Json)
+        .map_err(to_http_error)
+}
+
 pub async fn is_docker_running() -> Result<StatusCode, (StatusCode, String)> {
     match kittynode_core::docker::is_docker_running().await {
         true => Ok(StatusCode::OK),
@@ -169,20 +311,51 @@ pub async fn is_validator_installed() -> Result<Json<bool>, (StatusCode, String)
         .map_err(to_http_error)
 }
 
+pub async fn get_node_health(
+    Query(params): Query<InstanceQuery>,
+) -> Result<Json<NodeHealth>, (StatusCode, String)> {
+    kittynode_core::ethereum::node_health(params.instance.as_deref())
+        .await
+        .map(Json)
+        .map_err(to_http_error)
+}
+
+/// Locates validator keys on the machine running the server.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorStatusRequest {
+    keys_path: PathBuf,
+}
+
+pub async fn get_validator_status(
+    Query(params): Query<InstanceQuery>,
+    Json(payload): Json<ValidatorStatusRequest>,
+) -> Result<Json<Vec<ValidatorStatus>>, (StatusCode, String)> {
+    kittynode_core::ethereum::validator_status(params.instance.as_deref(), &payload.keys_path)
+        .await
+        .map(Json)
+        .map_err(to_http_error)
+}
+
 pub async fn get_container_logs(
     Path(container_name): Path<String>,
     Query(params): Query<LogsQuery>,
 ) -> Result<Json<Vec<String>>, (StatusCode, String)> {
-    kittynode_core::docker::get_container_logs(&container_name, params.tail)
-        .await
-        .map(Json)
-        .map_err(to_http_error)
+    kittynode_core::docker::get_container_logs(
+        &container_name,
+        params.instance.as_deref(),
+        params.tail,
+    )
+    .await
+    .map(Json)
+    .map_err(to_http_error)
 }
 
 pub async fn get_package_config(
     Path(name): Path<String>,
+    Query(params): Query<InstanceQuery>,
 ) -> Result<Json<PackageConfig>, (StatusCode, String)> {
-    kittynode_core::packages::get_package_config(&name)
+    kittynode_core::packages::get_package_config(&name, params.instance.as_deref())
         .await
         .map(Json)
         .map_err(to_http_error)
@@ -190,14 +363,26 @@ pub async fn get_package_config(
 
 pub async fn update_package_config(
     Path(name): Path<String>,
+    Query(params): Query<InstanceQuery>,
     Json(config): Json<PackageConfig>,
 ) -> Result<StatusCode, (StatusCode, String)> {
-    kittynode_core::packages::update_package_config(&name, config)
+    kittynode_core::packages::update_package_config(&name, params.instance.as_deref(), config)
         .await
-        .map_err(to_http_error)?;
+        .map_err(to_config_http_error)?;
     Ok(StatusCode::OK)
 }
 
+pub async fn plan_update_package_config(
+    Path(name): Path<String>,
+    Query(params): Query<InstanceQuery>,
+    Json(config): Json<PackageConfig>,
+) -> Result<Json<Plan>, (StatusCode, String)> {
+    kittynode_core::packages::plan_update_package_config(&name, params.instance.as_deref(), &config)
+        .await
+        .map(Json)
+        .map_err(to_config_http_error)
+}
+
 pub async fn start_docker_if_needed() -> Result<Json<DockerStartStatus>, (StatusCode, String)> {
     kittynode_core::node::start_docker_if_needed()
         .await
@@ -231,20 +416,38 @@ pub fn app() -> Router {
         .route("/get_package_catalog", get(get_package_catalog))
         .route("/get_config", get(get_config))
         .route("/install_package/{name}", post(install_package))
+        .route(
+            "/install_package_stream/{name}",
+            post(install_package_stream),
+        )
+        .route("/plan_install_package/{name}", get(plan_install_package))
         .route("/delete_package/{name}", post(delete_package))
+        .route("/plan_delete_package/{name}", get(plan_delete_package))
+        .route("/upgrade_package/{name}", post(upgrade_package))
         .route("/stop_package/{name}", post(stop_package))
         .route("/start_package/{name}", post(start_package))
         .route("/get_installed_packages", get(get_installed_packages))
         .route("/get_packages", post(get_packages))
+        .route("/find_orphaned_resources", get(find_orphaned_resources))
+        .route(
+            "/remove_orphaned_resources",
+            post(remove_orphaned_resources),
+        )
         .route("/get_package/{name}", get(get_package))
         .route("/is_docker_running", get(is_docker_running))
         .route("/init_kittynode", post(init_kittynode))
         .route("/delete_kittynode", post(delete_kittynode))
         .route("/get_system_info", get(get_system_info))
         .route("/is_validator_installed", get(is_validator_installed))
+        .route("/get_node_health", get(get_node_health))
+        .route("/get_validator_status", post(get_validator_status))
         .route("/logs/{container_name}", get(get_container_logs))
         .route("/get_package_config/{name}", get(get_package_config))
         .route("/update_package_config/{name}", post(update_package_config))
+        .route(
+            "/plan_update_package_config/{name}",
+            post(plan_update_package_config),
+        )
         .route("/start_docker_if_needed", post(start_docker_if_needed))
         .route("/get_operational_state", get(get_operational_state))
 }
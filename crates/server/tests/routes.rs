@@ -285,4 +285,150 @@ async fn unknown_package_routes_map_to_404() {
         .await
         .expect("service call");
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let app = kittynode_server::app();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/upgrade_package/does-not-exist")
+                .body(Body::empty())
+                .expect("build request"),
+        )
+        .await
+        .expect("service call");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn node_health_without_a_configured_node_maps_to_404() {
+    let _home = TempHomeGuard::new();
+
+    let app = kittynode_server::app();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/get_node_health")
+                .body(Body::empty())
+                .expect("build request"),
+        )
+        .await
+        .expect("service call");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn validator_status_without_a_configured_node_maps_to_404() {
+    let _home = TempHomeGuard::new();
+
+    let app = kittynode_server::app();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/get_validator_status")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"keysPath":"/tmp/validator_keys"}"#))
+                .expect("build request"),
+        )
+        .await
+        .expect("service call");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn update_package_config_rejects_invalid_values_with_400() {
+    let _home = TempHomeGuard::new();
+
+    let app = kittynode_server::app();
+    let body = json!({ "values": { "network": "hoodi", "validator_enabled": "yes" } });
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/update_package_config/ethereum")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .expect("build request"),
+        )
+        .await
+        .expect("service call");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let text = response_text(response).await;
+    assert!(
+        text.contains("validator_enabled"),
+        "unexpected body: {text}"
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn install_package_stream_reports_errors_as_events() {
+    let _home = TempHomeGuard::new();
+
+    let app = kittynode_server::app();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/install_package_stream/does-not-exist")
+                .body(Body::empty())
+                .expect("build request"),
+        )
+        .await
+        .expect("service call");
+    assert_eq!(response.status(), StatusCode::OK);
+    let text = response_text(response).await;
+    assert!(text.contains("event: error"), "unexpected body: {text}");
+    assert!(text.contains("does-not-exist"), "unexpected body: {text}");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn plan_routes_describe_changes_without_applying_them() {
+    let _home = TempHomeGuard::new();
+
+    let app = kittynode_server::app();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/plan_delete_package/ethereum?include_images=true")
+                .body(Body::empty())
+                .expect("build request"),
+        )
+        .await
+        .expect("service call");
+    assert_eq!(response.status(), StatusCode::OK);
+    let json = json_response(response).await;
+    assert_eq!(json["package"], "ethereum");
+    assert_eq!(json["operation"], "delete");
+    assert_eq!(json["containers"].as_array().map(Vec::len), Some(0));
+
+    let app = kittynode_server::app();
+    let body = json!({ "values": { "network": "hoodi", "validator_enabled": "yes" } });
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/plan_update_package_config/ethereum")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .expect("build request"),
+        )
+        .await
+        .expect("service call");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let app = kittynode_server::app();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/plan_install_package/does-not-exist")
+                .body(Body::empty())
+                .expect("build request"),
+        )
+        .await
+        .expect("service call");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }